@@ -0,0 +1,22 @@
+# Planeta de lava escrito en el DSL de shaders.
+# Para probarlo, copiar a materials/<entidad>.shader (por ejemplo materials/mars.shader).
+
+fragment {
+    let p = normalize(obj_position);
+
+    # Corteza oscura con crestas
+    let crust = ridged(p * 3.0, 5);
+    let rock = vec3(0.12, 0.09, 0.08) * (0.5 + 0.8 * crust);
+
+    # Grietas de lava: valles del ruido celular, pulsando con el tiempo
+    let cells = worley(p * 6.0 + vec3(0.0, time * 0.05, 0.0));
+    let glow = (1.0 - smoothstep(0.05, 0.25, cells)) * (0.8 + 0.2 * sin(time * 2.0 + fbm(p * 8.0, 3) * 6.0));
+    let lava = mix(vec3(0.8, 0.15, 0.0), vec3(1.0, 0.75, 0.2), glow);
+
+    let lit = 0.08 + 0.92 * max(dot(normal, sun_dir), 0.0);
+    color = rock * lit + lava * glow;
+}
+
+vertex {
+    position = obj_position * (1.0 + 0.04 * ridged(normalize(obj_position) * 3.0, 5));
+}
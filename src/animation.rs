@@ -0,0 +1,165 @@
+// src/animation.rs
+// Curvas de animación (keyframes y LFOs) que mueven parámetros de material con el tiempo.
+use std::f32::consts::TAU;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Interp {
+    Step,
+    Linear,
+    Smooth, // smoothstep entre claves
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Waveform {
+    Sine,
+    Triangle,
+    Square,
+    Saw,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Keyframe {
+    pub time: f32,
+    pub value: f32,
+}
+
+#[derive(Clone, Debug)]
+pub enum Curve {
+    /// Claves ordenadas por tiempo; con `period` la curva se repite cada `period` segundos.
+    Keyframes { keys: Vec<Keyframe>, interp: Interp, period: Option<f32> },
+    /// Oscilador: `center + amplitude * wave(frequency * t + phase)`, con `wave` en [-1,1].
+    Lfo { wave: Waveform, center: f32, amplitude: f32, frequency: f32, phase: f32 },
+}
+
+impl Curve {
+    /// Builds a keyframe curve from `(time, value)` pairs (sorted here).
+    pub fn keyframes(keys: &[(f32, f32)], interp: Interp) -> Self {
+        let mut keys: Vec<Keyframe> = keys.iter().map(|&(time, value)| Keyframe { time, value }).collect();
+        keys.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Curve::Keyframes { keys, interp, period: None }
+    }
+
+    /// Makes a keyframe curve repeat every `period` seconds (no effect on LFOs).
+    pub fn looped(mut self, period: f32) -> Self {
+        if let Curve::Keyframes { period: p, .. } = &mut self {
+            *p = Some(period);
+        }
+        self
+    }
+
+    pub fn lfo(wave: Waveform, center: f32, amplitude: f32, frequency: f32) -> Self {
+        Curve::Lfo { wave, center, amplitude, frequency, phase: 0.0 }
+    }
+
+    /// Value of the curve at simulation time `t` (seconds).
+    pub fn sample(&self, t: f32) -> f32 {
+        match self {
+            Curve::Keyframes { keys, interp, period } => {
+                let t = match period {
+                    Some(p) if *p > 0.0 => t.rem_euclid(*p),
+                    _ => t,
+                };
+                sample_keys(keys, *interp, t)
+            }
+            Curve::Lfo { wave, center, amplitude, frequency, phase } => {
+                let x = (frequency * t + phase).rem_euclid(1.0);
+                let w = match wave {
+                    Waveform::Sine => (x * TAU).sin(),
+                    Waveform::Triangle => 1.0 - 4.0 * (x - 0.5).abs(),
+                    Waveform::Square => if x < 0.5 { 1.0 } else { -1.0 },
+                    Waveform::Saw => 2.0 * x - 1.0,
+                };
+                center + amplitude * w
+            }
+        }
+    }
+}
+
+fn sample_keys(keys: &[Keyframe], interp: Interp, t: f32) -> f32 {
+    let (first, last) = match (keys.first(), keys.last()) {
+        (Some(f), Some(l)) => (f, l),
+        _ => return 0.0,
+    };
+    if t <= first.time {
+        return first.value;
+    }
+    if t >= last.time {
+        return last.value;
+    }
+    // Primera clave posterior a t (existe porque t < last.time)
+    let i = keys.partition_point(|k| k.time <= t);
+    let (a, b) = (keys[i - 1], keys[i]);
+    let s = (t - a.time) / (b.time - a.time).max(1e-6);
+    let s = match interp {
+        Interp::Step => 0.0,
+        Interp::Linear => s,
+        Interp::Smooth => s * s * (3.0 - 2.0 * s),
+    };
+    a.value + (b.value - a.value) * s
+}
+
+/// A curve bound to a named parameter: a material field (see `FragmentShader::set_param`)
+/// or, if the shader has no such field, a custom uniform of the entity.
+#[derive(Clone, Debug)]
+pub struct ParamTrack {
+    pub param: String,
+    pub curve: Curve,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn keyframes_interpolate_clamp_and_loop() {
+        // Desordenadas a propósito: `keyframes` las ordena
+        let keys = [(2.0, 4.0), (0.0, 0.0), (1.0, 2.0)];
+        let linear = Curve::keyframes(&keys, Interp::Linear);
+        assert!(close(linear.sample(-1.0), 0.0) && close(linear.sample(5.0), 4.0));
+        assert!(close(linear.sample(0.25), 0.5) && close(linear.sample(1.5), 3.0));
+        assert!(close(linear.sample(1.0), 2.0));
+
+        let step = Curve::keyframes(&keys, Interp::Step);
+        assert!(close(step.sample(0.99), 0.0) && close(step.sample(1.0), 2.0) && close(step.sample(1.99), 2.0));
+
+        let smooth = Curve::keyframes(&keys, Interp::Smooth);
+        assert!(close(smooth.sample(0.5), 1.0));
+        assert!(smooth.sample(0.25) < linear.sample(0.25) && smooth.sample(0.75) > linear.sample(0.75));
+
+        let looped = Curve::keyframes(&keys, Interp::Linear).looped(2.0);
+        for t in [0.25, 1.5] {
+            assert!(close(looped.sample(t + 2.0), linear.sample(t)));
+            assert!(close(looped.sample(t - 4.0), linear.sample(t)));
+        }
+
+        assert_eq!(Curve::keyframes(&[], Interp::Linear).sample(1.0), 0.0);
+        assert_eq!(Curve::keyframes(&[(3.0, 7.0)], Interp::Smooth).sample(0.0), 7.0);
+    }
+
+    #[test]
+    fn lfo_waveforms_span_center_plus_minus_amplitude() {
+        let at = |wave, t| Curve::lfo(wave, 1.0, 0.5, 0.25).sample(t);
+        // Un ciclo dura 4 s: t = 0, 1, 2 y 3 son 0, ¼, ½ y ¾ de periodo
+        let expected = [
+            (Waveform::Sine, [1.0, 1.5, 1.0, 0.5]),
+            (Waveform::Triangle, [0.5, 1.0, 1.5, 1.0]),
+            (Waveform::Square, [1.5, 1.5, 0.5, 0.5]),
+            (Waveform::Saw, [0.5, 0.75, 1.0, 1.25]),
+        ];
+        for (wave, values) in expected {
+            for (t, value) in values.into_iter().enumerate() {
+                assert!(close(at(wave, t as f32), value), "{:?} at {}: {}", wave, t, at(wave, t as f32));
+                assert!(close(at(wave, t as f32 + 4.0), value) && close(at(wave, t as f32 - 8.0), value));
+            }
+        }
+
+        let shifted = Curve::Lfo { wave: Waveform::Saw, center: 0.0, amplitude: 1.0, frequency: 1.0, phase: 0.5 };
+        assert!(close(shifted.sample(0.0), 0.0));
+        // `looped` no cambia un LFO
+        assert!(close(Curve::lfo(Waveform::Saw, 0.0, 1.0, 1.0).looped(0.3).sample(0.6), 0.2));
+    }
+}
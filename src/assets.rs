@@ -0,0 +1,207 @@
+// src/assets.rs
+// Resolución de archivos de datos (mallas, texturas, materiales, modelos) sin depender del
+// directorio de trabajo, y malla de reemplazo cuando falta un recurso.
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use raylib::prelude::*;
+
+use crate::material::Material;
+use crate::mesh::Mesh;
+use crate::obj::{Obj, SubMesh};
+use crate::procedural::{generate_icosphere, sphere_normals};
+
+#[derive(Debug)]
+pub enum AssetError {
+    NotFound { file: String, searched: Vec<PathBuf> },
+    Load { file: PathBuf, cause: tobj::LoadError },
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssetError::NotFound { file, searched } => {
+                let dirs: Vec<String> = searched.iter().map(|d| d.display().to_string()).collect();
+                write!(f, "asset '{}' not found (searched: {})", file, dirs.join(", "))
+            }
+            AssetError::Load { file, cause } => write!(f, "failed to load '{}': {}", file.display(), cause),
+        }
+    }
+}
+
+impl std::error::Error for AssetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AssetError::NotFound { .. } => None,
+            AssetError::Load { cause, .. } => Some(cause),
+        }
+    }
+}
+
+/// Ordered list of directories where relative asset names are looked up.
+#[derive(Clone, Debug)]
+pub struct AssetPaths {
+    roots: Vec<PathBuf>,
+}
+
+/// Niveles que se suben desde el ejecutable: `target/release/` → raíz del proyecto
+const EXE_ANCESTORS: usize = 2;
+
+impl AssetPaths {
+    /// Working directory, then the executable's directory and its parents (so `cargo run` and
+    /// launching `target/release/sistemasolar` from anywhere both find the repo's assets).
+    pub fn new() -> Self {
+        let mut paths = AssetPaths { roots: Vec::new() };
+        if let Ok(cwd) = std::env::current_dir() {
+            paths.push(cwd);
+        }
+        if let Some(exe_dir) = std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)) {
+            for dir in exe_dir.ancestors().take(EXE_ANCESTORS + 1) {
+                paths.push(dir.to_path_buf());
+            }
+        }
+        paths
+    }
+
+    /// Searches the directory of `scene_file` before everything else.
+    pub fn with_scene(mut self, scene_file: &Path) -> Self {
+        let dir = scene_file.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        self.roots.retain(|r| *r != dir);
+        self.roots.insert(0, dir);
+        self
+    }
+
+    fn push(&mut self, dir: PathBuf) {
+        let dir = dir.canonicalize().unwrap_or(dir);
+        if !self.roots.contains(&dir) {
+            self.roots.push(dir);
+        }
+    }
+
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// First existing `root/relative`; absolute paths are only checked for existence.
+    pub fn resolve(&self, relative: &str) -> Result<PathBuf, AssetError> {
+        let path = Path::new(relative);
+        if path.is_absolute() {
+            return if path.exists() {
+                Ok(path.to_path_buf())
+            } else {
+                Err(AssetError::NotFound { file: relative.to_string(), searched: Vec::new() })
+            };
+        }
+        self.roots
+            .iter()
+            .map(|root| root.join(path))
+            .find(|candidate| candidate.exists())
+            .ok_or_else(|| AssetError::NotFound { file: relative.to_string(), searched: self.roots.clone() })
+    }
+
+    /// Like `resolve`, for optional assets: `None` when it simply is not there.
+    pub fn find(&self, relative: &str) -> Option<PathBuf> {
+        self.resolve(relative).ok()
+    }
+
+    pub fn load_obj(&self, relative: &str) -> Result<Obj, AssetError> {
+        let file = self.resolve(relative)?;
+        Obj::load(&file.to_string_lossy()).map_err(|cause| AssetError::Load { file, cause })
+    }
+
+    /// `load_obj`, or the placeholder mesh (after reporting why) so a missing model never stops
+    /// the program.
+    pub fn load_obj_or_placeholder(&self, relative: &str) -> Obj {
+        self.load_obj(relative).unwrap_or_else(|e| {
+            eprintln!("{}; using a placeholder mesh", e);
+            placeholder_obj()
+        })
+    }
+}
+
+impl Default for AssetPaths {
+    fn default() -> Self {
+        AssetPaths::new()
+    }
+}
+
+/// Built-in stand-in for a missing model: a small icosphere in "missing asset" magenta.
+pub fn placeholder_obj() -> Obj {
+    let vertices = generate_icosphere(1.5, 1);
+    let mesh = Mesh::from_soup(&vertices, &sphere_normals(&vertices), &[]);
+    let material = Material {
+        name: String::from("placeholder"),
+        albedo: Vector3::new(1.0, 0.0, 1.0),
+        roughness: 0.6,
+        ..Material::default()
+    };
+    Obj {
+        submeshes: vec![SubMesh { name: String::from("placeholder"), mesh, material: Some(0) }],
+        materials: vec![material],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+    use std::fs;
+
+    /// Empty directory of its own per test under the system temp dir.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sistemasolar-assets-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.canonicalize().unwrap()
+    }
+
+    #[test]
+    fn roots_start_at_the_scene_then_cwd_then_exe_ancestors() {
+        let scene = scratch_dir("order");
+        let paths = AssetPaths::new().with_scene(&scene.join("scene.json"));
+        let canonical = |d: &Path| d.canonicalize().unwrap();
+
+        let mut expected = vec![scene.clone(), canonical(&std::env::current_dir().unwrap())];
+        let exe_dir = std::env::current_exe().unwrap().parent().unwrap().to_path_buf();
+        for dir in exe_dir.ancestors().take(EXE_ANCESTORS + 1).map(canonical) {
+            if !expected.contains(&dir) {
+                expected.push(dir);
+            }
+        }
+        let roots = paths.roots();
+        assert_eq!(roots, expected);
+
+        // Abrir la misma escena otra vez no duplica su directorio
+        assert_eq!(paths.clone().with_scene(&scene.join("other.json")).roots(), roots);
+        let _ = fs::remove_dir_all(&scene);
+    }
+
+    #[test]
+    fn resolve_takes_the_first_root_holding_the_file() {
+        let (a, b) = (scratch_dir("first"), scratch_dir("second"));
+        fs::write(b.join("both.txt"), "b").unwrap();
+        fs::write(b.join("only_b.txt"), "b").unwrap();
+        let paths = AssetPaths { roots: vec![a.clone(), b.clone()] };
+        assert_eq!(paths.resolve("both.txt").unwrap(), b.join("both.txt"));
+
+        fs::write(a.join("both.txt"), "a").unwrap();
+        assert_eq!(paths.resolve("both.txt").unwrap(), a.join("both.txt"));
+        assert_eq!(paths.resolve("only_b.txt").unwrap(), b.join("only_b.txt"));
+        assert_eq!(paths.find(&b.join("only_b.txt").to_string_lossy()), Some(b.join("only_b.txt")));
+
+        let err = paths.resolve("missing.obj").unwrap_err();
+        assert!(matches!(&err, AssetError::NotFound { file, searched } if file == "missing.obj" && *searched == [a.clone(), b.clone()]));
+        let message = err.to_string();
+        assert!(message.contains(&a.display().to_string()) && message.contains(&b.display().to_string()), "{}", message);
+        assert!(err.source().is_none());
+
+        fs::write(a.join("broken.obj"), "f 1 2 3\n").unwrap();
+        let err = paths.load_obj("broken.obj").err().expect("a face without vertices must not load");
+        assert!(matches!(&err, AssetError::Load { file, .. } if *file == a.join("broken.obj")));
+        assert!(err.source().is_some_and(|cause| cause.is::<tobj::LoadError>()));
+
+        let _ = fs::remove_dir_all(&a);
+        let _ = fs::remove_dir_all(&b);
+    }
+}
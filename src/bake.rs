@@ -0,0 +1,238 @@
+// src/bake.rs
+// Horneado de la parte estática de los shaders procedurales (cráteres, manchas) a un mapa
+// equirectangular, con caché en disco indexada por un hash de los parámetros.
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use raylib::prelude::*;
+
+use crate::simd::{self, F32x4, Vec3x4};
+use crate::shaders::{rocky_albedo, rocky_height, strips_spots, FragmentShader};
+use crate::texture::Texture;
+
+/// Cambiar cuando se modifique la parte estática de algún shader para invalidar la caché.
+const BAKE_VERSION: u32 = 3;
+const MAGIC: &[u8; 4] = b"SSBK";
+
+/// Equirectangular UV for a unit direction, matching `generate_uv_sphere_uvs`.
+pub fn equirect_uv(p: Vector3) -> Vector2 {
+    let theta = p.z.atan2(p.x);
+    let u = theta / std::f32::consts::TAU;
+    let v = p.y.clamp(-1.0, 1.0).acos() / std::f32::consts::PI;
+    Vector2::new(u - u.floor(), v)
+}
+
+/// `equirect_uv` for 4 unit directions, with the vectorized (approximate) `atan2`/`acos`.
+pub fn equirect_uv_x4(p: Vec3x4) -> (F32x4, F32x4) {
+    let u = simd::atan2(p.z, p.x) / std::f32::consts::TAU;
+    let v = simd::acos(p.y) / std::f32::consts::PI;
+    (u - u.floor(), v)
+}
+
+/// `equirect_uv` at the three corners of a triangle (object-space positions, any radius), with
+/// `u` unwrapped across the seam so the triangle's UV area, and with it the mip level picked
+/// for the baked map, stays that of the surface it covers.
+pub fn equirect_triangle_uvs(corners: [Vector3; 3]) -> [Vector2; 3] {
+    let mut uvs = corners.map(|p| equirect_uv(if p.length() > 0.0 { p.normalized() } else { p }));
+    for i in 1..3 {
+        let du = uvs[i].x - uvs[0].x;
+        uvs[i].x -= du.round();
+    }
+    uvs
+}
+
+/// Unit direction at the center of texel (x, y) of a `width`x`height` equirectangular map.
+fn texel_dir(x: usize, y: usize, width: usize, height: usize) -> Vector3 {
+    let theta = (x as f32 + 0.5) / width as f32 * std::f32::consts::TAU;
+    let phi = (y as f32 + 0.5) / height as f32 * std::f32::consts::PI;
+    let sin_phi = phi.sin();
+    Vector3::new(sin_phi * theta.cos(), phi.cos(), sin_phi * theta.sin())
+}
+
+/// Static layer of a bakeable shader at direction `p`, or None if the shader has nothing to bake.
+fn static_layer(shader: &FragmentShader, p: Vector3) -> Option<Vector4> {
+    match shader {
+        FragmentShader::Rocky { color } => {
+            // Albedo en RGB y altura (para el bump mapping) en A
+            let a = rocky_albedo(p, *color);
+            Some(Vector4::new(a.x, a.y, a.z, rocky_height(p)))
+        }
+        FragmentShader::Strips { .. } => Some(strips_spots(p)),
+        _ => None,
+    }
+}
+
+/// FNV-1a over the shader parameters; stable across runs and compiler versions.
+fn bake_key(shader: &FragmentShader, width: usize, height: usize) -> Option<u64> {
+    versioned_key(BAKE_VERSION, shader, width, height)
+}
+
+fn versioned_key(version: u32, shader: &FragmentShader, width: usize, height: usize) -> Option<u64> {
+    let mut words: Vec<u32> = vec![version, width as u32, height as u32];
+    match shader {
+        FragmentShader::Rocky { color } => {
+            words.extend([1, color.x.to_bits(), color.y.to_bits(), color.z.to_bits()]);
+        }
+        FragmentShader::Strips { angle } => {
+            words.extend([2, angle.to_bits()]);
+        }
+        _ => return None,
+    }
+
+    let mut h: u64 = 0xcbf29ce484222325;
+    for w in words {
+        for b in w.to_le_bytes() {
+            h ^= b as u64;
+            h = h.wrapping_mul(0x100000001b3);
+        }
+    }
+    Some(h)
+}
+
+/// Renders the static part of `shader` into an equirectangular texture.
+pub fn bake(shader: &FragmentShader, width: usize, height: usize) -> Option<Texture> {
+    static_layer(shader, Vector3::new(0.0, 1.0, 0.0))?;
+    let mut texels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            let p = texel_dir(x, y, width, height);
+            texels.push(static_layer(shader, p).unwrap_or(Vector4::new(0.0, 0.0, 0.0, 0.0)));
+        }
+    }
+    Some(Texture::from_texels(width, height, texels))
+}
+
+fn cache_path(cache_dir: &Path, key: u64, width: usize, height: usize) -> PathBuf {
+    cache_dir.join(format!("bake_{:016x}_{}x{}.bin", key, width, height))
+}
+
+fn read_cache(path: &Path, width: usize, height: usize) -> io::Result<Texture> {
+    let mut bytes = Vec::new();
+    fs::File::open(path)?.read_to_end(&mut bytes)?;
+    let header = 12;
+    if bytes.len() != header + width * height * 4 || &bytes[0..4] != MAGIC {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "bad bake cache file"));
+    }
+    let w = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as usize;
+    let h = u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize;
+    if w != width || h != height {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "bake cache size mismatch"));
+    }
+    let texels = bytes[header..]
+        .chunks_exact(4)
+        .map(|c| Vector4::new(c[0] as f32 / 255.0, c[1] as f32 / 255.0, c[2] as f32 / 255.0, c[3] as f32 / 255.0))
+        .collect();
+    Ok(Texture::from_texels(width, height, texels))
+}
+
+fn write_cache(path: &Path, texture: &Texture) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let level = &texture.levels[0];
+    let mut bytes = Vec::with_capacity(12 + level.texels.len() * 4);
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&(level.width as u32).to_le_bytes());
+    bytes.extend_from_slice(&(level.height as u32).to_le_bytes());
+    for t in &level.texels {
+        for c in [t.x, t.y, t.z, t.w] {
+            bytes.push((c.clamp(0.0, 1.0) * 255.0).round() as u8);
+        }
+    }
+    fs::File::create(path)?.write_all(&bytes)
+}
+
+/// Returns a `FragmentShader::Baked` wrapping `shader`, loading the map from `cache_dir` when a
+/// bake with the same parameters and resolution exists, or baking and storing it otherwise.
+/// Returns None for shaders without a static layer.
+pub fn bake_cached(shader: &FragmentShader, width: usize, height: usize, cache_dir: &Path) -> Option<FragmentShader> {
+    let key = bake_key(shader, width, height)?;
+    let path = cache_path(cache_dir, key, width, height);
+
+    let map = match read_cache(&path, width, height) {
+        Ok(texture) => texture,
+        Err(_) => {
+            let texture = bake(shader, width, height)?;
+            if let Err(e) = write_cache(&path, &texture) {
+                eprintln!("bake: could not write {}: {}", path.display(), e);
+            }
+            texture
+        }
+    };
+
+    Some(FragmentShader::Baked { source: Box::new(shader.clone()), map: Arc::new(map) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn triangle_uvs_unwrap_across_the_seam() {
+        // A ambos lados de la costura (u = 0 / 1), sobre el ecuador, a radio 2
+        let at = |theta: f32| Vector3::new(2.0 * theta.cos(), 0.0, 2.0 * theta.sin());
+        let uvs = equirect_triangle_uvs([at(-0.1), at(0.1), Vector3::new(0.0, 2.0, 0.0) + at(0.0) * 0.1]);
+        let width = (uvs[1].x - uvs[0].x).abs();
+        assert!((width - 0.2 / std::f32::consts::TAU).abs() < 1e-5, "u span {}", width);
+        assert!((uvs[0].y - 0.5).abs() < 1e-5);
+
+        // Lejos de la costura no cambia nada
+        let plain = equirect_triangle_uvs([at(1.0), at(1.2), at(1.1)]);
+        assert_eq!(plain[0], equirect_uv(at(1.0).normalized()));
+    }
+
+    // Carpeta propia por prueba para no pisar `cache/` ni otras pruebas en paralelo
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sistemasolar_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn rocky(r: f32) -> FragmentShader {
+        FragmentShader::Rocky { color: Vector3::new(r, 0.4, 0.3) }
+    }
+
+    #[test]
+    fn key_changes_with_params_size_and_version() {
+        let key = |shader: &FragmentShader| bake_key(shader, 8, 4).unwrap();
+        assert_eq!(key(&rocky(0.5)), key(&rocky(0.5)));
+        assert_ne!(key(&rocky(0.5)), key(&rocky(0.6)));
+        assert_ne!(key(&rocky(0.5)), key(&FragmentShader::Strips { angle: 0.5 }));
+        assert_ne!(key(&rocky(0.5)), bake_key(&rocky(0.5), 16, 8).unwrap());
+        assert_ne!(key(&rocky(0.5)), versioned_key(BAKE_VERSION + 1, &rocky(0.5), 8, 4).unwrap());
+        assert_eq!(bake_key(&FragmentShader::Star, 8, 4), None);
+    }
+
+    #[test]
+    fn cache_round_trip() {
+        let dir = scratch_dir("bake_cache");
+        let texels: Vec<Vector4> = (0..8 * 4).map(|i| Vector4::new(i as f32 / 31.0, 0.25, 1.0, 0.5)).collect();
+        let path = cache_path(&dir, 0x1234, 8, 4);
+        write_cache(&path, &Texture::from_texels(8, 4, texels.clone())).unwrap();
+
+        let back = read_cache(&path, 8, 4).unwrap();
+        for (a, b) in back.levels[0].texels.iter().zip(&texels) {
+            assert!((a.x - b.x).abs() < 0.51 / 255.0 && (a.w - b.w).abs() < 0.51 / 255.0);
+        }
+        assert!(read_cache(&path, 16, 2).is_err());
+
+        // La primera vez se hornea y se guarda; la segunda se lee lo guardado (aquí, una
+        // textura distinta escrita a mano en su lugar)
+        let shader = rocky(0.5);
+        let cached = cache_path(&dir, bake_key(&shader, 8, 4).unwrap(), 8, 4);
+        let Some(FragmentShader::Baked { map, .. }) = bake_cached(&shader, 8, 4, &dir) else { panic!("Rocky is bakeable") };
+        assert!(cached.exists());
+        assert_eq!(map.levels[0].texels.len(), 32);
+
+        write_cache(&cached, &Texture::from_texels(8, 4, texels)).unwrap();
+        let Some(FragmentShader::Baked { map, .. }) = bake_cached(&shader, 8, 4, &dir) else { panic!("Rocky is bakeable") };
+        assert!((map.levels[0].texels[31].x - 1.0).abs() < 1e-6);
+
+        // Otro parámetro, otro archivo
+        bake_cached(&rocky(0.6), 8, 4, &dir).unwrap();
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 3);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,90 @@
+// src/bench.rs
+// Benchmark del sombreado por paquetes frente al escalar (`cargo run --release -- --bench-shaders`).
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use raylib::prelude::*;
+
+use crate::bake::bake;
+use crate::fragment::Fragment;
+use crate::shaders::{fragment_shader, fragment_shader_batch, FragmentShader};
+use crate::matrix::create_model_matrix;
+use crate::uniforms::{ShaderParams, Uniforms};
+
+const FRAGMENTS: usize = 200_000;
+const RUNS: usize = 5;
+
+/// Fragments spread over the unit sphere (Fibonacci lattice), like a planet filling the screen.
+fn sphere_fragments(count: usize) -> Vec<Fragment> {
+    let golden = std::f32::consts::PI * (3.0 - 5.0f32.sqrt());
+    (0..count)
+        .map(|i| {
+            let y = 1.0 - 2.0 * (i as f32 + 0.5) / count as f32;
+            let r = (1.0 - y * y).sqrt();
+            let theta = golden * i as f32;
+            let p = Vector3::new(r * theta.cos(), y, r * theta.sin());
+            // Huella UV de un planeta de ~500 px: el mapa horneado se lee en su nivel 0
+            Fragment::new((i % 1300) as f32, (i / 1300) as f32, Vector3::new(1.0, 1.0, 1.0), 0.5, p).with_uv(Vector2::new(0.0, 0.0), -9.0)
+        })
+        .collect()
+}
+
+/// Best of `RUNS` to keep the numbers stable on a busy machine.
+fn best_of(mut f: impl FnMut()) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+pub fn run_shader_bench() {
+    let fragments = sphere_fragments(FRAGMENTS);
+    let uniforms = Uniforms {
+        time: 3.7,
+        resolution: Vector2::new(1300.0, 600.0),
+        world: create_model_matrix(Vector3::new(0.0, 0.0, 0.0), 1.0, Vector3::new(0.0, 0.0, 0.0)),
+        sun_dir: Vector3::new(0.6, 0.3, 0.74).normalized(),
+        camera_pos: Vector3::new(0.0, 0.0, 4.0),
+        lights: Vec::new(),
+        params: &ShaderParams::default(),
+    };
+
+    // Rocky procedural y horneado (como lo dibujan los planetas en tiempo real)
+    let rocky = FragmentShader::Rocky { color: Vector3::new(0.55, 0.48, 0.4) };
+    let baked = FragmentShader::Baked { source: Box::new(rocky.clone()), map: Arc::new(bake(&rocky, 512, 256).expect("Rocky has a static layer")) };
+    let shaders = [("Star", FragmentShader::Star), ("Rocky", rocky), ("Baked Rocky", baked)];
+
+    println!("{} fragments, best of {} runs", FRAGMENTS, RUNS);
+    for (name, shader) in &shaders {
+        let mut scalar = Vec::with_capacity(FRAGMENTS);
+        let t_scalar = best_of(|| {
+            scalar.clear();
+            scalar.extend(fragments.iter().map(|f| fragment_shader(f, &uniforms, shader)));
+        });
+
+        let mut batched = Vec::with_capacity(FRAGMENTS);
+        let t_batched = best_of(|| {
+            batched.clear();
+            fragment_shader_batch(&fragments, &uniforms, shader, &mut batched);
+        });
+
+        let max_diff = scalar
+            .iter()
+            .zip(&batched)
+            .map(|(a, b)| (a.x - b.x).abs().max((a.y - b.y).abs()).max((a.z - b.z).abs()))
+            .fold(0.0f32, f32::max);
+
+        println!(
+            "{:<11} scalar {:>8.2} ms  batched {:>8.2} ms  speedup {:.2}x  max diff {:e}",
+            name,
+            t_scalar.as_secs_f64() * 1e3,
+            t_batched.as_secs_f64() * 1e3,
+            t_scalar.as_secs_f64() / t_batched.as_secs_f64().max(1e-9),
+            max_diff,
+        );
+    }
+}
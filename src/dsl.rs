@@ -0,0 +1,745 @@
+// src/dsl.rs
+// Lenguaje de expresiones para prototipar shaders sin tocar Rust. Un archivo de material tiene
+// bloques `fragment { … }` y/o `vertex { … }`; se compila al cargar a un árbol de closures
+// tipado (float / vec3), así que los errores de tipos también salen con línea y columna.
+//
+//     fragment {
+//         let h = fbm(obj_position * 4.0, 5);
+//         let lit = max(dot(normal, sun_dir), 0.0);
+//         color = mix(vec3(0.3, 0.1, 0.05), vec3(1.0, 0.5, 0.1), h) * (0.1 + 0.9 * lit);
+//     }
+//     vertex {
+//         position = obj_position * (1.0 + 0.05 * noise(obj_position * 3.0 + time));
+//     }
+use std::fmt;
+use std::path::Path;
+
+use raylib::prelude::*;
+
+use crate::shaders::{fbm, ridged_fbm, value_noise3, worley3};
+
+#[derive(Debug)]
+pub enum DslError {
+    Io { path: String, message: String },
+    Syntax { line: usize, col: usize, message: String },
+    Type { line: usize, col: usize, message: String }, // float donde va vec3, aridad, `.x` de un float…
+}
+
+impl fmt::Display for DslError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DslError::Io { path, message } => write!(f, "failed to read shader '{}': {}", path, message),
+            DslError::Syntax { line, col, message } => write!(f, "{}:{}: {}", line, col, message),
+            DslError::Type { line, col, message } => write!(f, "{}:{}: type error: {}", line, col, message),
+        }
+    }
+}
+
+impl std::error::Error for DslError {}
+
+// --- Tokens ---
+
+#[derive(Clone, Debug, PartialEq)]
+enum Tok {
+    Num(f32),
+    Ident(String),
+    Punct(char),
+    Eof,
+}
+
+#[derive(Clone, Debug)]
+struct Token {
+    tok: Tok,
+    line: usize,
+    col: usize,
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>, DslError> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let (mut i, mut line, mut col) = (0, 1, 1);
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = (line, col);
+        if c == '\n' {
+            i += 1;
+            line += 1;
+            col = 1;
+            continue;
+        }
+        if c.is_whitespace() {
+            i += 1;
+            col += 1;
+            continue;
+        }
+        // Comentarios: `#` o `//` hasta el fin de línea
+        if c == '#' || (c == '/' && chars.get(i + 1) == Some(&'/')) {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        }
+        if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit())) {
+            let begin = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            // Exponente (`1e-3`, `2.5E4`): solo si le sigue un dígito, con signo opcional
+            if matches!(chars.get(i), Some('e' | 'E')) {
+                let digits_at = if matches!(chars.get(i + 1), Some('+' | '-')) { i + 2 } else { i + 1 };
+                if chars.get(digits_at).is_some_and(|d| d.is_ascii_digit()) {
+                    i = digits_at;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let text: String = chars[begin..i].iter().collect();
+            col += i - begin;
+            let value = text.parse::<f32>().map_err(|_| DslError::Syntax {
+                line: start.0,
+                col: start.1,
+                message: format!("invalid number '{}'", text),
+            })?;
+            tokens.push(Token { tok: Tok::Num(value), line: start.0, col: start.1 });
+            continue;
+        }
+        if c.is_alphabetic() || c == '_' {
+            let begin = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            col += i - begin;
+            tokens.push(Token { tok: Tok::Ident(chars[begin..i].iter().collect()), line: start.0, col: start.1 });
+            continue;
+        }
+        if "+-*/(),;={}.".contains(c) {
+            i += 1;
+            col += 1;
+            tokens.push(Token { tok: Tok::Punct(c), line: start.0, col: start.1 });
+            continue;
+        }
+        return Err(DslError::Syntax { line, col, message: format!("unexpected character '{}'", c) });
+    }
+    tokens.push(Token { tok: Tok::Eof, line, col });
+    Ok(tokens)
+}
+
+// --- Evaluación ---
+
+/// Entradas de una evaluación más las variables `let` (los floats van en `.x`).
+struct Frame {
+    time: f32,
+    obj_position: Vector3,
+    normal: Vector3,
+    sun_dir: Vector3,
+    slots: [Vector3; MAX_SLOTS],
+}
+
+/// Variables por bloque (la salida más los `let`): caben en un arreglo fijo en la pila, así
+/// evaluar un fragmento no reserva memoria
+const MAX_SLOTS: usize = 32;
+
+type FnF = Box<dyn Fn(&Frame) -> f32 + Send + Sync>;
+type FnV = Box<dyn Fn(&Frame) -> Vector3 + Send + Sync>;
+type Stmt = Box<dyn Fn(&mut Frame) + Send + Sync>;
+
+enum Node {
+    F(FnF),
+    V(FnV),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Ty {
+    Float,
+    Vec3,
+}
+
+impl Node {
+    fn ty(&self) -> Ty {
+        match self {
+            Node::F(_) => Ty::Float,
+            Node::V(_) => Ty::Vec3,
+        }
+    }
+
+    /// Promotes a float to a vec3 by splatting it.
+    fn into_vec(self) -> FnV {
+        match self {
+            Node::V(f) => f,
+            Node::F(f) => Box::new(move |fr| {
+                let v = f(fr);
+                Vector3::new(v, v, v)
+            }),
+        }
+    }
+}
+
+#[inline]
+fn zip3(a: Vector3, b: Vector3, f: impl Fn(f32, f32) -> f32) -> Vector3 {
+    Vector3::new(f(a.x, b.x), f(a.y, b.y), f(a.z, b.z))
+}
+
+/// One compiled block (`fragment` or `vertex`).
+pub struct Program {
+    stmts: Vec<Stmt>,
+    output: usize,
+    uses_time: bool,
+}
+
+impl Program {
+    fn run(&self, mut frame: Frame) -> Vector3 {
+        for stmt in &self.stmts {
+            stmt(&mut frame);
+        }
+        frame.slots[self.output]
+    }
+
+    pub fn eval(&self, time: f32, obj_position: Vector3, normal: Vector3, sun_dir: Vector3) -> Vector3 {
+        self.run(Frame { time, obj_position, normal, sun_dir, slots: [Vector3::new(0.0, 0.0, 0.0); MAX_SLOTS] })
+    }
+
+    /// Whether the block reads `time` (otherwise its result depends only on the vertex/fragment).
+    pub fn uses_time(&self) -> bool {
+        self.uses_time
+    }
+}
+
+/// Shaders compiled from one material file; either block may be missing.
+pub struct ShaderSource {
+    pub fragment: Option<Program>,
+    pub vertex: Option<Program>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Stage {
+    Fragment,
+    Vertex,
+}
+
+// --- Parser + compilador ---
+
+struct Compiler {
+    tokens: Vec<Token>,
+    pos: usize,
+    stage: Stage,
+    locals: Vec<(String, Ty, usize)>,
+    slot_count: usize,
+    uses_time: bool,
+}
+
+impl Compiler {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> Token {
+        let t = self.tokens[self.pos].clone();
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        t
+    }
+
+    fn error<T>(&self, at: &Token, message: impl Into<String>) -> Result<T, DslError> {
+        Err(DslError::Syntax { line: at.line, col: at.col, message: message.into() })
+    }
+
+    fn type_error<T>(&self, at: &Token, message: impl Into<String>) -> Result<T, DslError> {
+        Err(DslError::Type { line: at.line, col: at.col, message: message.into() })
+    }
+
+    fn is_punct(&self, c: char) -> bool {
+        self.peek().tok == Tok::Punct(c)
+    }
+
+    fn expect(&mut self, c: char) -> Result<Token, DslError> {
+        let t = self.next();
+        if t.tok != Tok::Punct(c) {
+            return self.error(&t, format!("expected '{}'", c));
+        }
+        Ok(t)
+    }
+
+    fn ident(&mut self) -> Result<(String, Token), DslError> {
+        let t = self.next();
+        match &t.tok {
+            Tok::Ident(name) => Ok((name.clone(), t.clone())),
+            _ => self.error(&t, "expected a name"),
+        }
+    }
+
+    fn new_slot(&mut self, at: &Token) -> Result<usize, DslError> {
+        if self.slot_count == MAX_SLOTS {
+            return self.error(at, format!("too many variables in one block (at most {})", MAX_SLOTS - 1));
+        }
+        self.slot_count += 1;
+        Ok(self.slot_count - 1)
+    }
+
+    /// `{ stmt* }` for one stage; the output variable is `color` or `position`.
+    fn block(&mut self, block_tok: &Token) -> Result<Program, DslError> {
+        self.locals.clear();
+        self.slot_count = 0;
+        self.uses_time = false;
+        let output_name = if self.stage == Stage::Fragment { "color" } else { "position" };
+        let output = self.new_slot(block_tok)?;
+        let mut assigned = false;
+        let mut stmts: Vec<Stmt> = Vec::new();
+
+        self.expect('{')?;
+        while !self.is_punct('}') {
+            if self.peek().tok == Tok::Eof {
+                let t = self.peek().clone();
+                return self.error(&t, "missing '}'");
+            }
+            let (name, name_tok) = self.ident()?;
+            let (target, is_let) = if name == "let" {
+                (self.ident()?, true)
+            } else {
+                ((name, name_tok), false)
+            };
+            self.expect('=')?;
+            let value = self.expr()?;
+            self.expect(';')?;
+
+            let (target_name, target_tok) = target;
+            if is_let {
+                if self.input(&target_name).is_some() {
+                    return self.error(&target_tok, format!("'{}' is a built-in input", target_name));
+                }
+                let ty = value.ty();
+                let slot = self.new_slot(&target_tok)?;
+                self.locals.retain(|(n, _, _)| *n != target_name);
+                self.locals.push((target_name, ty, slot));
+                stmts.push(match value {
+                    Node::F(f) => Box::new(move |fr: &mut Frame| {
+                        let v = f(fr);
+                        fr.slots[slot] = Vector3::new(v, 0.0, 0.0);
+                    }),
+                    Node::V(f) => Box::new(move |fr: &mut Frame| {
+                        let v = f(fr);
+                        fr.slots[slot] = v;
+                    }),
+                });
+            } else if target_name == output_name {
+                let f = value.into_vec();
+                stmts.push(Box::new(move |fr: &mut Frame| {
+                    let v = f(fr);
+                    fr.slots[output] = v;
+                }));
+                assigned = true;
+            } else {
+                return self.error(
+                    &target_tok,
+                    format!("cannot assign to '{}' (use 'let' or assign '{}')", target_name, output_name),
+                );
+            }
+        }
+        self.expect('}')?;
+
+        if !assigned {
+            return self.error(block_tok, format!("block never assigns '{}'", output_name));
+        }
+        Ok(Program { stmts, output, uses_time: self.uses_time })
+    }
+
+    fn input(&self, name: &str) -> Option<Node> {
+        Some(match name {
+            "time" => Node::F(Box::new(|fr| fr.time)),
+            "obj_position" => Node::V(Box::new(|fr| fr.obj_position)),
+            "normal" => Node::V(Box::new(|fr| fr.normal)),
+            "sun_dir" => Node::V(Box::new(|fr| fr.sun_dir)),
+            "pi" => Node::F(Box::new(|_| std::f32::consts::PI)),
+            _ => return None,
+        })
+    }
+
+    fn expr(&mut self) -> Result<Node, DslError> {
+        let mut lhs = self.term()?;
+        while self.is_punct('+') || self.is_punct('-') {
+            let op: fn(f32, f32) -> f32 = if self.next().tok == Tok::Punct('+') { |a, b| a + b } else { |a, b| a - b };
+            let rhs = self.term()?;
+            lhs = map2(lhs, rhs, op);
+        }
+        Ok(lhs)
+    }
+
+    fn term(&mut self) -> Result<Node, DslError> {
+        let mut lhs = self.unary()?;
+        while self.is_punct('*') || self.is_punct('/') {
+            let op: fn(f32, f32) -> f32 = if self.next().tok == Tok::Punct('*') { |a, b| a * b } else { |a, b| a / b };
+            let rhs = self.unary()?;
+            lhs = map2(lhs, rhs, op);
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Node, DslError> {
+        if self.is_punct('-') {
+            self.next();
+            return Ok(match self.unary()? {
+                Node::F(f) => Node::F(Box::new(move |fr| -f(fr))),
+                Node::V(f) => Node::V(Box::new(move |fr| -f(fr))),
+            });
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<Node, DslError> {
+        let mut node = self.primary()?;
+        while self.is_punct('.') {
+            self.next();
+            let (field, tok) = self.ident()?;
+            let f = match node {
+                Node::V(f) => f,
+                Node::F(_) => return self.type_error(&tok, format!("cannot take '.{}' of a float", field)),
+            };
+            node = match field.as_str() {
+                "x" | "r" => Node::F(Box::new(move |fr| f(fr).x)),
+                "y" | "g" => Node::F(Box::new(move |fr| f(fr).y)),
+                "z" | "b" => Node::F(Box::new(move |fr| f(fr).z)),
+                _ => return self.error(&tok, format!("unknown component '{}' (use x/y/z or r/g/b)", field)),
+            };
+        }
+        Ok(node)
+    }
+
+    fn primary(&mut self) -> Result<Node, DslError> {
+        let t = self.next();
+        match &t.tok {
+            Tok::Num(v) => {
+                let v = *v;
+                Ok(Node::F(Box::new(move |_| v)))
+            }
+            Tok::Punct('(') => {
+                let e = self.expr()?;
+                self.expect(')')?;
+                Ok(e)
+            }
+            Tok::Ident(name) => {
+                let name = name.clone();
+                if self.is_punct('(') {
+                    self.next();
+                    let mut args = Vec::new();
+                    if !self.is_punct(')') {
+                        loop {
+                            args.push(self.expr()?);
+                            if !self.is_punct(',') {
+                                break;
+                            }
+                            self.next();
+                        }
+                    }
+                    self.expect(')')?;
+                    return self.call(&t, &name, args);
+                }
+                if self.stage == Stage::Vertex && name == "sun_dir" {
+                    return self.error(&t, "'sun_dir' is only available in fragment shaders");
+                }
+                if let Some(node) = self.input(&name) {
+                    self.uses_time |= name == "time";
+                    return Ok(node);
+                }
+                match self.locals.iter().rev().find(|(n, _, _)| *n == name) {
+                    Some(&(_, Ty::Float, slot)) => Ok(Node::F(Box::new(move |fr| fr.slots[slot].x))),
+                    Some(&(_, Ty::Vec3, slot)) => Ok(Node::V(Box::new(move |fr| fr.slots[slot]))),
+                    None => self.error(&t, format!("unknown variable '{}'", name)),
+                }
+            }
+            Tok::Eof => self.error(&t, "unexpected end of file"),
+            _ => self.error(&t, "expected an expression"),
+        }
+    }
+
+    fn call(&self, at: &Token, name: &str, args: Vec<Node>) -> Result<Node, DslError> {
+        let sig: Vec<Ty> = args.iter().map(Node::ty).collect();
+        let arity = |n: usize| -> Result<(), DslError> {
+            if sig.len() != n {
+                return self.type_error(at, format!("'{}' takes {} argument(s), got {}", name, n, sig.len()));
+            }
+            Ok(())
+        };
+        let want_vec = |i: usize| -> Result<(), DslError> {
+            if sig[i] != Ty::Vec3 {
+                return self.type_error(at, format!("argument {} of '{}' must be a vec3", i + 1, name));
+            }
+            Ok(())
+        };
+        let want_float = |i: usize| -> Result<(), DslError> {
+            if sig[i] != Ty::Float {
+                return self.type_error(at, format!("argument {} of '{}' must be a float", i + 1, name));
+            }
+            Ok(())
+        };
+        let mut args = args.into_iter();
+        let mut arg = || args.next().unwrap();
+        let float_arg = |n: Node| match n {
+            Node::F(f) => f,
+            Node::V(_) => unreachable!("checked by want_float"),
+        };
+        let vec_arg = |n: Node| match n {
+            Node::V(f) => f,
+            Node::F(_) => unreachable!("checked by want_vec"),
+        };
+
+        let unary_math = |f: fn(f32) -> f32, n: Node| match n {
+            Node::F(a) => Node::F(Box::new(move |fr| f(a(fr)))),
+            Node::V(a) => Node::V(Box::new(move |fr| {
+                let v = a(fr);
+                Vector3::new(f(v.x), f(v.y), f(v.z))
+            })),
+        };
+
+        Ok(match name {
+            "vec3" => match sig.len() {
+                1 => {
+                    want_float(0)?;
+                    Node::V(arg().into_vec())
+                }
+                3 => {
+                    for i in 0..3 {
+                        want_float(i)?;
+                    }
+                    let (x, y, z) = (float_arg(arg()), float_arg(arg()), float_arg(arg()));
+                    Node::V(Box::new(move |fr| Vector3::new(x(fr), y(fr), z(fr))))
+                }
+                n => return self.type_error(at, format!("'vec3' takes 1 or 3 arguments, got {}", n)),
+            },
+            "sin" | "cos" | "abs" | "floor" | "fract" | "sqrt" | "exp" => {
+                arity(1)?;
+                let f: fn(f32) -> f32 = match name {
+                    "sin" => f32::sin,
+                    "cos" => f32::cos,
+                    "abs" => f32::abs,
+                    "floor" => f32::floor,
+                    "fract" => |x| x - x.floor(),
+                    "sqrt" => |x| x.max(0.0).sqrt(),
+                    _ => f32::exp,
+                };
+                unary_math(f, arg())
+            }
+            "pow" => {
+                arity(2)?;
+                want_float(0)?;
+                want_float(1)?;
+                let (a, b) = (float_arg(arg()), float_arg(arg()));
+                Node::F(Box::new(move |fr| a(fr).max(0.0).powf(b(fr))))
+            }
+            "min" | "max" | "step" => {
+                arity(2)?;
+                let f: fn(f32, f32) -> f32 = match name {
+                    "min" => f32::min,
+                    "max" => f32::max,
+                    _ => |edge, x| if x < edge { 0.0 } else { 1.0 },
+                };
+                let (a, b) = (arg(), arg());
+                map2(a, b, f)
+            }
+            "clamp" => {
+                arity(3)?;
+                want_float(1)?;
+                want_float(2)?;
+                let (x, lo, hi) = (arg(), float_arg(arg()), float_arg(arg()));
+                match x {
+                    Node::F(x) => Node::F(Box::new(move |fr| x(fr).clamp(lo(fr), hi(fr).max(lo(fr))))),
+                    Node::V(x) => Node::V(Box::new(move |fr| {
+                        let (l, h) = (lo(fr), hi(fr));
+                        let v = x(fr);
+                        Vector3::new(v.x.clamp(l, h.max(l)), v.y.clamp(l, h.max(l)), v.z.clamp(l, h.max(l)))
+                    })),
+                }
+            }
+            "mix" => {
+                arity(3)?;
+                want_float(2)?;
+                let (a, b, t) = (arg(), arg(), float_arg(arg()));
+                if sig[0] == Ty::Float && sig[1] == Ty::Float {
+                    let (a, b) = (float_arg(a), float_arg(b));
+                    Node::F(Box::new(move |fr| {
+                        let (a, t) = (a(fr), t(fr));
+                        a + (b(fr) - a) * t
+                    }))
+                } else {
+                    let (a, b) = (a.into_vec(), b.into_vec());
+                    Node::V(Box::new(move |fr| {
+                        let (a, t) = (a(fr), t(fr));
+                        a + (b(fr) - a) * t
+                    }))
+                }
+            }
+            "smoothstep" => {
+                arity(3)?;
+                for i in 0..3 {
+                    want_float(i)?;
+                }
+                let (e0, e1, x) = (float_arg(arg()), float_arg(arg()), float_arg(arg()));
+                Node::F(Box::new(move |fr| {
+                    let (e0, e1) = (e0(fr), e1(fr));
+                    let t = ((x(fr) - e0) / (e1 - e0)).clamp(0.0, 1.0);
+                    t * t * (3.0 - 2.0 * t)
+                }))
+            }
+            "dot" | "cross" => {
+                arity(2)?;
+                want_vec(0)?;
+                want_vec(1)?;
+                let (a, b) = (vec_arg(arg()), vec_arg(arg()));
+                if name == "dot" {
+                    Node::F(Box::new(move |fr| a(fr).dot(b(fr))))
+                } else {
+                    Node::V(Box::new(move |fr| a(fr).cross(b(fr))))
+                }
+            }
+            "length" | "normalize" => {
+                arity(1)?;
+                want_vec(0)?;
+                let a = vec_arg(arg());
+                if name == "length" {
+                    Node::F(Box::new(move |fr| a(fr).length()))
+                } else {
+                    Node::V(Box::new(move |fr| {
+                        let v = a(fr);
+                        if v.length() > 0.0 { v.normalized() } else { v }
+                    }))
+                }
+            }
+            "noise" | "worley" => {
+                arity(1)?;
+                want_vec(0)?;
+                let p = vec_arg(arg());
+                if name == "noise" {
+                    Node::F(Box::new(move |fr| value_noise3(p(fr))))
+                } else {
+                    Node::F(Box::new(move |fr| worley3(p(fr)).0))
+                }
+            }
+            "fbm" | "ridged" => {
+                arity(2)?;
+                want_vec(0)?;
+                want_float(1)?;
+                let (p, octaves) = (vec_arg(arg()), float_arg(arg()));
+                let noise: fn(Vector3, i32, f32, f32) -> f32 = if name == "fbm" { fbm } else { ridged_fbm };
+                Node::F(Box::new(move |fr| noise(p(fr), octaves(fr).clamp(1.0, 8.0) as i32, 2.0, 0.5)))
+            }
+            _ => return self.error(at, format!("unknown function '{}'", name)),
+        })
+    }
+}
+
+/// Component-wise `op` between two nodes, promoting a float operand to vec3.
+fn map2(lhs: Node, rhs: Node, op: fn(f32, f32) -> f32) -> Node {
+    match (lhs, rhs) {
+        (Node::F(a), Node::F(b)) => Node::F(Box::new(move |fr| op(a(fr), b(fr)))),
+        (Node::V(a), Node::F(b)) => Node::V(Box::new(move |fr| {
+            let (v, s) = (a(fr), b(fr));
+            Vector3::new(op(v.x, s), op(v.y, s), op(v.z, s))
+        })),
+        (Node::F(a), Node::V(b)) => Node::V(Box::new(move |fr| {
+            let (s, v) = (a(fr), b(fr));
+            Vector3::new(op(s, v.x), op(s, v.y), op(s, v.z))
+        })),
+        (Node::V(a), Node::V(b)) => Node::V(Box::new(move |fr| zip3(a(fr), b(fr), op))),
+    }
+}
+
+/// Compiles the source of a material file.
+pub fn compile(src: &str) -> Result<ShaderSource, DslError> {
+    let mut c = Compiler { tokens: tokenize(src)?, pos: 0, stage: Stage::Fragment, locals: Vec::new(), slot_count: 0, uses_time: false };
+    let mut out = ShaderSource { fragment: None, vertex: None };
+
+    while c.peek().tok != Tok::Eof {
+        let (name, tok) = c.ident()?;
+        c.stage = match name.as_str() {
+            "fragment" => Stage::Fragment,
+            "vertex" => Stage::Vertex,
+            _ => return c.error(&tok, format!("expected 'fragment' or 'vertex', found '{}'", name)),
+        };
+        let program = c.block(&tok)?;
+        let slot = if c.stage == Stage::Fragment { &mut out.fragment } else { &mut out.vertex };
+        if slot.is_some() {
+            return c.error(&tok, format!("duplicate '{}' block", name));
+        }
+        *slot = Some(program);
+    }
+
+    if out.fragment.is_none() && out.vertex.is_none() {
+        let t = c.peek().clone();
+        return c.error(&t, "material has no 'fragment' or 'vertex' block");
+    }
+    Ok(out)
+}
+
+/// Reads and compiles a material file.
+pub fn load(path: &Path) -> Result<ShaderSource, DslError> {
+    let src = std::fs::read_to_string(path).map_err(|e| DslError::Io {
+        path: path.display().to_string(),
+        message: e.to_string(),
+    })?;
+    compile(&src)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(x: f32, y: f32, z: f32) -> Vector3 {
+        Vector3::new(x, y, z)
+    }
+
+    fn fragment(body: &str) -> Program {
+        compile(&format!("fragment {{ {} }}", body)).unwrap().fragment.unwrap()
+    }
+
+    #[test]
+    fn compiles_the_lava_example() {
+        let source = compile(include_str!("../materials/ejemplos/lava.shader")).unwrap();
+        let (fragment, vertex) = (source.fragment.unwrap(), source.vertex.unwrap());
+        assert!(fragment.uses_time());
+        assert!(!vertex.uses_time());
+
+        let p = v(0.3, -0.5, 0.8).normalized();
+        let c = fragment.eval(1.25, p, p, v(0.0, 1.0, 0.0));
+        assert!(c.x.is_finite() && c.y.is_finite() && c.z.is_finite());
+        let lifted = vertex.eval(0.0, p, p, v(0.0, 0.0, 0.0)).length();
+        assert!((1.0..=1.04).contains(&lifted), "vertex moved to radius {}", lifted);
+    }
+
+    #[test]
+    fn evaluates_expressions() {
+        let program = fragment("let k = 2.0 * 3.0 - 1.0; color = vec3(k, -k / 4.0, 1e-3 * 2E3) + normal.y;");
+        assert_eq!(program.eval(0.0, v(0.0, 0.0, 0.0), v(0.0, 1.0, 0.0), v(0.0, 0.0, 0.0)), v(6.0, -0.25, 3.0));
+
+        let program = fragment("color = mix(vec3(0.0), vec3(2.0, 4.0, 8.0), clamp(time, 0.0, 0.5)) * dot(sun_dir, vec3(1.0, 0.0, 0.0));");
+        assert_eq!(program.eval(3.0, v(0.0, 0.0, 0.0), v(0.0, 0.0, 0.0), v(2.0, 5.0, 5.0)), v(2.0, 4.0, 8.0));
+
+        let vertex = compile("vertex { position = obj_position * 2.0 + vec3(0.0, time, 0.0); }").unwrap().vertex.unwrap();
+        assert!(vertex.uses_time());
+        assert_eq!(vertex.eval(1.5, v(1.0, 2.0, 3.0), v(0.0, 0.0, 0.0), v(0.0, 0.0, 0.0)), v(2.0, 5.5, 6.0));
+    }
+
+    #[test]
+    fn syntax_errors_point_at_the_token() {
+        let err = compile("fragment {\n    let a = 1.0 +;\n    color = vec3(a);\n}").err().unwrap();
+        assert!(matches!(&err, DslError::Syntax { line: 2, col: 18, .. }), "{:?}", err);
+        assert_eq!(err.to_string(), "2:18: expected an expression");
+
+        let err = compile("vertex {\n  position = sun_dir;\n}").err().unwrap();
+        assert!(matches!(err, DslError::Syntax { line: 2, col: 14, .. }), "{:?}", err);
+
+        let lets: String = (0..MAX_SLOTS).map(|i| format!("let v{} = 1.0;\n", i)).collect();
+        let err = compile(&format!("fragment {{\n{}color = vec3(1.0);\n}}", lets)).err().unwrap();
+        assert!(matches!(err, DslError::Syntax { line: 33, col: 5, .. }), "{:?}", err);
+    }
+
+    #[test]
+    fn type_errors_point_at_the_call() {
+        let err = compile("fragment {\n  color = dot(normal, 1.0);\n}").err().unwrap();
+        assert!(matches!(&err, DslError::Type { line: 2, col: 11, .. }), "{:?}", err);
+        assert_eq!(err.to_string(), "2:11: type error: argument 2 of 'dot' must be a vec3");
+
+        let err = compile("fragment {\n  let t = time;\n  color = vec3(t.x);\n}").err().unwrap();
+        assert!(matches!(err, DslError::Type { line: 3, col: 18, .. }), "{:?}", err);
+    }
+}
@@ -1,6 +1,71 @@
+use std::sync::Arc;
+
 use raylib::prelude::*;
 
-use crate::{VertexShader, procedural::{generate_ring, generate_uv_sphere}, shaders::FragmentShader};
+use crate::{VertexShader, material::Material, mesh::{Bounds, Mesh}, animation::{Curve, Interp, ParamTrack, Waveform}, procedural::{generate_asteroid, generate_cube_sphere, generate_icosphere, generate_ring, generate_uv_sphere, generate_uv_sphere_uvs, sphere_normals, sphere_uvs, SphereKind}, shaders::{FragmentShader, Storm}, uniforms::ShaderParams};
+
+/// Aristas más vivas que esto conservan normales de cara (p. ej. la nave).
+pub const CREASE_ANGLE: f32 = std::f32::consts::FRAC_PI_3;
+
+/// Welds a triangle soup into a shared mesh with smooth normals (hard edges above
+/// `CREASE_ANGLE`) and optional per-corner UVs.
+pub fn soup_mesh(vertices: &[Vector3], uvs: &[Vector2]) -> Arc<Mesh> {
+    Arc::new(Mesh::from_soup_smooth(vertices, uvs, CREASE_ANGLE))
+}
+
+/// Margen relativo alrededor de cada umbral de LOD para no alternar niveles en cada frame.
+const LOD_HYSTERESIS: f32 = 0.15;
+
+/// One level of detail: used while the entity's projected radius is at least
+/// `min_screen_radius` pixels (and below the next level's threshold).
+#[derive(Clone)]
+pub struct LodLevel {
+    pub min_screen_radius: f32,
+    pub mesh: Arc<Mesh>,
+}
+
+impl LodLevel {
+    pub fn new(min_screen_radius: f32, vertices: Vec<Vector3>, uvs: Vec<Vector2>) -> Self {
+        LodLevel { min_screen_radius, mesh: Arc::new(Mesh::from_soup_smooth(&vertices, &uvs, CREASE_ANGLE)) }
+    }
+}
+
+/// LOD chain for a sphere of the given tessellation, from a few pixels up to filling the screen
+/// (UV: 8x12 … 64x96, icosphere: 1 … 4 subdivisions, cube-sphere: 3 … 24 quads per edge).
+pub fn sphere_lods(kind: SphereKind, radius: f32, with_uvs: bool) -> Vec<LodLevel> {
+    [(0.0, 0), (10.0, 1), (45.0, 2), (150.0, 3)]
+        .iter()
+        .map(|&(px, level)| {
+            let (vertices, uvs) = match kind {
+                SphereKind::Uv => {
+                    let (lat, lon) = (8 << level, 12 << level);
+                    let uvs = if with_uvs { generate_uv_sphere_uvs(lat, lon) } else { Vec::new() };
+                    (generate_uv_sphere(radius, lat, lon), uvs)
+                }
+                SphereKind::Icosphere | SphereKind::CubeSphere => {
+                    let vertices = if kind == SphereKind::Icosphere {
+                        generate_icosphere(radius, level as u32 + 1)
+                    } else {
+                        generate_cube_sphere(radius, 3 << level)
+                    };
+                    let uvs = if with_uvs { sphere_uvs(&vertices) } else { Vec::new() };
+                    (vertices, uvs)
+                }
+            };
+            // En una esfera la normal exacta es la dirección del vértice
+            let normals = sphere_normals(&vertices);
+            LodLevel { min_screen_radius: px, mesh: Arc::new(Mesh::from_soup(&vertices, &normals, &uvs)) }
+        })
+        .collect()
+}
+
+/// Part of an entity's model drawn with its own material (e.g. one per OBJ `usemtl`).
+#[derive(Clone)]
+pub struct MeshPart {
+    pub name: String,
+    pub mesh: Arc<Mesh>,
+    pub fshader: FragmentShader,
+}
 
 #[derive(Clone)]
 pub struct Entity {
@@ -15,11 +80,27 @@ pub struct Entity {
     pub up: Vector3,
 
     pub motion: Motion,
-    pub vertices: Vec<Vector3>,
+    pub mesh: Arc<Mesh>,          // malla indexada (compartible), normales suavizadas con aristas vivas
     pub vshader: VertexShader,
     pub fshader: FragmentShader,
     pub spin: Vector3,            // angular velocity (rad/s) around each local axis
     pub face_tangent: bool,       // if true, add tangent-facing yaw from orbital motion      // if true, add tangent-facing yaw from orbital motion
+    pub params: ShaderParams,     // uniforms propios de la entidad
+    pub animations: Vec<ParamTrack>,
+
+    // Niveles de detalle (vacío = siempre `mesh`), ordenados por umbral creciente
+    pub lods: Vec<LodLevel>,
+    pub active_lod: usize,
+    pub lod_radius: f32,          // radio envolvente de la malla en espacio objeto
+
+    // Sub-mallas con material propio; si hay, se dibujan en lugar de `mesh` + `fshader`
+    pub parts: Vec<MeshPart>,
+
+    // Orientación dada por (right, up, forward) en vez de `rotation` (nave, nodos glTF)
+    pub uses_basis: bool,
+
+    // Volumen envolvente (espacio objeto) de todo lo que puede dibujar, para el culling
+    pub bounds: Bounds,
 }
 
 impl Entity {
@@ -44,7 +125,7 @@ impl Entity {
         rotation: Vector3,
         scale: f32,
         motion: Motion,
-        vertices: Vec<Vector3>,
+        mesh: Arc<Mesh>,
         vshader: VertexShader,
         fshader: FragmentShader,
         spin: Vector3,            // angular velocity (rad/s) around each local axis
@@ -86,7 +167,7 @@ impl Entity {
         let right = right0 * cr + up0 * sr;
         let up    = -right0 * sr + up0 * cr;
 
-        Entity {
+        let mut entity = Entity {
             name,
             translation,
             rotation,
@@ -95,11 +176,150 @@ impl Entity {
             right,
             up,
             motion,
-            vertices,
+            mesh,
             vshader,
             fshader,
             spin,
             face_tangent,
+            params: ShaderParams::default(),
+            animations: Vec::new(),
+            lods: Vec::new(),
+            active_lod: 0,
+            lod_radius: 0.0,
+            parts: Vec::new(),
+            uses_basis: false,
+            bounds: Bounds::default(),
+        };
+        entity.update_bounds();
+        entity
+    }
+
+    /// Entity drawn only as `parts` (an OBJ with materials, a glTF node), unrotated and without
+    /// spin. It has no mesh of its own; `fshader` is a neutral PBR that the parts never use.
+    pub fn from_parts(name: &'static str, translation: Vector3, scale: f32, motion: Motion, parts: Vec<MeshPart>, face_tangent: bool) -> Self {
+        let zero = Vector3::new(0.0, 0.0, 0.0);
+        Entity::new(
+            name,
+            translation,
+            zero,
+            scale,
+            motion,
+            Arc::default(),
+            VertexShader::Identity,
+            FragmentShader::Pbr { material: Material::default() },
+            zero,
+            face_tangent,
+        )
+        .with_parts(parts)
+    }
+
+    /// Recomputes `bounds` from what the entity may draw: its parts, every LOD level, or the mesh.
+    fn update_bounds(&mut self) {
+        let meshes: Vec<&Mesh> = if !self.parts.is_empty() {
+            self.parts.iter().map(|p| &*p.mesh).collect()
+        } else if !self.lods.is_empty() {
+            self.lods.iter().map(|l| &*l.mesh).collect()
+        } else {
+            vec![&self.mesh]
+        };
+        self.bounds = meshes.iter().map(|m| m.bounds()).reduce(|a, b| a.union(&b)).unwrap_or_default();
+    }
+
+    /// Orients the entity with its `right`/`up`/`forward` basis instead of Euler `rotation`.
+    pub fn with_basis(mut self) -> Self {
+        self.uses_basis = true;
+        self
+    }
+
+    /// Draws the entity as these parts, each with its own fragment shader.
+    pub fn with_parts(mut self, parts: Vec<MeshPart>) -> Self {
+        self.parts = parts;
+        self.update_bounds();
+        self
+    }
+
+    /// Replaces the fixed mesh by a set of LOD levels chosen by `select_lod`; the mesh given to
+    /// `new` is dropped, so LOD entities pass `Arc::default()`.
+    pub fn with_lods(mut self, mut levels: Vec<LodLevel>) -> Self {
+        self.mesh = Arc::default();
+        levels.sort_by(|a, b| a.min_screen_radius.total_cmp(&b.min_screen_radius));
+        self.lod_radius = levels.iter().map(|l| l.mesh.bounding_radius()).fold(0.0, f32::max);
+        self.active_lod = 0;
+        self.lods = levels;
+        self.update_bounds();
+        self
+    }
+
+    /// Applies a time-independent vertex shader (terrain, volcanic relief) to every mesh once and
+    /// switches to `Identity`, so rendering no longer displaces and recomputes normals per frame.
+    pub fn bake_vertex_shader(&mut self) {
+        if matches!(self.vshader, VertexShader::Identity) || self.vshader.is_animated() {
+            return;
+        }
+        let vshader = std::mem::replace(&mut self.vshader, VertexShader::Identity);
+        let displace = |mesh: &Arc<Mesh>| Arc::new(mesh.displaced(&vshader, CREASE_ANGLE));
+        self.mesh = displace(&self.mesh);
+        for level in &mut self.lods {
+            level.mesh = displace(&level.mesh);
+        }
+        for part in &mut self.parts {
+            part.mesh = displace(&part.mesh);
+        }
+        self.lod_radius = self.lods.iter().map(|l| l.mesh.bounding_radius()).fold(0.0, f32::max);
+        self.update_bounds();
+    }
+
+    /// Picks the LOD for a projected radius of `screen_radius` pixels. A level is only left
+    /// once the radius crosses its threshold by more than `LOD_HYSTERESIS`.
+    pub fn select_lod(&mut self, screen_radius: f32) {
+        let n = self.lods.len();
+        let mut i = self.active_lod.min(n.saturating_sub(1));
+        while i + 1 < n && screen_radius > self.lods[i + 1].min_screen_radius * (1.0 + LOD_HYSTERESIS) {
+            i += 1;
+        }
+        while i > 0 && screen_radius < self.lods[i].min_screen_radius * (1.0 - LOD_HYSTERESIS) {
+            i -= 1;
+        }
+        self.active_lod = i;
+    }
+
+    /// Current mesh: the active LOD, or the fixed mesh without LODs.
+    pub fn current_mesh(&self) -> &Mesh {
+        match self.lods.get(self.active_lod) {
+            Some(level) => &level.mesh,
+            None => &self.mesh,
+        }
+    }
+
+    /// What to draw this frame: every part, or the current mesh with the entity's shader.
+    pub fn draw_list(&self) -> Vec<(&Mesh, &FragmentShader)> {
+        if self.parts.is_empty() {
+            vec![(self.current_mesh(), &self.fshader)]
+        } else {
+            self.parts.iter().map(|p| (&*p.mesh, &p.fshader)).collect()
+        }
+    }
+
+    /// Sets a custom uniform parameter for this entity's shaders.
+    pub fn with_param(mut self, name: &str, value: f32) -> Self {
+        self.params.set(name, value);
+        self
+    }
+
+    /// Drives parameter `param` with `curve` over simulation time.
+    pub fn with_animation(mut self, param: &str, curve: Curve) -> Self {
+        self.animations.push(ParamTrack { param: param.to_string(), curve });
+        self
+    }
+
+    /// Evaluates the animation tracks at `time`: each one writes its material field, or the
+    /// custom uniform of the same name when the shader has no such field.
+    pub fn animate(&mut self, time: f32) {
+        for track in &self.animations {
+            let value = track.curve.sample(time);
+            if !self.fshader.set_param(&track.param, value) {
+                self.params.set(&track.param, value);
+            }
         }
     }
 
@@ -244,9 +464,17 @@ pub enum Motion {
     Static,
     Orbit { center: Vector3, radius: f32, angular_speed: f32, phase: f32 }, // world-center orbit
     OrbitAround { parent: &'static str, radius: f32, angular_speed: f32, phase: f32 }, // orbit around entity
+    // Fijo al padre con una transformación local (nodos glTF): traslación en los ejes y escala
+    // del padre, rotación como cuaternión (x, y, z, w)
+    Attached { parent: &'static str, translation: Vector3, rotation: Vector4, scale: f32 },
 }
 
 pub fn sample_system() -> Vec<Entity> {
+    // Giro propio (rad/s en y) de la Tierra y de su capa de nubes; el shader de la Tierra
+    // necesita la diferencia para mover la sombra de las nubes con ellas
+    const EARTH_SPIN: f32 = 4.0;
+    const CLOUD_SPIN: f32 = 4.5;
+
     vec![
         Entity::new(
             "sun",
@@ -254,12 +482,12 @@ pub fn sample_system() -> Vec<Entity> {
             Vector3::new(0.0, 0.0, 0.0),
             1.0,
             Motion::Static,
-            generate_uv_sphere(15.0, 24, 32),
+            Arc::default(),
             VertexShader::SolarFlare,
             FragmentShader::Star,
             Vector3::new(0.0, 1.0, 0.0),
             false,
-        ),
+        ).with_lods(sphere_lods(SphereKind::Uv, 15.0, false)),
         Entity::new(
             "earth",
             Vector3::new(0.0, 0.0, 0.0),
@@ -268,12 +496,34 @@ pub fn sample_system() -> Vec<Entity> {
             Motion::Orbit {
                 center: Vector3::new(0.0, 0.0, 0.0), radius: 40.0, angular_speed: 0.8, phase: 0.0 
             },
-            generate_uv_sphere(1.8, 16, 24),
+            Arc::default(),
             VertexShader::Identity,
-            FragmentShader::Rocky { color: Vector3::new(0.0, 0.5, 1.0) },
-            Vector3::new(0.0, 4.0, 0.0),
+            FragmentShader::Earth {
+                ocean: Vector3::new(0.02, 0.18, 0.45),
+                land: Vector3::new(0.22, 0.4, 0.12),
+                cloud_coverage: 0.45,
+                cloud_spin: CLOUD_SPIN - EARTH_SPIN,
+            },
+            Vector3::new(0.0, EARTH_SPIN, 0.0),
             false,
-        ),
+        ).with_lods(sphere_lods(SphereKind::Uv, 1.8, true)),
+        Entity::new(
+            "earth_clouds",
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 0.0),
+            1.0,
+            Motion::OrbitAround {
+                parent: "earth",
+                radius: 0.0,
+                angular_speed: 0.0,
+                phase: 0.0,
+            },
+            Arc::default(),
+            VertexShader::Identity,
+            FragmentShader::Clouds { coverage: 0.45 },
+            Vector3::new(0.0, CLOUD_SPIN, 0.0),
+            false,
+        ).with_lods(sphere_lods(SphereKind::Uv, 1.86, false)),
 
         Entity::new(
             "moon",
@@ -286,12 +536,12 @@ pub fn sample_system() -> Vec<Entity> {
                 angular_speed: 3.5,
                 phase: 0.0,
             },
-            generate_uv_sphere(0.8, 16, 24),
-            VertexShader::Identity,
+            Arc::default(),
+            VertexShader::TerrainDisplace { seed: 7, amplitude: 0.035, frequency: 2.5, octaves: 4, crater_density: 5.0 },
             FragmentShader::Rocky { color: Vector3::new(0.8, 0.8, 0.8) },
             Vector3::new(0.0, 0.0, 0.0),
             true,
-        ),
+        ).with_lods(sphere_lods(SphereKind::Icosphere, 0.8, false)),
 
         Entity::new(
             "mars",
@@ -301,11 +551,48 @@ pub fn sample_system() -> Vec<Entity> {
             Motion::Orbit {
                 center: Vector3::new(0.0, 0.0, 0.0), radius: 60.0, angular_speed: 0.7, phase: 0.0 
             },
-            generate_uv_sphere(1.2, 16, 24),
-            VertexShader::Identity,
+            Arc::default(),
+            VertexShader::TerrainDisplace { seed: 4, amplitude: 0.07, frequency: 2.0, octaves: 5, crater_density: 3.0 },
             FragmentShader::Rocky { color: Vector3::new(0.6, 0.2, 0.0) },
             Vector3::new(0.0, 2.0, 0.0),
             false,
+        ).with_lods(sphere_lods(SphereKind::Icosphere, 1.2, false)),
+
+        // Lunas de Marte: rocas irregulares, no esferas
+        Entity::new(
+            "phobos",
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.3, 0.0, 0.0),
+            1.0,
+            Motion::OrbitAround {
+                parent: "mars",
+                radius: 2.4,
+                angular_speed: 4.0,
+                phase: 0.0,
+            },
+            soup_mesh(&generate_asteroid(11, 0.3, 0.7, 3), &[]),
+            VertexShader::Identity,
+            FragmentShader::Rocky { color: Vector3::new(0.45, 0.38, 0.32) },
+            Vector3::new(0.0, 0.0, 0.0),
+            true,
+        ),
+
+        Entity::new(
+            "deimos",
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 0.2),
+            1.0,
+            Motion::OrbitAround {
+                parent: "mars",
+                radius: 3.6,
+                angular_speed: 1.6,
+                phase: 2.0,
+            },
+            soup_mesh(&generate_asteroid(23, 0.2, 0.5, 3), &[]),
+            VertexShader::Identity,
+            FragmentShader::Rocky { color: Vector3::new(0.55, 0.48, 0.4) },
+            Vector3::new(0.0, 0.0, 0.0),
+            true,
         ),
 
         Entity::new(
@@ -316,12 +603,59 @@ pub fn sample_system() -> Vec<Entity> {
             Motion::Orbit {
                 center: Vector3::new(0.0, 0.0, 0.0), radius: 80.0, angular_speed: 0.6, phase: 0.0 
             },
-            generate_uv_sphere(7.0, 16, 24),
+            Arc::default(),
             VertexShader::SolarFlare,
-            FragmentShader::Strips {angle: 0.0},
+            FragmentShader::GasGiant {
+                palette: vec![
+                    Vector3::new(0.95, 0.9, 0.78),
+                    Vector3::new(0.78, 0.55, 0.38),
+                    Vector3::new(0.9, 0.82, 0.66),
+                    Vector3::new(0.68, 0.45, 0.32),
+                ],
+                bands: 14,
+                wind: 0.25,
+                storms: vec![
+                    Storm { lat: -0.38, lon: 0.8, radius: 0.22, color: Vector3::new(0.78, 0.32, 0.18), spin: -0.6 },
+                    Storm { lat: 0.55, lon: 2.9, radius: 0.08, color: Vector3::new(0.95, 0.92, 0.88), spin: 0.9 },
+                ],
+            },
             Vector3::new(0.0, 7.0, 0.0),
             false,
-        ),
+        ).with_lods(sphere_lods(SphereKind::CubeSphere, 7.0, false)),
+        Entity::new(
+            "io",
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 0.0),
+            1.0,
+            Motion::OrbitAround {
+                parent: "jupyter",
+                radius: 11.0,
+                angular_speed: 2.2,
+                phase: 1.0,
+            },
+            Arc::default(),
+            VertexShader::Volcanic { amp: 0.08, freq: 3.0 },
+            FragmentShader::Volcanic { crust: Vector3::new(0.22, 0.2, 0.18), lava: Vector3::new(1.0, 0.35, 0.05), flow: 0.4 },
+            Vector3::new(0.0, 1.5, 0.0),
+            false,
+        ).with_lods(sphere_lods(SphereKind::Uv, 1.0, false)),
+        Entity::new(
+            "europa",
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 0.0),
+            1.0,
+            Motion::OrbitAround {
+                parent: "jupyter",
+                radius: 15.0,
+                angular_speed: 1.6,
+                phase: 3.5,
+            },
+            Arc::default(),
+            VertexShader::Identity,
+            FragmentShader::Icy { tint: Vector3::new(0.35, 0.6, 0.95), crack_density: 9.0 },
+            Vector3::new(0.0, 1.0, 0.0),
+            false,
+        ).with_lods(sphere_lods(SphereKind::Uv, 0.9, false)),
         Entity::new(
             "saturn",
             Vector3::new(0.0, 0.0, 0.0),
@@ -330,12 +664,23 @@ pub fn sample_system() -> Vec<Entity> {
             Motion::Orbit {
                 center: Vector3::new(0.0, 0.0, 0.0), radius: 100.0, angular_speed: 0.5, phase: 0.0 
             },
-            generate_uv_sphere(5.0, 16, 24),
+            Arc::default(),
             VertexShader::SolarFlare,
-            FragmentShader::Solid { color: Vector3::new(0.9, 0.7, 0.1) },
+            FragmentShader::GasGiant {
+                palette: vec![
+                    Vector3::new(0.93, 0.85, 0.6),
+                    Vector3::new(0.85, 0.72, 0.45),
+                    Vector3::new(0.9, 0.8, 0.55),
+                ],
+                bands: 22,
+                wind: 0.12,
+                storms: vec![
+                    Storm { lat: 1.35, lon: 0.0, radius: 0.15, color: Vector3::new(0.7, 0.68, 0.5), spin: 0.4 },
+                ],
+            },
             Vector3::new(0.0, 6.0, 0.0),
             false,
-        ),
+        ).with_lods(sphere_lods(SphereKind::CubeSphere, 5.0, false)),
         Entity::new(
             "saturn_ring", 
             Vector3::new(0.0, 0.0, 0.0),
@@ -347,20 +692,76 @@ pub fn sample_system() -> Vec<Entity> {
                 angular_speed: 0.0,
                 phase: 0.0,
             },
-            generate_ring(6.5, 10.5, 128), 
+            soup_mesh(&generate_ring(6.5, 10.5, 128), &[]),
             VertexShader::DisplacePlanarY { amp: 0.06, freq: 6.0, octaves: 3, lacunarity: 2.0, gain: 0.55, time_amp: 0.6 },
-            FragmentShader::Solid { color: Vector3::new(0.5, 0.4, 0.0) },
+            FragmentShader::Ring { inner: 6.5, outer: 10.5, color: Vector3::new(0.85, 0.75, 0.55), planet_radius: 5.0 },
             Vector3::new(0.0, 7.0, 0.0), 
             false,
         ),
 
+        // Estrella variable lejana (tipo cefeida): subida rápida y bajada lenta del brillo,
+        // con la temperatura oscilando en fase
+        Entity::new(
+            "variable_star",
+            Vector3::new(120.0, 40.0, -60.0),
+            Vector3::new(0.0, 0.0, 0.0),
+            1.0,
+            Motion::Static,
+            Arc::default(),
+            VertexShader::Identity,
+            FragmentShader::Star,
+            Vector3::new(0.0, 0.5, 0.0),
+            false,
+        ).with_lods(sphere_lods(SphereKind::Uv, 4.0, false))
+        .with_animation(
+            "intensity",
+            Curve::keyframes(&[(0.0, 0.45), (1.0, 1.3), (2.5, 0.9), (5.4, 0.45)], Interp::Smooth).looped(5.4),
+        )
+        .with_animation("temp", Curve::lfo(Waveform::Sine, 0.75, 0.12, 1.0 / 5.4)),
 
         // Orbits
-        Entity::new("orbit_earth", Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 0.0), 1.0, Motion::Static, generate_ring(40.0, 40.1, 128), VertexShader::Identity, FragmentShader::Solid {color: Vector3::new(1.0, 1.0, 1.0)}, Vector3::new(0.0, 0.0, 0.0), false),
-        Entity::new("orbit_moon", Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 0.0), 1.0, Motion::OrbitAround { parent: "earth", radius: 0.0, angular_speed: 0.0, phase: 0.0 }, generate_ring(5.5, 5.6, 128), VertexShader::Identity, FragmentShader::Solid {color: Vector3::new(1.0, 1.0, 1.0)}, Vector3::new(0.0, 0.0, 0.0), false),
-        Entity::new("orbit_mars", Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 0.0), 1.0, Motion::Static, generate_ring(60.0, 60.1, 128), VertexShader::Identity, FragmentShader::Solid {color: Vector3::new(1.0, 1.0, 1.0)}, Vector3::new(0.0, 0.0, 0.0), false),
-        Entity::new("orbit_jupyter", Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 0.0), 1.0, Motion::Static, generate_ring(80.0, 80.1, 128), VertexShader::Identity, FragmentShader::Solid {color: Vector3::new(1.0, 1.0, 1.0)}, Vector3::new(0.0, 0.0, 0.0), false),
-        Entity::new("orbit_saturn", Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 0.0), 1.0, Motion::Static, generate_ring(100.0, 100.1, 128), VertexShader::Identity, FragmentShader::Solid {color: Vector3::new(1.0, 1.0, 1.0)}, Vector3::new(0.0, 0.0, 0.0), false),
+        Entity::new("orbit_earth", Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 0.0), 1.0, Motion::Static, soup_mesh(&generate_ring(40.0, 40.1, 128), &[]), VertexShader::Identity, FragmentShader::Solid {color: Vector3::new(1.0, 1.0, 1.0)}, Vector3::new(0.0, 0.0, 0.0), false),
+        Entity::new("orbit_moon", Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 0.0), 1.0, Motion::OrbitAround { parent: "earth", radius: 0.0, angular_speed: 0.0, phase: 0.0 }, soup_mesh(&generate_ring(5.5, 5.6, 128), &[]), VertexShader::Identity, FragmentShader::Solid {color: Vector3::new(1.0, 1.0, 1.0)}, Vector3::new(0.0, 0.0, 0.0), false),
+        Entity::new("orbit_mars", Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 0.0), 1.0, Motion::Static, soup_mesh(&generate_ring(60.0, 60.1, 128), &[]), VertexShader::Identity, FragmentShader::Solid {color: Vector3::new(1.0, 1.0, 1.0)}, Vector3::new(0.0, 0.0, 0.0), false),
+        Entity::new("orbit_jupyter", Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 0.0), 1.0, Motion::Static, soup_mesh(&generate_ring(80.0, 80.1, 128), &[]), VertexShader::Identity, FragmentShader::Solid {color: Vector3::new(1.0, 1.0, 1.0)}, Vector3::new(0.0, 0.0, 0.0), false),
+        Entity::new("orbit_saturn", Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 0.0), 1.0, Motion::Static, soup_mesh(&generate_ring(100.0, 100.1, 128), &[]), VertexShader::Identity, FragmentShader::Solid {color: Vector3::new(1.0, 1.0, 1.0)}, Vector3::new(0.0, 0.0, 0.0), false),
 
     ]
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lod_sphere() -> Entity {
+        let zero = Vector3::new(0.0, 0.0, 0.0);
+        Entity::new("s", zero, zero, 1.0, Motion::Static, Arc::default(), VertexShader::Identity, FragmentShader::Star, zero, false)
+            .with_lods(sphere_lods(SphereKind::Uv, 1.0, false))
+    }
+
+    #[test]
+    fn select_lod_waits_past_the_hysteresis_margin() {
+        let mut e = lod_sphere();
+        let level = |e: &mut Entity, px: f32| {
+            e.select_lod(px);
+            e.active_lod
+        };
+        // Umbrales 0, 10, 45 y 150 px con un margen del 15 %
+        assert_eq!(level(&mut e, 11.0), 0);
+        assert_eq!(level(&mut e, 12.0), 1);
+        assert_eq!(level(&mut e, 9.0), 1);
+        assert_eq!(level(&mut e, 8.0), 0);
+        assert_eq!(level(&mut e, 1000.0), 3);
+        assert_eq!(level(&mut e, 140.0), 3);
+        assert_eq!(level(&mut e, 0.0), 0);
+        assert!(std::ptr::eq(e.current_mesh(), &*e.lods[0].mesh));
+    }
+
+    #[test]
+    fn lod_entities_bound_only_their_levels() {
+        let e = lod_sphere();
+        assert!(e.mesh.positions.is_empty());
+        assert!((e.lod_radius - 1.0).abs() < 1e-5);
+        assert!((e.bounds.radius - 1.0).abs() < 1e-3, "{}", e.bounds.radius);
+        assert!(e.bounds.center.length() < 1e-5);
+    }
+}
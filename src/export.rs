@@ -0,0 +1,279 @@
+// src/export.rs
+// Exportación de la malla de una entidad a OBJ o PLY (ASCII), opcionalmente congelando el
+// vertex shader en un instante dado.
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use raylib::prelude::*;
+
+use crate::entity::{Entity, CREASE_ANGLE};
+use crate::mesh::Mesh;
+use crate::shaders::{apply_vertex_shader, VertexShader};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ExportFormat {
+    Obj,
+    Ply,
+}
+
+impl ExportFormat {
+    /// Format from the file extension (`.obj` / `.ply`, any case).
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "obj" => Some(ExportFormat::Obj),
+            "ply" => Some(ExportFormat::Ply),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ExportError {
+    UnsupportedFormat { path: String },
+    Io { path: String, message: String },
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::UnsupportedFormat { path } => write!(f, "{}: unknown export format (use .obj or .ply)", path),
+            ExportError::Io { path, message } => write!(f, "{}: {}", path, message),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+/// A named piece of geometry ready to be written, in object space.
+struct Group {
+    name: String,
+    mesh: Mesh,
+}
+
+/// Writes what `entity` would draw (its parts, or its current mesh) in object space. With
+/// `vertex_time` the vertex shader is applied at that time and normals are recomputed like
+/// `render` does; without it the undeformed mesh is written.
+pub fn export_entity(entity: &Entity, path: &Path, vertex_time: Option<f32>) -> Result<(), ExportError> {
+    let label = path.display().to_string();
+    let format = ExportFormat::from_path(path).ok_or(ExportError::UnsupportedFormat { path: label.clone() })?;
+
+    let groups: Vec<Group> = if entity.parts.is_empty() {
+        vec![Group { name: entity.name.to_string(), mesh: entity.current_mesh().clone() }]
+    } else {
+        entity.parts.iter().map(|p| Group { name: p.name.clone(), mesh: Mesh::clone(&p.mesh) }).collect()
+    };
+    let groups: Vec<Group> = match vertex_time {
+        Some(time) => groups.into_iter().map(|g| Group { name: g.name, mesh: deform(&g.mesh, &entity.vshader, time) }).collect(),
+        None => groups,
+    };
+
+    let io = |e: std::io::Error| ExportError::Io { path: label.clone(), message: e.to_string() };
+    let mut out = BufWriter::new(File::create(path).map_err(io)?);
+    match format {
+        ExportFormat::Obj => write_obj(&mut out, entity.name, &groups),
+        ExportFormat::Ply => write_ply(&mut out, entity.name, &groups),
+    }
+    .and_then(|_| out.flush())
+    .map_err(io)
+}
+
+/// Mesh with the vertex shader frozen at `time`.
+fn deform(mesh: &Mesh, vshader: &VertexShader, time: f32) -> Mesh {
+    if matches!(vshader, VertexShader::Identity) {
+        return mesh.clone();
+    }
+    let positions: Vec<Vector3> = mesh.positions.iter().map(|&v| apply_vertex_shader(v, vshader, time)).collect();
+    let normals = mesh.recompute_normals(&positions, CREASE_ANGLE);
+    Mesh { positions, normals, uvs: mesh.uvs.clone(), indices: mesh.indices.clone() }
+}
+
+fn has_normals(mesh: &Mesh) -> bool {
+    mesh.normals.len() == mesh.positions.len()
+}
+
+/// OBJ with one `g` per group; indices are global and 1-based. `vt` is flipped back to the
+/// OBJ convention (v = 0 at the bottom of the image), the inverse of what `obj.rs` does.
+fn write_obj(out: &mut impl Write, name: &str, groups: &[Group]) -> std::io::Result<()> {
+    writeln!(out, "# {} — exportado por sistemasolar", name)?;
+    let (mut v_base, mut vt_base, mut vn_base) = (1usize, 1usize, 1usize);
+    for group in groups {
+        let mesh = &group.mesh;
+        writeln!(out, "g {}", group.name.replace(char::is_whitespace, "_"))?;
+        for p in &mesh.positions {
+            writeln!(out, "v {} {} {}", p.x, p.y, p.z)?;
+        }
+        if mesh.has_uvs() {
+            for uv in &mesh.uvs {
+                writeln!(out, "vt {} {}", uv.x, 1.0 - uv.y)?;
+            }
+        }
+        if has_normals(mesh) {
+            for n in &mesh.normals {
+                writeln!(out, "vn {} {} {}", n.x, n.y, n.z)?;
+            }
+        }
+
+        let corner = |i: u32| {
+            let i = i as usize;
+            match (mesh.has_uvs(), has_normals(mesh)) {
+                (true, true) => format!("{}/{}/{}", v_base + i, vt_base + i, vn_base + i),
+                (true, false) => format!("{}/{}", v_base + i, vt_base + i),
+                (false, true) => format!("{}//{}", v_base + i, vn_base + i),
+                (false, false) => format!("{}", v_base + i),
+            }
+        };
+        for tri in mesh.indices.chunks_exact(3) {
+            writeln!(out, "f {} {} {}", corner(tri[0]), corner(tri[1]), corner(tri[2]))?;
+        }
+
+        v_base += mesh.positions.len();
+        if mesh.has_uvs() {
+            vt_base += mesh.uvs.len();
+        }
+        if has_normals(mesh) {
+            vn_base += mesh.normals.len();
+        }
+    }
+    Ok(())
+}
+
+/// ASCII PLY with all groups merged. Normals and UVs (`s`, `t`, with `t` flipped as in OBJ) are
+/// written only if every group has them, since PLY vertex properties are shared by the element.
+fn write_ply(out: &mut impl Write, name: &str, groups: &[Group]) -> std::io::Result<()> {
+    let vertex_count: usize = groups.iter().map(|g| g.mesh.positions.len()).sum();
+    let face_count: usize = groups.iter().map(|g| g.mesh.triangle_count()).sum();
+    let with_normals = groups.iter().all(|g| has_normals(&g.mesh));
+    let with_uvs = groups.iter().all(|g| g.mesh.has_uvs());
+
+    writeln!(out, "ply")?;
+    writeln!(out, "format ascii 1.0")?;
+    writeln!(out, "comment {} — exportado por sistemasolar", name)?;
+    writeln!(out, "element vertex {}", vertex_count)?;
+    for axis in ["x", "y", "z"] {
+        writeln!(out, "property float {}", axis)?;
+    }
+    if with_normals {
+        for axis in ["nx", "ny", "nz"] {
+            writeln!(out, "property float {}", axis)?;
+        }
+    }
+    if with_uvs {
+        writeln!(out, "property float s")?;
+        writeln!(out, "property float t")?;
+    }
+    writeln!(out, "element face {}", face_count)?;
+    writeln!(out, "property list uchar uint vertex_indices")?;
+    writeln!(out, "end_header")?;
+
+    for group in groups {
+        let mesh = &group.mesh;
+        for (i, p) in mesh.positions.iter().enumerate() {
+            write!(out, "{} {} {}", p.x, p.y, p.z)?;
+            if with_normals {
+                let n = mesh.normals[i];
+                write!(out, " {} {} {}", n.x, n.y, n.z)?;
+            }
+            if with_uvs {
+                write!(out, " {} {}", mesh.uvs[i].x, 1.0 - mesh.uvs[i].y)?;
+            }
+            writeln!(out)?;
+        }
+    }
+    let mut base = 0u32;
+    for group in groups {
+        for tri in group.mesh.indices.chunks_exact(3) {
+            writeln!(out, "3 {} {} {}", base + tri[0], base + tri[1], base + tri[2])?;
+        }
+        base += group.mesh.positions.len() as u32;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::procedural::{generate_uv_sphere, generate_uv_sphere_uvs};
+
+    fn v(x: f32, y: f32, z: f32) -> Vector3 {
+        Vector3::new(x, y, z)
+    }
+
+    // Dos grupos: un triángulo con UVs y normales, y un quad (dos triángulos) solo con posiciones
+    fn groups() -> Vec<Group> {
+        let tri = Mesh {
+            positions: vec![v(0.0, 0.0, 0.0), v(1.0, 0.0, 0.0), v(0.0, 1.0, 0.0)],
+            normals: vec![v(0.0, 0.0, 1.0); 3],
+            uvs: vec![Vector2::new(0.0, 0.0), Vector2::new(1.0, 0.0), Vector2::new(0.0, 0.25)],
+            indices: vec![0, 1, 2],
+        };
+        let quad = Mesh {
+            positions: vec![v(0.0, 0.0, 2.0), v(1.0, 0.0, 2.0), v(1.0, 1.0, 2.0), v(0.0, 1.0, 2.0)],
+            indices: vec![0, 1, 2, 0, 2, 3],
+            ..Mesh::default()
+        };
+        vec![Group { name: "first tri".into(), mesh: tri }, Group { name: "quad".into(), mesh: quad }]
+    }
+
+    fn written(write: impl Fn(&mut Vec<u8>) -> std::io::Result<()>) -> Vec<String> {
+        let mut bytes = Vec::new();
+        write(&mut bytes).unwrap();
+        String::from_utf8(bytes).unwrap().lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn obj_indices_are_one_based_across_groups() {
+        let lines = written(|out| write_obj(out, "test", &groups()));
+        assert_eq!(lines[0], "# test — exportado por sistemasolar");
+        assert_eq!(lines[1], "g first_tri");
+        assert!(lines.contains(&"vt 0 0.75".to_string()), "vt is flipped back to OBJ's convention");
+        let faces: Vec<&str> = lines.iter().filter(|l| l.starts_with("f ")).map(String::as_str).collect();
+        assert_eq!(faces, ["f 1/1/1 2/2/2 3/3/3", "f 4 5 6", "f 4 6 7"]);
+        assert_eq!(lines.iter().filter(|l| l.starts_with("v ")).count(), 7);
+        assert!(lines.contains(&"g quad".to_string()));
+    }
+
+    #[test]
+    fn ply_offsets_each_group_and_drops_partial_attributes() {
+        let lines = written(|out| write_ply(out, "test", &groups()));
+        let header_end = lines.iter().position(|l| l == "end_header").unwrap();
+        let header = &lines[..header_end];
+        assert_eq!(&header[..2], ["ply", "format ascii 1.0"]);
+        assert!(header.contains(&"element vertex 7".to_string()));
+        assert!(header.contains(&"element face 3".to_string()));
+        // El quad no tiene normales ni UVs, así que ningún vértice las lleva
+        assert!(!header.iter().any(|l| l.contains("nx") || l.ends_with(" s")));
+
+        let body = &lines[header_end + 1..];
+        assert_eq!(body.len(), 7 + 3);
+        assert_eq!(body[3], "0 0 2");
+        assert_eq!(&body[7..], ["3 0 1 2", "3 3 4 5", "3 3 5 6"]);
+    }
+
+    #[test]
+    fn exported_uv_sphere_faces_outward() {
+        let mesh = Mesh::from_soup(&generate_uv_sphere(1.0, 8, 12), &[], &generate_uv_sphere_uvs(8, 12));
+        let lines = written(|out| write_obj(out, "sphere", &[Group { name: "sphere".into(), mesh: mesh.clone() }]));
+        let positions: Vec<Vector3> = lines
+            .iter()
+            .filter_map(|l| l.strip_prefix("v "))
+            .map(|l| {
+                let c: Vec<f32> = l.split(' ').map(|x| x.parse().unwrap()).collect();
+                v(c[0], c[1], c[2])
+            })
+            .collect();
+        let mut faces = 0;
+        for l in lines.iter().filter_map(|l| l.strip_prefix("f ")) {
+            let idx: Vec<usize> = l.split(' ').map(|c| c.split('/').next().unwrap().parse::<usize>().unwrap() - 1).collect();
+            let (a, b, c) = (positions[idx[0]], positions[idx[1]], positions[idx[2]]);
+            let n = (b - a).cross(c - a);
+            // Los triángulos de los polos tienen área nula
+            if n.length() > 1e-6 {
+                assert!(n.dot(a + b + c) > 0.0, "face {} points inward", l);
+            }
+            faces += 1;
+        }
+        assert_eq!(faces, mesh.triangle_count());
+    }
+}
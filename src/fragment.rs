@@ -7,6 +7,9 @@ pub struct Fragment {
     pub color: Vector3,
     pub depth: f32,
     pub obj_position: Vector3,
+    pub normal: Vector3, // normal interpolada en espacio objeto
+    pub uv: Vector2,
+    pub uv_lod: f32, // log2 de unidades UV por pixel (constante por triángulo)
 }
 
 impl Fragment {
@@ -15,7 +18,21 @@ impl Fragment {
             position: Vector3::new(x, y, depth),
             color,
             depth,
-            obj_position
+            obj_position,
+            normal: Vector3::new(0.0, 0.0, 0.0),
+            uv: Vector2::new(0.0, 0.0),
+            uv_lod: 0.0,
         }
     }
+
+    pub fn with_normal(mut self, normal: Vector3) -> Self {
+        self.normal = normal;
+        self
+    }
+
+    pub fn with_uv(mut self, uv: Vector2, uv_lod: f32) -> Self {
+        self.uv = uv;
+        self.uv_lod = uv_lod;
+        self
+    }
 }
\ No newline at end of file
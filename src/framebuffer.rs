@@ -43,6 +43,19 @@ impl Framebuffer {
             
         }
     }
+    /// Alpha-blends the current color over the stored pixel if it passes the depth test.
+    /// The depth buffer is left untouched so translucent layers don't occlude what's behind.
+    pub fn blend_pixel(&mut self, x: u32, y: u32, depth: f32, alpha: f32) {
+        if x < self.width && y < self.height && depth < self.depth_buffer[(y*self.width + x) as usize] {
+            let dst = self.color_buffer.get_color(x as i32, y as i32);
+            let src = self.current_color;
+            let a = alpha.clamp(0.0, 1.0);
+            let mix = |s: u8, d: u8| (s as f32 * a + d as f32 * (1.0 - a)) as u8;
+            let out = Color::new(mix(src.r, dst.r), mix(src.g, dst.g), mix(src.b, dst.b), 255);
+            self.color_buffer.draw_pixel(x as i32, y as i32, out);
+        }
+    }
+
     pub fn get_color(&mut self, x: u32, y: u32) {
         self.color_buffer.get_color(x as i32, y as i32);
     }
@@ -60,14 +73,19 @@ impl Framebuffer {
         self.color_buffer.export_image(file_path);
     }
 
+    /// Presents the color buffer, with `overlay` lines of text drawn on top (frame statistics).
     pub fn swap_buffers(
         &self,
         window: &mut RaylibHandle,
         raylib_thread: &RaylibThread,
+        overlay: &[String],
     ) {
         if let Ok(texture) = window.load_texture_from_image(raylib_thread, &self.color_buffer) {
             let mut renderer = window.begin_drawing(raylib_thread);
             renderer.draw_texture(&texture, 0, 0, Color::WHITE);
+            for (i, line) in overlay.iter().enumerate() {
+                renderer.draw_text(line, 10, 10 + 18 * i as i32, 16, Color::LIGHTGRAY);
+            }
         }
     }
 }
\ No newline at end of file
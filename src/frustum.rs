@@ -0,0 +1,144 @@
+// src/frustum.rs
+// Frustum de la cámara (5 planos) para descartar entidades enteras antes de la etapa de vértices.
+use raylib::prelude::*;
+
+use crate::matrix::multiply_matrix_matrix;
+use crate::mesh::Bounds;
+
+/// `normal · p + d >= 0` on the inside; `normal` is unit length.
+#[derive(Clone, Copy, Debug)]
+pub struct Plane {
+    pub normal: Vector3,
+    pub d: f32,
+}
+
+impl Plane {
+    fn new(a: f32, b: f32, c: f32, d: f32) -> Self {
+        let len = (a * a + b * b + c * c).sqrt().max(1e-12);
+        Plane { normal: Vector3::new(a, b, c) / len, d: d / len }
+    }
+
+    pub fn distance(&self, p: Vector3) -> f32 {
+        self.normal.dot(p) + self.d
+    }
+}
+
+/// World-space view frustum: left, right, bottom, top, near. There is no far plane, since
+/// the rasterizer draws whatever lies past it.
+#[derive(Clone, Debug)]
+pub struct Frustum {
+    pub planes: [Plane; 5],
+}
+
+impl Frustum {
+    /// Planes of `projection · view` (Gribb & Hartmann), for the OpenGL clip volume
+    /// -w <= x, y, z <= w that `create_projection_matrix` produces.
+    pub fn new(view: &Matrix, projection: &Matrix) -> Self {
+        let m = multiply_matrix_matrix(projection, view);
+        // Filas de la matriz (raylib guarda por columnas)
+        let r0 = [m.m0, m.m4, m.m8, m.m12];
+        let r1 = [m.m1, m.m5, m.m9, m.m13];
+        let r2 = [m.m2, m.m6, m.m10, m.m14]; // solo para el plano cercano
+        let r3 = [m.m3, m.m7, m.m11, m.m15];
+        let plane = |row: [f32; 4], sign: f32| {
+            Plane::new(r3[0] + sign * row[0], r3[1] + sign * row[1], r3[2] + sign * row[2], r3[3] + sign * row[3])
+        };
+        Frustum { planes: [plane(r0, 1.0), plane(r0, -1.0), plane(r1, 1.0), plane(r1, -1.0), plane(r2, 1.0)] }
+    }
+
+    /// False only if the sphere is entirely outside one of the planes.
+    pub fn intersects_sphere(&self, center: Vector3, radius: f32) -> bool {
+        self.planes.iter().all(|p| p.distance(center) >= -radius)
+    }
+
+    /// Object-space `bounds` placed by `model` (rotation and uniform scale): the sphere is tried
+    /// first, then the box as an oriented box. Conservative: may keep a few invisible objects,
+    /// never drops a visible one.
+    pub fn intersects_bounds(&self, model: &Matrix, bounds: &Bounds) -> bool {
+        // Columnas de la matriz: ejes del objeto en mundo, ya escalados
+        let axes = [
+            Vector3::new(model.m0, model.m1, model.m2),
+            Vector3::new(model.m4, model.m5, model.m6),
+            Vector3::new(model.m8, model.m9, model.m10),
+        ];
+        let scale = axes.iter().map(|a| a.length()).fold(0.0, f32::max);
+        let c = bounds.center;
+        let center = Vector3::new(model.m12, model.m13, model.m14) + axes[0] * c.x + axes[1] * c.y + axes[2] * c.z;
+        if !self.intersects_sphere(center, bounds.radius * scale) {
+            return false;
+        }
+
+        // `bounds.center` es también el centro de la caja
+        let half = (bounds.max - bounds.min) * 0.5;
+        self.planes.iter().all(|p| {
+            let extent = half.x * p.normal.dot(axes[0]).abs() + half.y * p.normal.dot(axes[1]).abs() + half.z * p.normal.dot(axes[2]).abs();
+            p.distance(center) >= -extent
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+    use crate::matrix::{create_model_matrix, create_projection_matrix, create_view_matrix};
+
+    // Cámara en z = 10 mirando al origen, 60° de campo y aspecto 1: a la profundidad del
+    // origen la pantalla abarca |x|, |y| <= 10·tan 30° ≈ 5.77
+    fn camera() -> Frustum {
+        let view = create_view_matrix(Vector3::new(0.0, 0.0, 10.0), Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0));
+        let projection = create_projection_matrix(PI / 3.0, 1.0, 0.5, 100.0);
+        Frustum::new(&view, &projection)
+    }
+
+    fn unit_cube() -> Bounds {
+        let h = Vector3::new(1.0, 1.0, 1.0);
+        Bounds { min: -h, max: h, center: Vector3::new(0.0, 0.0, 0.0), radius: 3.0f32.sqrt() }
+    }
+
+    #[test]
+    fn planes_come_out_of_the_camera() {
+        let frustum = camera();
+        for plane in &frustum.planes {
+            assert!((plane.normal.length() - 1.0).abs() < 1e-5);
+            assert!(plane.distance(Vector3::new(0.0, 0.0, 0.0)) > 0.0);
+        }
+        // Cercano: mira hacia -z y pasa a 0.5 del ojo
+        let near = frustum.planes[4];
+        assert!((near.normal - Vector3::new(0.0, 0.0, -1.0)).length() < 1e-5);
+        assert!(near.distance(Vector3::new(0.0, 0.0, 9.5)).abs() < 1e-4);
+        // Izquierdo y derecho pasan por el ojo con 30° a cada lado
+        let edge = 10.0 * (PI / 6.0).tan();
+        assert!(frustum.planes[0].distance(Vector3::new(-edge, 0.0, 0.0)).abs() < 1e-4);
+        assert!(frustum.planes[1].distance(Vector3::new(edge, 0.0, 0.0)).abs() < 1e-4);
+        assert!(frustum.planes[0].distance(Vector3::new(0.0, 0.0, 10.0)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn spheres_in_front_are_kept_and_behind_culled() {
+        let frustum = camera();
+        assert!(frustum.intersects_sphere(Vector3::new(0.0, 0.0, 0.0), 1.0));
+        assert!(!frustum.intersects_sphere(Vector3::new(0.0, 0.0, 20.0), 1.0));
+        assert!(!frustum.intersects_sphere(Vector3::new(50.0, 0.0, 0.0), 1.0));
+        // Atraviesa el plano cercano o se asoma por el borde derecho
+        assert!(frustum.intersects_sphere(Vector3::new(0.0, 0.0, 10.0), 1.0));
+        assert!(frustum.intersects_sphere(Vector3::new(6.5, 0.0, 0.0), 1.0));
+        // Sin plano lejano: lo que está más allá de 100 se conserva
+        assert!(frustum.intersects_sphere(Vector3::new(0.0, 0.0, -500.0), 1.0));
+    }
+
+    #[test]
+    fn boxes_follow_their_model_matrix() {
+        let frustum = camera();
+        let cube = unit_cube();
+        let place = |x: f32, z: f32, scale: f32| create_model_matrix(Vector3::new(x, 0.0, z), scale, Vector3::new(0.3, 0.7, 0.0));
+        assert!(frustum.intersects_bounds(&place(0.0, 0.0, 1.0), &cube));
+        assert!(!frustum.intersects_bounds(&place(0.0, 20.0, 1.0), &cube));
+        // A x = 20: con escala 5 queda fuera, con escala 20 llega hasta el centro de la pantalla
+        assert!(!frustum.intersects_bounds(&place(20.0, 0.0, 5.0), &cube));
+        assert!(frustum.intersects_bounds(&place(20.0, 0.0, 20.0), &cube));
+        // Un centro desplazado en los bounds también se mueve con la matriz
+        let offset = Bounds { center: Vector3::new(0.0, 0.0, 30.0), min: Vector3::new(-1.0, -1.0, 29.0), max: Vector3::new(1.0, 1.0, 31.0), ..cube };
+        assert!(!frustum.intersects_bounds(&Matrix::identity(), &offset));
+    }
+}
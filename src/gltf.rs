@@ -0,0 +1,791 @@
+// src/gltf.rs
+// Importador glTF 2.0: .gltf (buffers externos o en data URI) y .glb binario.
+// Produce mallas indexadas con normales y UVs, materiales PBR y la jerarquía de nodos.
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use raylib::prelude::*;
+
+use crate::entity::{Entity, MeshPart, Motion, CREASE_ANGLE};
+use crate::json::{self, Json, JsonError};
+use crate::material::Material;
+use crate::mesh::Mesh;
+use crate::shaders::FragmentShader;
+use crate::texture::Texture;
+
+#[derive(Debug)]
+pub enum GltfError {
+    Io { path: String, message: String },
+    Json { path: String, error: JsonError },
+    Format { path: String, message: String },
+}
+
+impl fmt::Display for GltfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GltfError::Io { path, message } => write!(f, "{}: {}", path, message),
+            GltfError::Json { path, error } => write!(f, "{}:{}", path, error),
+            GltfError::Format { path, message } => write!(f, "{}: {}", path, message),
+        }
+    }
+}
+
+impl std::error::Error for GltfError {}
+
+/// One glTF primitive: geometry plus the material it is drawn with.
+pub struct GltfPrimitive {
+    pub mesh: Mesh,
+    pub material: Material,
+}
+
+pub struct GltfNode {
+    pub name: String,
+    pub parent: Option<usize>,  // índice en `GltfScene::nodes`; los padres van antes que sus hijos
+    pub translation: Vector3,
+    pub rotation: Vector4,      // cuaternión (x, y, z, w)
+    pub scale: Vector3,
+    pub primitives: Vec<GltfPrimitive>,
+}
+
+pub struct GltfScene {
+    pub nodes: Vec<GltfNode>,
+}
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const CHUNK_JSON: u32 = 0x4E4F_534A;
+const CHUNK_BIN: u32 = 0x004E_4942;
+
+const MODE_TRIANGLES: usize = 4;
+
+/// Tope de elementos de un accessor sin bufferView (todo ceros), que no tiene buffer contra
+/// el cual acotar `count`
+const MAX_ZERO_ACCESSOR: usize = 1 << 24;
+
+/// Loads the default scene of a `.gltf` or `.glb` file.
+pub fn load(path: &Path) -> Result<GltfScene, GltfError> {
+    let label = path.display().to_string();
+    let bytes = std::fs::read(path).map_err(|e| GltfError::Io { path: label.clone(), message: e.to_string() })?;
+    let base_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    from_bytes(&bytes, &base_dir, &label)
+}
+
+/// Parses a `.gltf` or `.glb` already in memory; relative URIs resolve against `base_dir` and
+/// errors are reported against `label`.
+pub fn from_bytes(bytes: &[u8], base_dir: &Path, label: &str) -> Result<GltfScene, GltfError> {
+    let label = label.to_string();
+    let format = |message: &str| GltfError::Format { path: label.clone(), message: message.to_string() };
+
+    let (text, bin) = if bytes.starts_with(GLB_MAGIC) { split_glb(bytes).map_err(|m| format(&m))? } else { (bytes, None) };
+    let text = std::str::from_utf8(text).map_err(|_| format("JSON is not valid UTF-8"))?;
+    let doc = json::parse(text).map_err(|error| GltfError::Json { path: label.clone(), error })?;
+
+    let version = doc.get("asset").get("version").as_str().unwrap_or("");
+    if !version.starts_with('2') {
+        return Err(format(&format!("unsupported glTF version '{}'", version)));
+    }
+
+    let base_dir = base_dir.to_path_buf();
+    let buffers = doc
+        .get("buffers")
+        .items()
+        .iter()
+        .enumerate()
+        .map(|(i, buffer)| load_buffer(buffer, i, bin, &base_dir))
+        .collect::<Result<Vec<_>, String>>()
+        .map_err(|m| format(&m))?;
+
+    let mut importer = Importer { doc: &doc, buffers, base_dir, label: label.clone(), images: HashMap::new(), materials: HashMap::new() };
+    importer.scene().map_err(|m| format(&m))
+}
+
+/// Splits a GLB container into its JSON chunk and optional BIN chunk.
+fn split_glb(bytes: &[u8]) -> Result<(&[u8], Option<&[u8]>), String> {
+    let u32_at = |at: usize| bytes.get(at..at + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+    let version = u32_at(4).ok_or("truncated GLB header")?;
+    if version != 2 {
+        return Err(format!("unsupported GLB version {}", version));
+    }
+    let total = (u32_at(8).ok_or("truncated GLB header")? as usize).min(bytes.len());
+
+    let (mut json, mut bin) = (None, None);
+    let mut at = 12;
+    while at + 8 <= total {
+        let length = u32_at(at).unwrap_or(0) as usize;
+        let kind = u32_at(at + 4).unwrap_or(0);
+        let data = bytes.get(at + 8..at + 8 + length).ok_or("GLB chunk runs past the end of the file")?;
+        match kind {
+            CHUNK_JSON if json.is_none() => json = Some(data),
+            CHUNK_BIN if bin.is_none() => bin = Some(data),
+            _ => {} // los tipos de chunk desconocidos se ignoran (spec)
+        }
+        at += 8 + length;
+    }
+    Ok((json.ok_or("GLB has no JSON chunk")?, bin))
+}
+
+fn load_buffer(buffer: &Json, index: usize, bin: Option<&[u8]>, base_dir: &Path) -> Result<Vec<u8>, String> {
+    let length = buffer.get("byteLength").as_usize().unwrap_or(0);
+    let data = match buffer.get("uri").as_str() {
+        Some(uri) => read_uri(uri, base_dir)?.1,
+        None if index == 0 => bin.ok_or("buffer 0 has no uri and the file has no BIN chunk")?.to_vec(),
+        None => return Err(format!("buffer {} has no uri", index)),
+    };
+    if data.len() < length {
+        return Err(format!("buffer {} is {} bytes, expected {}", index, data.len(), length));
+    }
+    Ok(data)
+}
+
+/// Resolves a `data:` URI or a path relative to the glTF file. Returns (mime type, bytes).
+fn read_uri(uri: &str, base_dir: &Path) -> Result<(Option<String>, Vec<u8>), String> {
+    if let Some(rest) = uri.strip_prefix("data:") {
+        let (header, payload) = rest.split_once(',').ok_or("malformed data URI")?;
+        let mime = header.split(';').next().filter(|m| !m.is_empty()).map(str::to_string);
+        if !header.ends_with(";base64") {
+            return Err("only base64 data URIs are supported".to_string());
+        }
+        return decode_base64(payload).map(|bytes| (mime, bytes));
+    }
+    let path = base_dir.join(percent_decode(uri));
+    std::fs::read(&path).map(|bytes| (None, bytes)).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn decode_base64(text: &str) -> Result<Vec<u8>, String> {
+    let value = |c: u8| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' | b'-' => Some(62),
+        b'/' | b'_' => Some(63),
+        _ => None,
+    };
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut acc, mut bits) = (0u32, 0u32);
+    for c in text.bytes().filter(|c| !c.is_ascii_whitespace() && *c != b'=') {
+        let v = value(c).ok_or("invalid base64 in data URI")?;
+        acc = (acc << 6) | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    Ok(out)
+}
+
+fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok()).and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                out.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+struct Importer<'a> {
+    doc: &'a Json,
+    buffers: Vec<Vec<u8>>,
+    base_dir: PathBuf,
+    label: String,
+    images: HashMap<usize, Option<Arc<Texture>>>,
+    materials: HashMap<usize, Material>,
+}
+
+impl Importer<'_> {
+    /// Nodes of the default scene, depth first so every parent precedes its children.
+    fn scene(&mut self) -> Result<GltfScene, String> {
+        let doc = self.doc;
+        let node_count = doc.get("nodes").items().len();
+        let scene_index = doc.get("scene").as_usize().unwrap_or(0);
+        let roots: Vec<usize> = match doc.get("scenes").items().get(scene_index) {
+            Some(scene) => scene.get("nodes").items().iter().filter_map(Json::as_usize).collect(),
+            // Sin escenas: raíces = nodos que no son hijos de nadie
+            None => {
+                let children: Vec<usize> =
+                    doc.get("nodes").items().iter().flat_map(|n| n.get("children").items().iter().filter_map(Json::as_usize)).collect();
+                (0..node_count).filter(|i| !children.contains(i)).collect()
+            }
+        };
+
+        let mut nodes = Vec::new();
+        let mut visited = vec![false; node_count];
+        let mut stack: Vec<(usize, Option<usize>)> = roots.into_iter().rev().map(|r| (r, None)).collect();
+        while let Some((index, parent)) = stack.pop() {
+            if index >= node_count || visited[index] {
+                continue; // índice inválido o ciclo: se ignora
+            }
+            visited[index] = true;
+            let node = &doc.get("nodes").items()[index];
+            let (translation, rotation, scale) = node_transform(node);
+            let primitives = match node.get("mesh").as_usize() {
+                Some(mesh) => self.mesh(mesh)?,
+                None => Vec::new(),
+            };
+            let name = node.get("name").as_str().map_or_else(|| format!("node{}", index), str::to_string);
+            nodes.push(GltfNode { name, parent, translation, rotation, scale, primitives });
+
+            let me = nodes.len() - 1;
+            for child in node.get("children").items().iter().rev().filter_map(Json::as_usize) {
+                stack.push((child, Some(me)));
+            }
+        }
+        Ok(GltfScene { nodes })
+    }
+
+    fn mesh(&mut self, index: usize) -> Result<Vec<GltfPrimitive>, String> {
+        let doc = self.doc;
+        let mesh = doc.get("meshes").at(index);
+        if mesh.is_null() {
+            return Err(format!("mesh {} does not exist", index));
+        }
+        let mut primitives = Vec::new();
+        for (p, primitive) in mesh.get("primitives").items().iter().enumerate() {
+            let mode = primitive.get("mode").as_usize().unwrap_or(MODE_TRIANGLES);
+            if mode != MODE_TRIANGLES {
+                eprintln!("{}: mesh {} primitive {} skipped: mode {} is not triangles", self.label, index, p, mode);
+                continue;
+            }
+            let attributes = primitive.get("attributes");
+            let position = attributes.get("POSITION").as_usize().ok_or(format!("mesh {} primitive {} has no POSITION", index, p))?;
+            let positions = to_vec3(&self.accessor(position)?);
+            let normals = match attributes.get("NORMAL").as_usize() {
+                Some(a) => to_vec3(&self.accessor(a)?),
+                None => Vec::new(),
+            };
+            let uvs = match attributes.get("TEXCOORD_0").as_usize() {
+                Some(a) => to_vec2(&self.accessor(a)?),
+                None => Vec::new(),
+            };
+            let indices: Vec<u32> = match primitive.get("indices").as_usize() {
+                Some(a) => self.accessor(a)?.values.iter().map(|&i| i as u32).collect(),
+                None => (0..positions.len() as u32).collect(),
+            };
+            if let Some(&bad) = indices.iter().find(|&&i| i as usize >= positions.len()) {
+                return Err(format!("mesh {} primitive {}: index {} out of range", index, p, bad));
+            }
+            let indices: Vec<u32> = indices[..indices.len() / 3 * 3].to_vec();
+
+            let uvs = if uvs.len() == positions.len() { uvs } else { Vec::new() };
+            let mesh = if normals.len() == positions.len() {
+                let normals = normals.into_iter().map(|n| if n.length() > 0.0 { n.normalized() } else { n }).collect();
+                Mesh { positions, normals, uvs, indices }
+            } else {
+                // Sin NORMAL la spec pide normales planas; se suavizan respetando aristas vivas
+                let soup: Vec<Vector3> = indices.iter().map(|&i| positions[i as usize]).collect();
+                let soup_uvs: Vec<Vector2> = if uvs.is_empty() { Vec::new() } else { indices.iter().map(|&i| uvs[i as usize]).collect() };
+                Mesh::from_soup_smooth(&soup, &soup_uvs, CREASE_ANGLE)
+            };
+
+            let material = match primitive.get("material").as_usize() {
+                Some(m) => self.material(m),
+                None => Material::default(),
+            };
+            primitives.push(GltfPrimitive { mesh, material });
+        }
+        Ok(primitives)
+    }
+
+    /// Reads an accessor as `f64`s (`width` per element); normalized integers map to [0,1]/[-1,1].
+    fn accessor(&self, index: usize) -> Result<Accessor, String> {
+        let accessor = self.doc.get("accessors").at(index);
+        if accessor.is_null() {
+            return Err(format!("accessor {} does not exist", index));
+        }
+        if !accessor.get("sparse").is_null() {
+            return Err(format!("accessor {}: sparse accessors are not supported", index));
+        }
+        let count = accessor.get("count").as_usize().unwrap_or(0);
+        let width = match accessor.get("type").as_str() {
+            Some("SCALAR") => 1,
+            Some("VEC2") => 2,
+            Some("VEC3") => 3,
+            Some("VEC4") => 4,
+            other => return Err(format!("accessor {}: unsupported type {:?}", index, other)),
+        };
+        let component = accessor.get("componentType").as_usize().unwrap_or(0);
+        let size = match component {
+            5120 | 5121 => 1,
+            5122 | 5123 => 2,
+            5125 | 5126 => 4,
+            _ => return Err(format!("accessor {}: unknown componentType {}", index, component)),
+        };
+        let normalized = accessor.get("normalized").as_bool().unwrap_or(false);
+
+        // Sin bufferView el accessor vale ceros (spec)
+        let Some(view_index) = accessor.get("bufferView").as_usize() else {
+            if count > MAX_ZERO_ACCESSOR {
+                return Err(format!("accessor {}: count {} is too large", index, count));
+            }
+            return Ok(Accessor { width, values: vec![0.0; count * width] });
+        };
+        let view = self.doc.get("bufferViews").at(view_index);
+        let buffer = view.get("buffer").as_usize().and_then(|b| self.buffers.get(b)).ok_or(format!("bufferView {} has no buffer", view_index))?;
+
+        // `count`, los offsets y el stride vienen del archivo: todo se suma con checked_* y el
+        // último elemento tiene que caber en el bufferView antes de reservar memoria
+        let past_end = || format!("accessor {} reads past the end of bufferView {}", index, view_index);
+        let view_offset = view.get("byteOffset").as_usize().unwrap_or(0);
+        let view_end = view_offset
+            .checked_add(view.get("byteLength").as_usize().unwrap_or(0))
+            .filter(|&end| end <= buffer.len())
+            .ok_or_else(|| format!("bufferView {} runs past the end of its buffer", view_index))?;
+        let start = view_offset.checked_add(accessor.get("byteOffset").as_usize().unwrap_or(0)).ok_or_else(past_end)?;
+        let stride = view.get("byteStride").as_usize().filter(|&s| s > 0).unwrap_or(size * width);
+        if count > 0 {
+            let end = stride
+                .checked_mul(count - 1)
+                .and_then(|last| last.checked_add(start))
+                .and_then(|last| last.checked_add(size * width))
+                .ok_or_else(past_end)?;
+            if end > view_end {
+                return Err(past_end());
+            }
+        }
+
+        let read = |at: usize| -> f64 {
+            let b = &buffer[at..at + size];
+            match (component, normalized) {
+                (5120, false) => b[0] as i8 as f64,
+                (5120, true) => (b[0] as i8 as f64 / 127.0).max(-1.0),
+                (5121, false) => b[0] as f64,
+                (5121, true) => b[0] as f64 / 255.0,
+                (5122, false) => i16::from_le_bytes([b[0], b[1]]) as f64,
+                (5122, true) => (i16::from_le_bytes([b[0], b[1]]) as f64 / 32767.0).max(-1.0),
+                (5123, false) => u16::from_le_bytes([b[0], b[1]]) as f64,
+                (5123, true) => u16::from_le_bytes([b[0], b[1]]) as f64 / 65535.0,
+                (5125, _) => u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+                _ => f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+            }
+        };
+        let mut values = Vec::with_capacity(count * width);
+        for element in 0..count {
+            for c in 0..width {
+                values.push(read(start + element * stride + c * size));
+            }
+        }
+        Ok(Accessor { width, values })
+    }
+
+    /// Maps a glTF metallic/roughness material onto ours; textures that fail to load are skipped.
+    fn material(&mut self, index: usize) -> Material {
+        if let Some(m) = self.materials.get(&index) {
+            return m.clone();
+        }
+        let doc = self.doc;
+        let m = doc.get("materials").at(index);
+        let pbr = m.get("pbrMetallicRoughness");
+        let defaults = Material::default();
+
+        let base = pbr.get("baseColorFactor").as_f32s();
+        let (albedo, alpha) = match base.as_slice() {
+            [r, g, b, a] => (Vector3::new(*r, *g, *b), *a),
+            _ => (Vector3::new(1.0, 1.0, 1.0), 1.0),
+        };
+        let emissive = match m.get("emissiveFactor").as_f32s().as_slice() {
+            [r, g, b] => Vector3::new(*r, *g, *b),
+            _ => defaults.emissive,
+        };
+        let strength = m.get("extensions").get("KHR_materials_emissive_strength").get("emissiveStrength").as_f32().unwrap_or(1.0);
+        // BLEND mezcla con el alfa; MASK lo recorta contra alphaCutoff (0.5 por defecto)
+        let alpha_mode = m.get("alphaMode").as_str().unwrap_or("OPAQUE");
+        let alpha_cutoff = (alpha_mode == "MASK").then(|| m.get("alphaCutoff").as_f32().unwrap_or(0.5));
+
+        // metallicRoughnessTexture: G = rugosidad, B = metalicidad (igual que lee `shade_pbr`)
+        let metal_rough = self.texture(pbr.get("metallicRoughnessTexture"));
+        let material = Material {
+            name: m.get("name").as_str().map_or_else(|| format!("material{}", index), str::to_string),
+            albedo,
+            metallic: pbr.get("metallicFactor").as_f32().unwrap_or(1.0).clamp(0.0, 1.0),
+            roughness: pbr.get("roughnessFactor").as_f32().unwrap_or(1.0).clamp(0.04, 1.0),
+            specular: defaults.specular,
+            emissive: emissive * strength,
+            ao: 1.0,
+            opacity: if alpha_mode == "OPAQUE" { 1.0 } else { alpha.clamp(0.0, 1.0) },
+            alpha_cutoff,
+            albedo_map: self.texture(pbr.get("baseColorTexture")),
+            roughness_map: metal_rough.clone(),
+            metallic_map: metal_rough,
+            emissive_map: self.texture(m.get("emissiveTexture")),
+            ao_map: self.texture(m.get("occlusionTexture")),
+        };
+        self.materials.insert(index, material.clone());
+        material
+    }
+
+    /// Texture referenced by a textureInfo object (`{"index": n}`), decoded once per image.
+    fn texture(&mut self, info: &Json) -> Option<Arc<Texture>> {
+        let doc = self.doc;
+        let texture = doc.get("textures").at(info.get("index").as_usize()?);
+        let image_index = texture.get("source").as_usize()?;
+        if let Some(cached) = self.images.get(&image_index) {
+            return cached.clone();
+        }
+
+        let image = doc.get("images").at(image_index);
+        let label = format!("{} image {}", self.label, image_index);
+        let loaded = self.image_bytes(image).and_then(|(mime, bytes)| {
+            let file_type = match mime.as_deref() {
+                Some("image/png") => ".png",
+                Some("image/jpeg") => ".jpg",
+                Some(other) => return Err(format!("unsupported image type '{}'", other)),
+                None => return Err("unknown image type".to_string()),
+            };
+            Texture::load_from_memory(&bytes, file_type, &label).map_err(|e| e.to_string())
+        });
+        let texture = match loaded {
+            Ok(t) => Some(Arc::new(t)),
+            Err(e) => {
+                eprintln!("{}: {}", label, e);
+                None
+            }
+        };
+        self.images.insert(image_index, texture.clone());
+        texture
+    }
+
+    /// Encoded bytes and mime type of an image: embedded in a bufferView, a data URI or a file.
+    fn image_bytes(&self, image: &Json) -> Result<(Option<String>, Vec<u8>), String> {
+        let mime = image.get("mimeType").as_str().map(str::to_string);
+        if let Some(view_index) = image.get("bufferView").as_usize() {
+            let view = self.doc.get("bufferViews").at(view_index);
+            let buffer = view.get("buffer").as_usize().and_then(|b| self.buffers.get(b)).ok_or("image bufferView has no buffer")?;
+            let start = view.get("byteOffset").as_usize().unwrap_or(0);
+            let end = start.checked_add(view.get("byteLength").as_usize().unwrap_or(0)).ok_or("image bufferView out of range")?;
+            let bytes = buffer.get(start..end).ok_or("image bufferView out of range")?;
+            return Ok((mime, bytes.to_vec()));
+        }
+        let uri = image.get("uri").as_str().ok_or("image has neither bufferView nor uri")?;
+        let (uri_mime, bytes) = read_uri(uri, &self.base_dir)?;
+        // Para archivos sueltos el tipo sale de la extensión
+        let by_extension = || {
+            let lower = uri.to_ascii_lowercase();
+            if lower.ends_with(".png") {
+                Some("image/png".to_string())
+            } else if lower.ends_with(".jpg") || lower.ends_with(".jpeg") {
+                Some("image/jpeg".to_string())
+            } else {
+                None
+            }
+        };
+        Ok((mime.or(uri_mime).or_else(by_extension), bytes))
+    }
+}
+
+struct Accessor {
+    width: usize,
+    values: Vec<f64>,
+}
+
+fn to_vec3(a: &Accessor) -> Vec<Vector3> {
+    a.values.chunks_exact(a.width).filter(|_| a.width >= 3).map(|c| Vector3::new(c[0] as f32, c[1] as f32, c[2] as f32)).collect()
+}
+
+fn to_vec2(a: &Accessor) -> Vec<Vector2> {
+    a.values.chunks_exact(a.width).filter(|_| a.width >= 2).map(|c| Vector2::new(c[0] as f32, c[1] as f32)).collect()
+}
+
+/// Local TRS of a node, from its `translation`/`rotation`/`scale` or decomposed from `matrix`.
+fn node_transform(node: &Json) -> (Vector3, Vector4, Vector3) {
+    let m = node.get("matrix").as_f32s();
+    if m.len() == 16 {
+        // Column-major: columnas 0..2 = ejes escalados, columna 3 = traslación
+        let col = |c: usize| Vector3::new(m[4 * c], m[4 * c + 1], m[4 * c + 2]);
+        let (x, y, z) = (col(0), col(1), col(2));
+        let scale = Vector3::new(x.length(), y.length(), z.length());
+        let unit = |v: Vector3, s: f32| if s > 0.0 { v / s } else { v };
+        let rotation = quaternion_from_axes(unit(x, scale.x), unit(y, scale.y), unit(z, scale.z));
+        return (col(3), rotation, scale);
+    }
+    let t = node.get("translation").as_f32s();
+    let r = node.get("rotation").as_f32s();
+    let s = node.get("scale").as_f32s();
+    (
+        if t.len() == 3 { Vector3::new(t[0], t[1], t[2]) } else { Vector3::new(0.0, 0.0, 0.0) },
+        if r.len() == 4 { Vector4::new(r[0], r[1], r[2], r[3]) } else { Vector4::new(0.0, 0.0, 0.0, 1.0) },
+        if s.len() == 3 { Vector3::new(s[0], s[1], s[2]) } else { Vector3::new(1.0, 1.0, 1.0) },
+    )
+}
+
+/// Unit quaternion (x, y, z, w) of the rotation whose columns are the axes `x`, `y`, `z`.
+fn quaternion_from_axes(x: Vector3, y: Vector3, z: Vector3) -> Vector4 {
+    let trace = x.x + y.y + z.z;
+    let q = if trace > 0.0 {
+        let s = (trace + 1.0).sqrt() * 2.0;
+        Vector4::new((y.z - z.y) / s, (z.x - x.z) / s, (x.y - y.x) / s, 0.25 * s)
+    } else if x.x > y.y && x.x > z.z {
+        let s = (1.0 + x.x - y.y - z.z).sqrt() * 2.0;
+        Vector4::new(0.25 * s, (y.x + x.y) / s, (z.x + x.z) / s, (y.z - z.y) / s)
+    } else if y.y > z.z {
+        let s = (1.0 + y.y - x.x - z.z).sqrt() * 2.0;
+        Vector4::new((y.x + x.y) / s, 0.25 * s, (z.y + y.z) / s, (z.x - x.z) / s)
+    } else {
+        let s = (1.0 + z.z - x.x - y.y).sqrt() * 2.0;
+        Vector4::new((z.x + x.z) / s, (z.y + y.z) / s, 0.25 * s, (x.y - y.x) / s)
+    };
+    let len = (q.x * q.x + q.y * q.y + q.z * q.z + q.w * q.w).sqrt();
+    if len > 0.0 { Vector4::new(q.x / len, q.y / len, q.z / len, q.w / len) } else { Vector4::new(0.0, 0.0, 0.0, 1.0) }
+}
+
+impl GltfScene {
+    /// One entity per node, parented with `Motion::Attached` under a new root entity `name`
+    /// placed at `translation` with uniform `scale`. Entities only scale uniformly, so a node's
+    /// non-uniform scale is baked into its own vertices and only the mean passes to children.
+    pub fn to_entities(&self, name: &str, translation: Vector3, scale: f32) -> Vec<Entity> {
+        // Los nombres de entidad son `&'static str`: los de un modelo cargado viven todo el programa
+        let leak = |s: String| -> &'static str { Box::leak(s.into_boxed_str()) };
+        let root_name = leak(name.to_string());
+
+        let mut entities = vec![
+            Entity::from_parts(root_name, translation, scale, Motion::Static, Vec::new(), false).with_basis(),
+        ];
+
+        let mut names: Vec<&'static str> = Vec::with_capacity(self.nodes.len());
+        for (i, node) in self.nodes.iter().enumerate() {
+            let mut entity_name = format!("{}/{}", name, node.name);
+            if names.contains(&entity_name.as_str()) {
+                entity_name = format!("{}#{}", entity_name, i);
+            }
+            let entity_name = leak(entity_name);
+            names.push(entity_name);
+
+            let uniform = ((node.scale.x * node.scale.y * node.scale.z).abs()).cbrt();
+            let residual = if uniform > 0.0 { node.scale / uniform } else { Vector3::new(1.0, 1.0, 1.0) };
+            let parts = node
+                .primitives
+                .iter()
+                .enumerate()
+                .map(|(p, primitive)| MeshPart {
+                    name: format!("{}#{}", node.name, p),
+                    mesh: Arc::new(scale_mesh(&primitive.mesh, residual)),
+                    fshader: FragmentShader::Pbr { material: primitive.material.clone() },
+                })
+                .collect();
+
+            let parent = node.parent.map_or(root_name, |p| names[p]);
+            entities.push(
+                Entity::from_parts(
+                    entity_name,
+                    translation,
+                    scale,
+                    Motion::Attached { parent, translation: node.translation, rotation: node.rotation, scale: uniform },
+                    parts,
+                    false,
+                )
+                .with_basis(),
+            );
+        }
+        entities
+    }
+}
+
+/// Applies a per-axis scale to positions (normals get the inverse-transpose, i.e. 1/scale).
+fn scale_mesh(mesh: &Mesh, s: Vector3) -> Mesh {
+    if s == Vector3::new(1.0, 1.0, 1.0) {
+        return mesh.clone();
+    }
+    let mut out = mesh.clone();
+    for p in &mut out.positions {
+        *p = Vector3::new(p.x * s.x, p.y * s.y, p.z * s.z);
+    }
+    for n in &mut out.normals {
+        let scaled = Vector3::new(n.x / s.x, n.y / s.y, n.z / s.z);
+        *n = if scaled.length() > 0.0 { scaled.normalized() } else { *n };
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base64(bytes: &[u8]) -> String {
+        const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let mut out = String::new();
+        for chunk in bytes.chunks(3) {
+            let n = chunk.iter().enumerate().fold(0u32, |acc, (i, &b)| acc | (b as u32) << (16 - 8 * i));
+            for k in 0..4 {
+                out.push(if k <= chunk.len() { ALPHABET[(n >> (18 - 6 * k) & 63) as usize] as char } else { '=' });
+            }
+        }
+        out
+    }
+
+    /// Triangle (0,0,0) (1,0,0) (0,1,0) as f32 positions followed by u16 indices, padded to 4 bytes.
+    fn triangle_buffer() -> Vec<u8> {
+        let mut data = Vec::new();
+        for v in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+            data.extend_from_slice(&v.to_le_bytes());
+        }
+        for i in [0u16, 1, 2] {
+            data.extend_from_slice(&i.to_le_bytes());
+        }
+        data.extend_from_slice(&[0, 0]);
+        data
+    }
+
+    /// glTF for `triangle_buffer`; without `uri` the buffer is the GLB BIN chunk.
+    fn triangle_json(uri: Option<&str>) -> String {
+        let uri = uri.map_or(String::new(), |u| format!(r#""uri": "{}", "#, u));
+        format!(
+            r#"{{
+                "asset": {{"version": "2.0"}},
+                "scene": 0,
+                "scenes": [{{"nodes": [0]}}],
+                "nodes": [{{"name": "tri", "mesh": 0, "translation": [1, 2, 3]}}],
+                "meshes": [{{"primitives": [{{"attributes": {{"POSITION": 0}}, "indices": 1, "material": 0}}]}}],
+                "materials": [{{"alphaMode": "MASK", "pbrMetallicRoughness": {{"baseColorFactor": [1, 0, 0, 0.8], "metallicFactor": 0.25}}}}],
+                "buffers": [{{{}"byteLength": 44}}],
+                "bufferViews": [
+                    {{"buffer": 0, "byteOffset": 0, "byteLength": 36}},
+                    {{"buffer": 0, "byteOffset": 36, "byteLength": 6}}
+                ],
+                "accessors": [
+                    {{"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3"}},
+                    {{"bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR"}}
+                ]
+            }}"#,
+            uri
+        )
+    }
+
+    fn assert_triangle(scene: &GltfScene) {
+        assert_eq!(scene.nodes.len(), 1);
+        let node = &scene.nodes[0];
+        assert_eq!(node.name, "tri");
+        assert_eq!(node.parent, None);
+        assert_eq!(node.translation, Vector3::new(1.0, 2.0, 3.0));
+
+        let primitive = &node.primitives[0];
+        assert_eq!(primitive.mesh.indices, vec![0, 1, 2]);
+        assert_eq!(primitive.mesh.positions[1], Vector3::new(1.0, 0.0, 0.0));
+        // Sin NORMAL se calculan: el triángulo es antihorario visto desde +z
+        assert!(primitive.mesh.normals.iter().all(|n| (*n - Vector3::new(0.0, 0.0, 1.0)).length() < 1e-6));
+
+        let material = &primitive.material;
+        assert_eq!(material.albedo, Vector3::new(1.0, 0.0, 0.0));
+        assert_eq!(material.metallic, 0.25);
+        assert!((material.opacity - 0.8).abs() < 1e-6);
+        assert_eq!(material.alpha_cutoff, Some(0.5));
+    }
+
+    fn glb(chunks: &[(u32, &[u8])]) -> Vec<u8> {
+        let mut body = Vec::new();
+        for (kind, data) in chunks {
+            body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            body.extend_from_slice(&kind.to_le_bytes());
+            body.extend_from_slice(data);
+        }
+        let mut out = GLB_MAGIC.to_vec();
+        out.extend_from_slice(&2u32.to_le_bytes());
+        out.extend_from_slice(&(12 + body.len() as u32).to_le_bytes());
+        out.extend_from_slice(&body);
+        out
+    }
+
+    #[test]
+    fn embedded_data_uri_triangle() {
+        let uri = format!("data:application/octet-stream;base64,{}", base64(&triangle_buffer()));
+        let scene = from_bytes(triangle_json(Some(&uri)).as_bytes(), Path::new(""), "tri.gltf").unwrap();
+        assert_triangle(&scene);
+    }
+
+    #[test]
+    fn glb_chunks_are_split() {
+        // JSON rellenado con espacios a múltiplo de 4, como pide la spec
+        let mut json = triangle_json(None).into_bytes();
+        json.resize(json.len().div_ceil(4) * 4, b' ');
+        let bin = triangle_buffer();
+        let bytes = glb(&[(CHUNK_JSON, &json), (0x1234_5678, &[1, 2, 3, 4]), (CHUNK_BIN, &bin)]);
+
+        let (text, found_bin) = split_glb(&bytes).unwrap();
+        assert_eq!(text, json.as_slice());
+        assert_eq!(found_bin, Some(bin.as_slice()));
+        assert_triangle(&from_bytes(&bytes, Path::new(""), "tri.glb").unwrap());
+
+        let truncated = &bytes[..bytes.len() - 1];
+        let mut header_says_more = truncated.to_vec();
+        header_says_more[8..12].copy_from_slice(&(bytes.len() as u32).to_le_bytes());
+        assert_eq!(split_glb(&header_says_more).unwrap_err(), "GLB chunk runs past the end of the file");
+        assert_eq!(split_glb(&glb(&[(CHUNK_BIN, &bin)])).unwrap_err(), "GLB has no JSON chunk");
+    }
+
+    #[test]
+    fn json_errors_keep_line_and_column() {
+        let err = from_bytes(b"{\n \"asset\": }", Path::new(""), "bad.gltf").err().expect("should fail");
+        assert!(matches!(&err, GltfError::Json { error, .. } if (error.line, error.col) == (2, 11)));
+        assert_eq!(err.to_string(), "bad.gltf:2:11: unexpected character");
+    }
+
+    #[test]
+    fn accessors_follow_stride_and_normalization() {
+        // Vista 0 intercalada (stride 16): VEC3 f32 + VEC2 u8 normalizado + 2 bytes de relleno
+        let mut buffer = Vec::new();
+        for (p, uv) in [([1.0f32, 2.0, 3.0], [255u8, 0]), ([4.0, 5.0, 6.0], [51, 255])] {
+            for v in p {
+                buffer.extend_from_slice(&v.to_le_bytes());
+            }
+            buffer.extend_from_slice(&[uv[0], uv[1], 0, 0]);
+        }
+        // Vista 1: dos i16 y cuatro bytes más
+        buffer.extend_from_slice(&(-32768i16).to_le_bytes());
+        buffer.extend_from_slice(&32767i16.to_le_bytes());
+        buffer.extend_from_slice(&[0; 4]);
+
+        let doc = json::parse(
+            r#"{
+                "bufferViews": [
+                    {"buffer": 0, "byteLength": 32, "byteStride": 16},
+                    {"buffer": 0, "byteOffset": 32, "byteLength": 8}
+                ],
+                "accessors": [
+                    {"bufferView": 0, "componentType": 5126, "count": 2, "type": "VEC3"},
+                    {"bufferView": 0, "byteOffset": 12, "componentType": 5121, "normalized": true, "count": 2, "type": "VEC2"},
+                    {"bufferView": 1, "componentType": 5122, "normalized": true, "count": 2, "type": "SCALAR"},
+                    {"bufferView": 1, "componentType": 5122, "count": 2, "type": "SCALAR"},
+                    {"componentType": 5126, "count": 2, "type": "VEC2"},
+                    {"bufferView": 1, "componentType": 5126, "count": 3, "type": "SCALAR"},
+                    {"bufferView": 1, "componentType": 5126, "count": 5e18, "type": "SCALAR"},
+                    {"componentType": 5126, "count": 1e12, "type": "VEC3"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let importer = Importer {
+            doc: &doc,
+            buffers: vec![buffer],
+            base_dir: PathBuf::new(),
+            label: String::from("test"),
+            images: HashMap::new(),
+            materials: HashMap::new(),
+        };
+        let values = |i: usize| importer.accessor(i).map(|a| (a.width, a.values));
+
+        assert_eq!(values(0).unwrap(), (3, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]));
+        let (width, uvs) = values(1).unwrap();
+        assert_eq!(width, 2);
+        for (got, want) in uvs.iter().zip([1.0, 0.0, 0.2, 1.0]) {
+            assert!((got - want).abs() < 1e-9, "{} != {}", got, want);
+        }
+        assert_eq!(values(2).unwrap().1, vec![-1.0, 1.0]);
+        assert_eq!(values(3).unwrap().1, vec![-32768.0, 32767.0]);
+        assert_eq!(values(4).unwrap().1, vec![0.0; 4]);
+
+        assert_eq!(values(5).unwrap_err(), "accessor 5 reads past the end of bufferView 1");
+        // `count` enorme: el cálculo desborda y se rechaza sin reservar memoria
+        assert_eq!(values(6).unwrap_err(), "accessor 6 reads past the end of bufferView 1");
+        assert_eq!(values(7).unwrap_err(), "accessor 7: count 1000000000000 is too large");
+    }
+}
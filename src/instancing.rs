@@ -0,0 +1,224 @@
+// src/instancing.rs
+// Dibujo instanciado: muchos cuerpos que comparten unas pocas mallas (`Arc<Mesh>`) y shaders,
+// cada uno con su propia órbita, escala y giro. Pensado para cinturones de asteroides.
+use std::sync::Arc;
+
+use raylib::prelude::*;
+
+use crate::entity::{LodLevel, CREASE_ANGLE};
+use crate::matrix::rotate_by_quaternion;
+use crate::mesh::Mesh;
+use crate::procedural::{generate_asteroid, Rng};
+use crate::shaders::FragmentShader;
+use crate::uniforms::ShaderParams;
+
+/// One look shared by many instances: the fragment shader used up close and the flat colour
+/// used when an instance covers about a pixel.
+#[derive(Clone)]
+pub struct ShaderVariant {
+    pub fshader: FragmentShader,
+    pub color: Vector3,
+}
+
+/// Per-instance state; everything else (geometry, shading) is looked up in the set.
+#[derive(Clone, Copy, Debug)]
+pub struct Instance {
+    pub shape: usize,        // índice en `InstanceSet::shapes`
+    pub variant: usize,      // índice en `InstanceSet::variants`
+    pub scale: f32,
+    // Órbita circular alrededor del centro del conjunto
+    pub radius: f32,
+    pub angular_speed: f32,
+    pub phase: f32,
+    pub inclination: f32,    // rad, inclinación del plano orbital
+    pub height: f32,         // desplazamiento vertical del plano
+    // Giro propio
+    pub spin_axis: Vector3,  // unitario
+    pub spin_speed: f32,     // rad/s
+}
+
+impl Instance {
+    /// World position at `time` on its orbit around `center`.
+    pub fn position(&self, center: Vector3, time: f32) -> Vector3 {
+        let theta = self.phase + self.angular_speed * time;
+        let (s, c) = theta.sin_cos();
+        let (si, ci) = self.inclination.sin_cos();
+        center + Vector3::new(self.radius * c, self.height + self.radius * s * si, self.radius * s * ci)
+    }
+
+    /// (right, up, forward) after spinning `spin_speed · time` around `spin_axis`.
+    pub fn basis(&self, time: f32) -> (Vector3, Vector3, Vector3) {
+        let (s, c) = (0.5 * self.spin_speed * time).sin_cos();
+        let axis = self.spin_axis * s;
+        let q = Vector4::new(axis.x, axis.y, axis.z, c);
+        (
+            rotate_by_quaternion(Vector3::new(1.0, 0.0, 0.0), q),
+            rotate_by_quaternion(Vector3::new(0.0, 1.0, 0.0), q),
+            rotate_by_quaternion(Vector3::new(0.0, 0.0, 1.0), q),
+        )
+    }
+}
+
+/// Many bodies drawn from a few shared shapes (each a LOD chain) and shader variants.
+#[derive(Clone)]
+pub struct InstanceSet {
+    pub name: &'static str,
+    pub center: Vector3,
+    pub shapes: Vec<Vec<LodLevel>>, // niveles ordenados por umbral creciente
+    pub shape_radii: Vec<f32>,      // radio envolvente de cada forma en espacio objeto
+    pub variants: Vec<ShaderVariant>,
+    pub instances: Vec<Instance>,
+    pub params: ShaderParams,
+}
+
+impl InstanceSet {
+    pub fn new(name: &'static str, center: Vector3, mut shapes: Vec<Vec<LodLevel>>, variants: Vec<ShaderVariant>) -> Self {
+        for levels in shapes.iter_mut() {
+            levels.sort_by(|a, b| a.min_screen_radius.total_cmp(&b.min_screen_radius));
+        }
+        let shape_radii = shapes
+            .iter()
+            .map(|levels| levels.iter().map(|l| l.mesh.bounding_radius()).fold(0.0, f32::max))
+            .collect();
+        InstanceSet { name, center, shapes, shape_radii, variants, instances: Vec::new(), params: ShaderParams::default() }
+    }
+
+    /// World-space bounding radius of `instance`.
+    pub fn instance_radius(&self, instance: &Instance) -> f32 {
+        self.shape_radii[instance.shape] * instance.scale
+    }
+
+    /// Mesh of `instance`'s shape for a projected radius of `screen_radius` pixels. Unlike
+    /// `Entity::select_lod` there is no hysteresis: instances keep no per-frame state.
+    pub fn mesh_for(&self, instance: &Instance, screen_radius: f32) -> &Mesh {
+        let levels = &self.shapes[instance.shape];
+        let i = levels.iter().rposition(|l| screen_radius >= l.min_screen_radius).unwrap_or(0);
+        &levels[i].mesh
+    }
+}
+
+/// Formas distintas de roca en un cinturón (cada instancia elige una)
+const BELT_SHAPES: u32 = 4;
+
+/// (radio proyectado mínimo en píxeles, subdivisiones de la icosfera) de cada LOD de una roca
+const BELT_LODS: [(f32, u32); 3] = [(0.0, 1), (20.0, 2), (60.0, 3)];
+
+/// Asteroid belt of `count` rocks between `inner` and `outer` around the origin: a handful of
+/// `generate_asteroid` shapes and rock colours shared by all, with per-rock size, tumble and
+/// a slightly inclined orbit whose speed falls off with radius (Kepler, ω ∝ r^-1.5).
+pub fn asteroid_belt(seed: u32, count: usize, inner: f32, outer: f32) -> InstanceSet {
+    let shapes = (0..BELT_SHAPES)
+        .map(|k| {
+            BELT_LODS
+                .iter()
+                .map(|&(px, detail)| {
+                    let rock = generate_asteroid(seed.wrapping_add(k), 1.0, 0.6 + 0.1 * k as f32, detail);
+                    LodLevel { min_screen_radius: px, mesh: Arc::new(Mesh::from_soup_smooth(&rock, &[], CREASE_ANGLE)) }
+                })
+                .collect()
+        })
+        .collect();
+    let variants = [Vector3::new(0.42, 0.4, 0.38), Vector3::new(0.5, 0.4, 0.3), Vector3::new(0.35, 0.3, 0.28)]
+        .into_iter()
+        .map(|color| ShaderVariant { fshader: FragmentShader::Rocky { color }, color })
+        .collect();
+    let mut set = InstanceSet::new("asteroid_belt", Vector3::new(0.0, 0.0, 0.0), shapes, variants);
+
+    let mut rng = Rng::new(seed ^ 0x5EED_BE17);
+    let middle = 0.5 * (inner + outer);
+    set.instances = (0..count)
+        .map(|_| {
+            // Más densidad hacia el centro del anillo (suma de dos uniformes)
+            let radius = inner + (outer - inner) * 0.5 * (rng.unit() + rng.unit());
+            let axis = Vector3::new(rng.range(-1.0, 1.0), rng.range(-1.0, 1.0), rng.range(-1.0, 1.0));
+            Instance {
+                shape: rng.next_u32() as usize % set.shapes.len(),
+                variant: rng.next_u32() as usize % set.variants.len(),
+                // Muchas piedras chicas, pocas grandes
+                scale: 0.08 + 0.3 * rng.unit().powi(3),
+                radius,
+                angular_speed: 0.65 * (middle / radius).powf(1.5),
+                phase: rng.range(0.0, std::f32::consts::TAU),
+                inclination: rng.range(-0.04, 0.04),
+                height: rng.range(-0.75, 0.75),
+                spin_axis: if axis.length() > 1e-3 { axis.normalized() } else { Vector3::new(0.0, 1.0, 0.0) },
+                spin_speed: rng.range(0.2, 1.7),
+            }
+        })
+        .collect();
+    set
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Vector3, b: Vector3) -> bool {
+        (a - b).length() < 1e-4
+    }
+
+    fn instance() -> Instance {
+        Instance {
+            shape: 0,
+            variant: 0,
+            scale: 0.5,
+            radius: 10.0,
+            angular_speed: 0.25,
+            phase: 0.0,
+            inclination: 0.3,
+            height: 0.5,
+            spin_axis: Vector3::new(1.0, 2.0, -0.5).normalized(),
+            spin_speed: 1.3,
+        }
+    }
+
+    #[test]
+    fn position_follows_the_inclined_orbit() {
+        let center = Vector3::new(3.0, -1.0, 2.0);
+        let rock = instance();
+        assert!(close(rock.position(center, 0.0), center + Vector3::new(10.0, 0.5, 0.0)));
+        // Un cuarto de vuelta: sube por el plano inclinado
+        let quarter = std::f32::consts::FRAC_PI_2 / rock.angular_speed;
+        let (si, ci) = rock.inclination.sin_cos();
+        assert!(close(rock.position(center, quarter), center + Vector3::new(0.0, 0.5 + 10.0 * si, 10.0 * ci)));
+        for t in [0.0, 1.0, 7.5, 40.0] {
+            let offset = rock.position(center, t) - center - Vector3::new(0.0, rock.height, 0.0);
+            assert!((offset.length() - rock.radius).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn basis_stays_orthonormal_and_spins_around_its_axis() {
+        let rock = instance();
+        assert!(close(rock.basis(0.0).0, Vector3::new(1.0, 0.0, 0.0)));
+        for t in [0.0, 0.4, 3.0, 25.0] {
+            let (right, up, forward) = rock.basis(t);
+            for v in [right, up, forward] {
+                assert!((v.length() - 1.0).abs() < 1e-5);
+            }
+            assert!(right.dot(up).abs() < 1e-5 && up.dot(forward).abs() < 1e-5 && forward.dot(right).abs() < 1e-5);
+            assert!(close(right.cross(up), forward));
+            // El eje de giro no se mueve
+            let a = rock.spin_axis;
+            assert!(close(right * a.x + up * a.y + forward * a.z, a));
+        }
+    }
+
+    #[test]
+    fn mesh_for_picks_the_finest_level_reached() {
+        let level = |px: f32, corners: usize| LodLevel {
+            min_screen_radius: px,
+            mesh: Arc::new(Mesh { positions: vec![Vector3::new(1.0, 0.0, 0.0); corners], ..Mesh::default() }),
+        };
+        // Desordenados a propósito: `new` los ordena por umbral
+        let set = InstanceSet::new("test", Vector3::new(0.0, 0.0, 0.0), vec![vec![level(60.0, 3), level(0.0, 1), level(20.0, 2)]], Vec::new());
+        let rock = instance();
+        let corners = |px: f32| set.mesh_for(&rock, px).positions.len();
+        assert_eq!(corners(0.5), 1);
+        assert_eq!(corners(19.9), 1);
+        assert_eq!(corners(20.0), 2);
+        assert_eq!(corners(59.0), 2);
+        assert_eq!(corners(500.0), 3);
+        assert_eq!(set.instance_radius(&rock), 0.5);
+    }
+}
@@ -0,0 +1,385 @@
+// src/json.rs
+// Lector JSON mínimo (RFC 8259) para el importador glTF; sin dependencias externas.
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>), // conserva el orden del archivo
+}
+
+#[derive(Debug)]
+pub struct JsonError {
+    pub line: usize,
+    pub col: usize,
+    pub message: String,
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.col, self.message)
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+static NULL: Json = Json::Null;
+
+impl Json {
+    /// Member `key` of an object; `Null` if absent or not an object (so lookups can chain).
+    pub fn get(&self, key: &str) -> &Json {
+        match self {
+            Json::Object(members) => members.iter().find(|(k, _)| k == key).map_or(&NULL, |(_, v)| v),
+            _ => &NULL,
+        }
+    }
+
+    /// Element `i` of an array; `Null` if out of range or not an array.
+    pub fn at(&self, i: usize) -> &Json {
+        match self {
+            Json::Array(items) => items.get(i).unwrap_or(&NULL),
+            _ => &NULL,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Json::Null)
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_f32(&self) -> Option<f32> {
+        self.as_f64().map(|n| n as f32)
+    }
+
+    /// Non-negative integer (indices, counts, offsets).
+    pub fn as_usize(&self) -> Option<usize> {
+        match self {
+            Json::Number(n) if *n >= 0.0 && n.fract() == 0.0 => Some(*n as usize),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Array items; empty for anything else.
+    pub fn items(&self) -> &[Json] {
+        match self {
+            Json::Array(items) => items,
+            _ => &[],
+        }
+    }
+
+    /// Numeric array as `f32`s (non-numbers are skipped).
+    pub fn as_f32s(&self) -> Vec<f32> {
+        self.items().iter().filter_map(Json::as_f32).collect()
+    }
+}
+
+pub fn parse(text: &str) -> Result<Json, JsonError> {
+    let mut parser = Parser { src: text.as_bytes(), pos: 0, depth: 0 };
+    parser.skip_ws();
+    let value = parser.value()?;
+    parser.skip_ws();
+    if parser.pos < parser.src.len() {
+        return Err(parser.error("trailing characters after the document"));
+    }
+    Ok(value)
+}
+
+/// Límite de anidamiento para no desbordar la pila con archivos maliciosos
+const MAX_DEPTH: usize = 256;
+
+struct Parser<'a> {
+    src: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> JsonError {
+        let before = &self.src[..self.pos.min(self.src.len())];
+        let line = before.iter().filter(|&&b| b == b'\n').count() + 1;
+        let col = before.iter().rev().take_while(|&&b| b != b'\n').count() + 1;
+        JsonError { line, col, message: message.to_string() }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), JsonError> {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", byte as char)))
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> Result<Json, JsonError> {
+        if self.src[self.pos..].starts_with(word.as_bytes()) {
+            self.pos += word.len();
+            Ok(value)
+        } else {
+            Err(self.error("unexpected character"))
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, JsonError> {
+        match self.peek() {
+            Some(b'{') => self.nested(Parser::object),
+            Some(b'[') => self.nested(Parser::array),
+            Some(b'"') => self.string().map(Json::String),
+            Some(b't') => self.literal("true", Json::Bool(true)),
+            Some(b'f') => self.literal("false", Json::Bool(false)),
+            Some(b'n') => self.literal("null", Json::Null),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn nested(&mut self, f: fn(&mut Self) -> Result<Json, JsonError>) -> Result<Json, JsonError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(self.error("document nested too deeply"));
+        }
+        self.depth += 1;
+        let value = f(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn object(&mut self) -> Result<Json, JsonError> {
+        self.expect(b'{')?;
+        let mut members = Vec::new();
+        self.skip_ws();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Json::Object(members));
+        }
+        loop {
+            self.skip_ws();
+            if self.peek() != Some(b'"') {
+                return Err(self.error("expected a string key"));
+            }
+            let key = self.string()?;
+            self.skip_ws();
+            self.expect(b':')?;
+            self.skip_ws();
+            members.push((key, self.value()?));
+            self.skip_ws();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Json::Object(members));
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, JsonError> {
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        self.skip_ws();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Json::Array(items));
+        }
+        loop {
+            self.skip_ws();
+            items.push(self.value()?);
+            self.skip_ws();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Json::Array(items));
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, JsonError> {
+        let start = self.pos;
+        let digits = |p: &mut Self| {
+            let from = p.pos;
+            while let Some(b'0'..=b'9') = p.peek() {
+                p.pos += 1;
+            }
+            p.pos > from
+        };
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        if !digits(self) {
+            return Err(self.error("expected a digit"));
+        }
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            if !digits(self) {
+                return Err(self.error("expected a digit after '.'"));
+            }
+        }
+        if let Some(b'e' | b'E') = self.peek() {
+            self.pos += 1;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.pos += 1;
+            }
+            if !digits(self) {
+                return Err(self.error("expected a digit in the exponent"));
+            }
+        }
+        // Solo hay ASCII entre start y pos
+        let text = std::str::from_utf8(&self.src[start..self.pos]).unwrap_or("");
+        text.parse::<f64>().map(Json::Number).map_err(|_| self.error("invalid number"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, JsonError> {
+        let hex = self.src.get(self.pos..self.pos + 4).ok_or_else(|| self.error("truncated \\u escape"))?;
+        let code = std::str::from_utf8(hex)
+            .ok()
+            .and_then(|h| u32::from_str_radix(h, 16).ok())
+            .ok_or_else(|| self.error("invalid \\u escape"))?;
+        self.pos += 4;
+        Ok(code)
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.expect(b'"')?;
+        let mut out: Vec<u8> = Vec::new();
+        loop {
+            let Some(byte) = self.peek() else {
+                return Err(self.error("unterminated string"));
+            };
+            self.pos += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let Some(esc) = self.peek() else {
+                        return Err(self.error("unterminated string"));
+                    };
+                    self.pos += 1;
+                    let c = match esc {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let mut code = self.hex4()?;
+                            // Par sustituto UTF-16; uno suelto queda como U+FFFD
+                            if (0xD800..0xDC00).contains(&code) && self.src[self.pos..].starts_with(b"\\u") {
+                                self.pos += 2;
+                                let low = self.hex4()?;
+                                if !(0xDC00..0xE000).contains(&low) {
+                                    return Err(self.error("invalid UTF-16 surrogate pair"));
+                                }
+                                code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                            }
+                            char::from_u32(code).unwrap_or('\u{FFFD}')
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    let mut buf = [0u8; 4];
+                    out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                0x00..=0x1F => return Err(self.error("control character in string")),
+                _ => out.push(byte),
+            }
+        }
+        String::from_utf8(out).map_err(|_| self.error("invalid UTF-8 in string"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_at(text: &str) -> (usize, usize, String) {
+        let e = parse(text).expect_err("should not parse");
+        (e.line, e.col, e.message)
+    }
+
+    #[test]
+    fn numbers() {
+        let doc = parse("[0, -0, 12, -3.25, 1e3, 2.5E-2, -4e+1]").unwrap();
+        let values: Vec<f64> = doc.items().iter().map(|v| v.as_f64().unwrap()).collect();
+        assert_eq!(values, vec![0.0, 0.0, 12.0, -3.25, 1000.0, 0.025, -40.0]);
+        assert_eq!(doc.at(2).as_usize(), Some(12));
+        assert_eq!(doc.at(3).as_usize(), None);
+
+        assert_eq!(error_at("1."), (1, 3, "expected a digit after '.'".to_string()));
+        assert_eq!(error_at("-"), (1, 2, "expected a digit".to_string()));
+        assert_eq!(error_at("1e"), (1, 3, "expected a digit in the exponent".to_string()));
+    }
+
+    #[test]
+    fn strings_escapes_and_surrogates() {
+        let doc = parse(r#"["a\"b\\c\/d", "\n\t\r\b\f", "\u00e9\u4e2d", "\ud83d\ude00", "\ud83d", "ñ"]"#).unwrap();
+        assert_eq!(doc.at(0).as_str(), Some("a\"b\\c/d"));
+        assert_eq!(doc.at(1).as_str(), Some("\n\t\r\u{8}\u{c}"));
+        assert_eq!(doc.at(2).as_str(), Some("é中"));
+        assert_eq!(doc.at(3).as_str(), Some("😀"));
+        assert_eq!(doc.at(4).as_str(), Some("\u{FFFD}"));
+        assert_eq!(doc.at(5).as_str(), Some("ñ"));
+
+        assert_eq!(error_at(r#""\ud83d\u0041""#).2, "invalid UTF-16 surrogate pair");
+        assert_eq!(error_at(r#""\x""#).2, "invalid escape");
+        assert_eq!(error_at("\"a\nb\"").2, "control character in string");
+        assert_eq!(error_at("\"abc").2, "unterminated string");
+    }
+
+    #[test]
+    fn objects_keep_order_and_lookups_chain() {
+        let doc = parse(r#"{"b": {"c": [true, null]}, "a": false}"#).unwrap();
+        let Json::Object(members) = &doc else { panic!("not an object") };
+        assert_eq!(members.iter().map(|(k, _)| k.as_str()).collect::<Vec<_>>(), ["b", "a"]);
+        assert_eq!(doc.get("b").get("c").at(0).as_bool(), Some(true));
+        assert!(doc.get("b").get("c").at(1).is_null());
+        assert!(doc.get("missing").get("deeper").at(3).is_null());
+        assert_eq!(doc.get("a").as_bool(), Some(false));
+    }
+
+    #[test]
+    fn errors_report_line_and_column() {
+        assert_eq!(error_at("{\n  \"a\": 1,\n  \"b\": tru\n}"), (3, 8, "unexpected character".to_string()));
+        assert_eq!(error_at("[1, 2\n  3]"), (2, 3, "expected ',' or ']'".to_string()));
+        assert_eq!(error_at("{\"a\": 1} x"), (1, 10, "trailing characters after the document".to_string()));
+        assert_eq!(error_at("{1: 2}"), (1, 2, "expected a string key".to_string()));
+        assert_eq!(error_at(&"[".repeat(MAX_DEPTH + 1)).2, "document nested too deeply");
+    }
+}
@@ -2,10 +2,16 @@ use raylib::prelude::*;
 
 pub struct Light {
     pub position: Vector3,
+    pub color: Vector3,
+    pub intensity: f32,
 }
 
 impl Light {
     pub fn new(position: Vector3) -> Self {
-        Light { position }
+        Light { position, color: Vector3::new(1.0, 1.0, 1.0), intensity: 1.0 }
+    }
+
+    pub fn with_color(position: Vector3, color: Vector3, intensity: f32) -> Self {
+        Light { position, color, intensity }
     }
 }
\ No newline at end of file
@@ -16,18 +16,75 @@ mod entity;
 mod shaders;
 mod obj;
 mod skybox;
+mod prominence;
 
 mod uniforms;
 mod procedural;
+mod texture;
+mod bake;
+mod material;
+mod animation;
+mod dsl;
+mod simd;
+mod bench;
+mod mesh;
+mod json;
+mod gltf;
+mod export;
+mod assets;
+mod shipgen;
+mod instancing;
+mod frustum;
 use camera::Camera;
-use entity::{Entity, Motion};
+use entity::{Entity, Motion, CREASE_ANGLE};
 use framebuffer::Framebuffer;
+use frustum::Frustum;
+use instancing::InstanceSet;
 use light::Light;
+use assets::AssetPaths;
+use mesh::Mesh;
 use uniforms::Uniforms;
 use fragment::Fragment;
 use obj::Obj;
-use triangle::triangle;
-use crate::{entity::sample_system, matrix::*, procedural::*, shaders::*, skybox::*, uniforms::*};
+use triangle::{triangle, Vertex};
+use crate::{entity::sample_system, matrix::*, procedural::*, prominence::{draw_prominences, SunDisc}, shaders::*, skybox::*, uniforms::*};
+
+/// Naves de tráfico generadas (una semilla por nombre)
+const NPC_SHIPS: [&str; 3] = ["npc_ship_0", "npc_ship_1", "npc_ship_2"];
+
+/// Campo de visión vertical de la cámara
+const FOV_Y: f32 = PI / 3.0;
+
+/// Planos de recorte de la proyección. `project` no recorta contra el lejano (solo fija el
+/// rango de profundidad), así que el frustum tampoco lo usa
+const NEAR_PLANE: f32 = 0.5;
+const FAR_PLANE: f32 = 100.0;
+
+/// Conteos de un frame, mostrados en la esquina de la ventana
+#[derive(Default)]
+struct FrameStats {
+    entities_drawn: usize,
+    entities_culled: usize,   // fuera del frustum: sin trabajo de vértices
+    instances_meshed: usize,  // dibujadas con su malla
+    instances_splatted: usize, // dibujadas como disco
+    instances_culled: usize,
+}
+
+impl FrameStats {
+    fn overlay(&self, fps: u32) -> Vec<String> {
+        vec![
+            format!("{} fps", fps),
+            format!("entities: {} drawn, {} culled", self.entities_drawn, self.entities_culled),
+            format!(
+                "instances: {} meshed, {} splatted, {} culled",
+                self.instances_meshed, self.instances_splatted, self.instances_culled
+            ),
+        ]
+    }
+}
+
+/// Instancias con un radio proyectado menor (en píxeles) se dibujan como un disco plano
+const SPLAT_RADIUS_PX: f32 = 4.0;
 
 fn transform(
     vertex: Vector3,
@@ -39,34 +96,7 @@ fn transform(
     viewport: &Matrix,
 ) -> Option<Vector3> {
     let model: Matrix = create_model_matrix(translation, scale, rotation);
-    let vertex4 = Vector4::new(vertex.x, vertex.y, vertex.z, 1.0);
-
-    let world_transform = multiply_matrix_vector4(&model, &vertex4);
-    let view_transform = multiply_matrix_vector4(view, &world_transform);
-    let projection_transform = multiply_matrix_vector4(projection, &view_transform);
-
-    // 1) Rechazar cosas detrás de la cámara / w <= 0
-    if projection_transform.w <= 0.0 {
-        return None;
-    }
-
-    let inv_w = 1.0 / projection_transform.w;
-    let ndc = Vector4::new(
-        projection_transform.x * inv_w,
-        projection_transform.y * inv_w,
-        projection_transform.z * inv_w,
-        1.0,
-    );
-
-    // 2) Clipping básico en el cubo NDC [-1,1]^3
-    if ndc.x < -1.0 || ndc.x > 1.0 ||
-       ndc.y < -1.0 || ndc.y > 1.0 ||
-       ndc.z < -1.0 {
-        return None;
-    }
-
-    let screen = multiply_matrix_vector4(viewport, &ndc);
-    Some(Vector3::new(screen.x, screen.y, ndc.z))
+    project(vertex, &model, view, projection, viewport)
 }
 
 fn transform_with_basis(
@@ -80,28 +110,26 @@ fn transform_with_basis(
     projection: &Matrix,
     viewport: &Matrix,
 ) -> Option<Vector3> {
-    let sx = scale;
-    let r = right * sx;
-    let u = up * sx;
-    let f = forward * sx;
-
-    let model = Matrix {
-        // Column 0: right
-        m0: r.x, m1: r.y, m2: r.z, m3: 0.0,
-        // Column 1: up
-        m4: u.x, m5: u.y, m6: u.z, m7: 0.0,
-        // Column 2: forward
-        m8: f.x, m9: f.y, m10: f.z, m11: 0.0,
-        // Column 3: translation
-        m12: translation.x, m13: translation.y, m14: translation.z, m15: 1.0,
-    };
+    let model = create_basis_model_matrix(translation, scale, right, up, forward);
+    project(vertex, &model, view, projection, viewport)
+}
 
+/// Object-space vertex → (screen x, screen y, NDC z) with an already built model matrix, so
+/// a mesh (or each instance of it) builds its matrix once instead of once per vertex.
+fn project(
+    vertex: Vector3,
+    model: &Matrix,
+    view: &Matrix,
+    projection: &Matrix,
+    viewport: &Matrix,
+) -> Option<Vector3> {
     let vertex4 = Vector4::new(vertex.x, vertex.y, vertex.z, 1.0);
 
-    let world_transform = multiply_matrix_vector4(&model, &vertex4);
+    let world_transform = multiply_matrix_vector4(model, &vertex4);
     let view_transform = multiply_matrix_vector4(view, &world_transform);
     let projection_transform = multiply_matrix_vector4(projection, &view_transform);
 
+    // 1) Rechazar cosas detrás de la cámara / w <= 0
     if projection_transform.w <= 0.0 {
         return None;
     }
@@ -114,9 +142,10 @@ fn transform_with_basis(
         1.0,
     );
 
+    // 2) Clipping básico en el cubo NDC [-1,1]^3
     if ndc.x < -1.0 || ndc.x > 1.0 ||
        ndc.y < -1.0 || ndc.y > 1.0 ||
-       ndc.z < -1.0  {
+       ndc.z < -1.0 {
         return None;
     }
 
@@ -124,89 +153,434 @@ fn transform_with_basis(
     Some(Vector3::new(screen.x, screen.y, ndc.z))
 }
 
+/// Lo que comparten todos los draws de un frame: cámara, tiempo, luces y frustum.
+pub struct FrameContext<'a> {
+    pub view: Matrix,
+    pub projection: Matrix,
+    pub viewport: Matrix,
+    pub time: f32,
+    pub resolution: Vector2,
+    pub eye: Vector3,
+    pub lights: &'a [Light],
+    pub px_per_unit: f32, // radio proyectado en píxeles de una unidad a distancia 1
+    pub frustum: Frustum,
+}
+
+/// Where a mesh goes in the world: translation and uniform scale, oriented either by Euler
+/// angles or, when `basis` is set, by an explicit (right, up, forward) frame.
+#[derive(Clone, Copy)]
+pub struct Placement {
+    pub translation: Vector3,
+    pub scale: f32,
+    pub rotation: Vector3,
+    pub basis: Option<(Vector3, Vector3, Vector3)>, // (right, up, forward)
+}
+
+impl Placement {
+    pub fn model_matrix(&self) -> Matrix {
+        match self.basis {
+            Some((right, up, forward)) => create_basis_model_matrix(self.translation, self.scale, right, up, forward),
+            None => create_model_matrix(self.translation, self.scale, self.rotation),
+        }
+    }
+
+    /// World-space direction `v` in object space (rotation only).
+    pub fn to_object(&self, v: Vector3) -> Vector3 {
+        match self.basis {
+            Some((right, up, forward)) => Vector3::new(v.dot(right), v.dot(up), v.dot(forward)),
+            None => inverse_rotate_vector(v, self.rotation),
+        }
+    }
+}
+
 pub fn render(
     framebuffer: &mut Framebuffer,
-    translation: Vector3,
-    scale: f32,
-    rotation: Vector3,
-    basis: Option<(Vector3, Vector3, Vector3)>, // (right, up, forward)
-    vertex_array: &[Vector3],
+    ctx: &FrameContext,
+    placement: &Placement,
+    mesh: &Mesh,
     vshader: &VertexShader,
     fshader: &FragmentShader,
-    view: &Matrix,
-    projection: &Matrix,
-    viewport: &Matrix,
-    time: f32,
-    resolution: Vector2,
-    temp: f32,
-    intensity: f32,
+    params: &ShaderParams,
 ) {
     let light = Light::new(Vector3::new(0.0, 10.0, 0.0));
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    let mut obj_vertices_after_vs = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
+    let (view, projection, viewport, time) = (&ctx.view, &ctx.projection, &ctx.viewport, ctx.time);
+    let Placement { translation, scale, .. } = *placement;
+
+    let world = placement.model_matrix();
+
+    // Vertex Stage con caché post-transformación: cada vértice único de la malla se desplaza y
+    // proyecta una sola vez, y los triángulos leen el resultado a través de los índices
+    let mut transformed_vertices = Vec::with_capacity(mesh.positions.len());
+    let mut obj_vertices_after_vs = Vec::with_capacity(mesh.positions.len());
+    for vertex in &mesh.positions {
         let v_obj = apply_vertex_shader(*vertex, vshader, time);
-        
-        let transformed = if let Some((right, up, forward)) = basis {
-            transform_with_basis(v_obj, translation, scale, right, up, forward, view, projection, viewport)
-        } else {
-            transform(v_obj, translation, scale, rotation, view, projection, viewport)
-        };
-        
+
+        let transformed = project(v_obj, &world, view, projection, viewport);
+
         obj_vertices_after_vs.push(v_obj);
         transformed_vertices.push(transformed);
     }
 
+    // Normales por vértice: si el vertex shader movió la geometría se recalculan a partir de
+    // los vértices desplazados vecinos, para que la iluminación siga al relieve nuevo (el
+    // relieve fijo ya viene aplicado en la malla, ver `Entity::bake_vertex_shader`)
+    let recomputed;
+    let normals = if matches!(vshader, VertexShader::Identity) && mesh.normals.len() == mesh.positions.len() {
+        &mesh.normals
+    } else {
+        recomputed = mesh.recompute_normals(&obj_vertices_after_vs, CREASE_ANGLE);
+        &recomputed
+    };
+
     // Primitive Assembly Stage
+    let has_uvs = mesh.has_uvs();
+    let vertex_at = |i: usize, screen: Vector3| Vertex {
+        screen,
+        obj: obj_vertices_after_vs[i],
+        normal: normals[i],
+        uv: if has_uvs { mesh.uvs[i] } else { Vector2::new(0.0, 0.0) },
+    };
+    // Los mapas horneados se leen con `equirect_uv(p)` y no con las UV de la malla (que puede no
+    // tenerlas): esas coordenadas son las que dan la huella en pantalla y el nivel de mip
+    let equirect = matches!(fshader, FragmentShader::Baked { .. });
     let mut triangles = Vec::new();
-    let mut obj_tris = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 >= transformed_vertices.len() {
-            break;
-        }
-
+    for tri in mesh.indices.chunks_exact(3) {
+        let (i0, i1, i2) = (tri[0] as usize, tri[1] as usize, tri[2] as usize);
         if let (Some(v0), Some(v1), Some(v2)) = (
-            transformed_vertices[i],
-            transformed_vertices[i + 1],
-            transformed_vertices[i + 2],
+            transformed_vertices[i0],
+            transformed_vertices[i1],
+            transformed_vertices[i2],
         ) {
-            triangles.push([v0, v1, v2]);
-            obj_tris.push([
-                obj_vertices_after_vs[i],
-                obj_vertices_after_vs[i + 1],
-                obj_vertices_after_vs[i + 2],
-            ]);
+            let mut corners = [vertex_at(i0, v0), vertex_at(i1, v1), vertex_at(i2, v2)];
+            if equirect {
+                let uvs = bake::equirect_triangle_uvs(corners.map(|c| c.obj));
+                for (corner, uv) in corners.iter_mut().zip(uvs) {
+                    corner.uv = uv;
+                }
+            }
+            triangles.push(corners);
         }
     }
 
     // Rasterization Stage
     let mut fragments = Vec::new();
-    for (tri, obj_tri) in triangles.iter().zip(obj_tris.iter()) {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2], &obj_tri[0], &obj_tri[1], &obj_tri[2], &light));
+    for tri in &triangles {
+        fragments.extend(triangle(&tri[0], &tri[1], &tri[2], &light));
     }
-    
+
+    // El Sol está en el origen: dirección hacia él, llevada a espacio objeto
+    let to_sun = if translation.length() > 0.0 { -translation.normalized() } else { Vector3::new(0.0, 1.0, 0.0) };
+    let sun_dir = placement.to_object(to_sun);
+
+    // Posición de la cámara en espacio objeto (inversa de T · R · S)
+    let camera_pos = placement.to_object((ctx.eye - translation) / scale.max(1e-6));
+
+    // Luces de la escena llevadas a espacio objeto
+    let obj_lights = ctx
+        .lights
+        .iter()
+        .map(|l| {
+            let to_light = l.position - translation;
+            let dir = if to_light.length() > 0.0 { placement.to_object(to_light.normalized()) } else { sun_dir };
+            ObjLight { dir, radiance: l.color * l.intensity }
+        })
+        .collect();
+
     let uniforms = Uniforms {
         time,
-        resolution,
-        temp,
-        intensity,
+        resolution: ctx.resolution,
+        world,
+        sun_dir,
+        camera_pos,
+        lights: obj_lights,
+        params,
     };
 
-    // Fragment Processing Stage
+    // Fragment Processing Stage: se descartan los fragmentos transparentes y el resto se
+    // sombrea por paquetes
+    let mut visible = Vec::with_capacity(fragments.len());
+    let mut alphas = Vec::with_capacity(fragments.len());
     for fragment in fragments {
-        let final_rgb = fragment_shader(&fragment, &uniforms, &fshader);
+        let alpha = fragment_alpha(&fragment, &uniforms, fshader);
+        if alpha > 0.0 {
+            visible.push(fragment);
+            alphas.push(alpha);
+        }
+    }
+    let mut colors = Vec::new();
+    fragment_shader_batch(&visible, &uniforms, fshader, &mut colors);
+
+    for ((fragment, alpha), final_rgb) in visible.iter().zip(alphas).zip(colors) {
         let out = vec3_to_color(final_rgb);
         framebuffer.set_current_color(out);
-        framebuffer.set_pixel(
-            fragment.position.x as u32,
-            fragment.position.y as u32,
-            fragment.depth
+        if alpha < 1.0 {
+            // Capas semitransparentes (nubes): mezclan sin escribir profundidad
+            framebuffer.blend_pixel(
+                fragment.position.x as u32,
+                fragment.position.y as u32,
+                fragment.depth,
+                alpha,
+            );
+        } else {
+            framebuffer.set_pixel(
+                fragment.position.x as u32,
+                fragment.position.y as u32,
+                fragment.depth
+            );
+        }
+    }
+
+}
+
+/// Disc of `radius` pixels at constant `depth`, darkened towards the rim like a small sphere.
+/// Below one pixel it becomes a single pixel blended by its coverage.
+/// The centre may lie off screen: the part of the disc that reaches the window is still drawn.
+fn draw_splat(framebuffer: &mut Framebuffer, x: f32, y: f32, depth: f32, radius: f32, rgb: Vector3) {
+    if radius < 1.0 {
+        if x < 0.0 || y < 0.0 {
+            return;
+        }
+        framebuffer.set_current_color(vec3_to_color(rgb));
+        framebuffer.blend_pixel(x as u32, y as u32, depth, radius.max(0.2));
+        return;
+    }
+    let r = radius.ceil() as i32;
+    let (cx, cy) = (x.floor() as i32, y.floor() as i32);
+    for py in (cy - r).max(0)..=cy + r {
+        for px in (cx - r).max(0)..=cx + r {
+            let d2 = ((px - cx) * (px - cx) + (py - cy) * (py - cy)) as f32 / (radius * radius);
+            if d2 > 1.0 {
+                continue;
+            }
+            framebuffer.set_current_color(vec3_to_color(rgb * (0.55 + 0.45 * (1.0 - d2).sqrt())));
+            framebuffer.set_pixel(px as u32, py as u32, depth);
+        }
+    }
+}
+
+/// Draws every instance of `set`. Instances whose bounding sphere is outside `frustum`, or whose
+/// disc lies entirely off screen, are skipped before any vertex work; those smaller than
+/// `SPLAT_RADIUS_PX` become a flat lit disc (even when only part of it is on screen),
+/// and the rest go through `render` with the LOD of their shared shape, their variant's shader
+/// and a model matrix built once per instance.
+fn render_instances(framebuffer: &mut Framebuffer, ctx: &FrameContext, set: &InstanceSet, stats: &mut FrameStats) {
+    let FrameContext { time, resolution, eye, lights, px_per_unit, .. } = *ctx;
+    let view_projection = multiply_matrix_matrix(&ctx.projection, &ctx.view);
+    for instance in &set.instances {
+        let position = instance.position(set.center, time);
+        let radius = set.instance_radius(instance);
+        if !ctx.frustum.intersects_sphere(position, radius) {
+            stats.instances_culled += 1;
+            continue;
+        }
+        let dist = (position - eye).length().max(1e-3);
+        let screen_radius = radius / dist * px_per_unit;
+        let variant = &set.variants[instance.variant];
+
+        // `w` es la profundidad en espacio vista; con el centro detrás de la cámara se dibuja la malla
+        let clip = multiply_matrix_vector4(&view_projection, &Vector4::new(position.x, position.y, position.z, 1.0));
+        if clip.w > 0.0 {
+            let ndc = Vector4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+            let screen = multiply_matrix_vector4(&ctx.viewport, &ndc);
+            // Un disco cuyo centro cae justo fuera del borde aún asoma hasta `screen_radius` píxeles
+            if screen.x < -screen_radius
+                || screen.y < -screen_radius
+                || screen.x > resolution.x + screen_radius
+                || screen.y > resolution.y + screen_radius
+            {
+                stats.instances_culled += 1;
+                continue;
+            }
+
+            if screen_radius < SPLAT_RADIUS_PX {
+                if ndc.z < -1.0 {
+                    stats.instances_culled += 1;
+                    continue;
+                }
+                // Fase como la de una esfera vista desde la cámara: llena de frente a la luz, oscura a contraluz
+                let to_eye = (eye - position) / dist;
+                let lit = lights.iter().fold(Vector3::new(0.0, 0.0, 0.0), |acc, l| {
+                    let to_light = (l.position - position).normalized();
+                    acc + l.color * (l.intensity * 0.5 * (1.0 + to_light.dot(to_eye)))
+                });
+                let rgb = variant.color * (lit * 0.85 + Vector3::new(0.15, 0.15, 0.15));
+                draw_splat(framebuffer, screen.x, screen.y, ndc.z, screen_radius, rgb);
+                stats.instances_splatted += 1;
+                continue;
+            }
+        }
+
+        let placement = Placement {
+            translation: position,
+            scale: instance.scale,
+            rotation: Vector3::new(0.0, 0.0, 0.0),
+            basis: Some(instance.basis(time)),
+        };
+        render(
+            framebuffer,
+            ctx,
+            &placement,
+            set.mesh_for(instance, screen_radius),
+            &VertexShader::Identity,
+            &variant.fshader,
+            &set.params,
+        );
+        stats.instances_meshed += 1;
+    }
+}
+
+/// Modelos glTF/GLB de `models/`: cada archivo se agrega como una jerarquía de entidades cuya
+/// raíz (con el nombre del archivo) queda en fila junto a la posición inicial de la nave.
+fn load_gltf_models(entities: &mut Vec<Entity>, dir: &std::path::Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "gltf" || ext == "glb"))
+        .collect();
+    paths.sort();
+
+    for (k, path) in paths.iter().enumerate() {
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else { continue };
+        match gltf::load(path) {
+            Ok(scene) => {
+                let position = Vector3::new(-20.0 + 20.0 * k as f32, 50.0, 170.0);
+                entities.extend(scene.to_entities(name, position, 1.0));
+            }
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+}
+
+/// Materiales escritos en el DSL: `materials/<entidad>.shader` reemplaza los shaders de esa
+/// entidad (solo los bloques presentes). Los errores se reportan como `archivo:línea:columna`.
+fn load_dsl_materials(entities: &mut [Entity], dir: &std::path::Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        if path.extension().is_none_or(|ext| ext != "shader") {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else { continue };
+        let Some(entity) = entities.iter_mut().find(|ent| ent.name == name) else {
+            eprintln!("{}: no entity named '{}'", path.display(), name);
+            continue;
+        };
+        match dsl::load(&path) {
+            Ok(source) => {
+                if let Some(program) = source.fragment {
+                    entity.fshader = FragmentShader::Expr { program: std::sync::Arc::new(program) };
+                }
+                if let Some(program) = source.vertex {
+                    entity.vshader = VertexShader::Expr { program: std::sync::Arc::new(program) };
+                }
+            }
+            Err(e @ (dsl::DslError::Syntax { .. } | dsl::DslError::Type { .. })) => eprintln!("{}:{}", path.display(), e),
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+}
+
+/// The sun's current `temp` and `intensity` uniforms, with the `Star` shader's defaults.
+fn sun_params(sun: &Entity) -> (f32, f32) {
+    (sun.params.get("temp").unwrap_or(0.1), sun.params.get("intensity").unwrap_or(0.5))
+}
+
+/// Every entity of the scene, before anything render-only (baking, textures): the solar system,
+/// the ship (generated from `ship_seed` if given), the NPC traffic, the glTF models in `models/`
+/// and the DSL materials in `materials/`. Shared by the window and `--export`.
+fn build_scene(assets: &AssetPaths, ship_seed: Option<u32>) -> Vec<Entity> {
+    let mut entities = sample_system();
+
+    // Si falta `nave.obj` se avisa y se dibuja la malla de reemplazo
+    let ship_obj = match ship_seed {
+        Some(seed) => shipgen::generate_ship(seed).to_obj(),
+        None => assets.load_obj_or_placeholder("nave.obj"),
+    };
+
+    // The ship we will follow: una parte por material del .mtl, con sus normales y UVs del OBJ
+    entities.push(Entity::from_parts("ship", Vector3::new(0.0, 50.0, 200.0), 1.0, Motion::Static, ship_obj.to_parts(), false).with_basis());
+
+    // Tráfico: naves generadas con semilla orbitando la Tierra
+    for (k, name) in NPC_SHIPS.iter().enumerate() {
+        let npc = shipgen::generate_ship(1000 + k as u32).to_obj();
+        entities.push(
+            Entity::from_parts(
+                name,
+                Vector3::new(0.0, 0.0, 0.0),
+                0.35,
+                Motion::OrbitAround { parent: "earth", radius: 3.4 + 0.5 * k as f32, angular_speed: 0.9 - 0.12 * k as f32, phase: 1.7 * k as f32 },
+                npc.to_parts(),
+                true,
+            ),
         );
     }
 
+    if let Some(dir) = assets.find("models") {
+        load_gltf_models(&mut entities, &dir);
+    }
+
+    if let Some(dir) = assets.find("materials") {
+        load_dsl_materials(&mut entities, &dir);
+    }
+    entities
+}
+
+fn run_export(args: &[String], assets: &AssetPaths, ship_seed: Option<u32>) -> i32 {
+    let (Some(name), Some(file)) = (args.first(), args.get(1)) else {
+        eprintln!("usage: --export <entity> <file.obj|file.ply> [time]");
+        return 2;
+    };
+    let time = match args.get(2).map(|t| t.parse::<f32>()) {
+        Some(Ok(t)) => Some(t),
+        Some(Err(_)) => {
+            eprintln!("--export: '{}' is not a time in seconds", args[2]);
+            return 2;
+        }
+        None => None,
+    };
+
+    let mut entities = build_scene(assets, ship_seed);
+    let Some(entity) = entities.iter_mut().find(|e| e.name == name.as_str()) else {
+        eprintln!("--export: no entity named '{}'", name);
+        return 1;
+    };
+    entity.active_lod = entity.lods.len().saturating_sub(1);
+
+    match export::export_entity(entity, std::path::Path::new(file), time) {
+        Ok(()) => {
+            println!("{} -> {}", name, file);
+            0
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
 }
 
 fn main() {
+    if std::env::args().any(|a| a == "--bench-shaders") {
+        bench::run_shader_bench();
+        return;
+    }
+
+    let args: Vec<String> = std::env::args().collect();
+
+    // Los recursos se buscan junto al archivo de escena (`--scene <archivo>`), luego en el
+    // directorio actual y luego junto al ejecutable
+    let assets = match args.iter().position(|a| a == "--scene").and_then(|at| args.get(at + 1)) {
+        Some(scene) => AssetPaths::new().with_scene(std::path::Path::new(scene)),
+        None => AssetPaths::new(),
+    };
+
+    // `--ship-seed <n>` cambia la nave hecha a mano por una generada
+    let ship_seed = args.iter().position(|a| a == "--ship-seed").and_then(|at| args.get(at + 1)).and_then(|s| s.parse::<u32>().ok());
+
+    // `--export <entidad> <archivo.obj|.ply> [segundos]`: escribe la malla más detallada de la
+    // entidad (con el vertex shader aplicado en ese instante, si se da) y termina
+    if let Some(at) = args.iter().position(|a| a == "--export") {
+        std::process::exit(run_export(&args[at + 1..], &assets, ship_seed));
+    }
+
     let window_width = 1300;
     let window_height = 600;
 
@@ -216,34 +590,50 @@ fn main() {
         .log_level(TraceLogLevel::LOG_WARNING)
         .build();
 
-    let projection = create_projection_matrix(PI/3.0, window_width as f32 / window_height as f32, 0.5, 100.0);
+    let projection = create_projection_matrix(FOV_Y, window_width as f32 / window_height as f32, NEAR_PLANE, FAR_PLANE);
     let viewport = create_viewport_matrix(0.0, 0.0, window_width as f32, window_height as f32);
 
     let mut framebuffer = Framebuffer::new(window_width as u32, window_height as u32, Color::BLACK);
     framebuffer.set_background_color(Color::new(4, 12, 36, 255));
 
-    let ship_obj = Obj::load("nave.obj").unwrap_or_else(|_| Obj::load("sphere.obj").expect("Failed to load any mesh"));
-    let ship_vertices = ship_obj.get_vertex_array();
-
     let mut temp_control: f32 = 0.1;      // 0 (rojo) … 1 (blanco/azulado)
     let mut intensity_control: f32 = 0.5; // 1 = normal, >1 más brillante
 
     // --- Scene entities ---
-    let mut entities: Vec<Entity> = sample_system();
-    entities.push(// The ship we will follow
-        Entity::new(
-            "ship",
-            Vector3::new(0.0, 50.0, 200.0),
-            Vector3::new(0.0, 0.0, 0.0),
-            1.0,
-            Motion::Static,
-            ship_vertices.clone(),
-            VertexShader::Identity,
-            FragmentShader::AlienShip,
-            Vector3::new(0.0, 0.0, 0.0),
-            false,
-        ),
-    );
+    let mut entities = build_scene(&assets, ship_seed);
+
+    // El relieve que no depende del tiempo se aplica una vez a las mallas
+    for e in entities.iter_mut() {
+        e.bake_vertex_shader();
+    }
+
+    // Cinturón de asteroides entre Marte (r = 60) y Júpiter (r = 80): mallas compartidas e
+    // instancias con órbita propia
+    let mut belt = instancing::asteroid_belt(2024, 6000, 64.0, 74.0);
+
+    // Hornear la parte estática de Rocky/Strips (cacheada en disco por parámetros)
+    let cache_dir = std::path::Path::new("cache");
+    for fshader in entities.iter_mut().map(|e| &mut e.fshader).chain(belt.variants.iter_mut().map(|v| &mut v.fshader)) {
+        if let Some(baked) = bake::bake_cached(fshader, 512, 256, cache_dir) {
+            *fshader = baked;
+        }
+    }
+
+    // Textura de la Tierra (mapa equirectangular): solo si el usuario pone una foto en `textures/`;
+    // si no, queda el shader procedural `Earth` con luces nocturnas y sombras de nubes
+    if let Some(earth_path) = ["textures/earth.jpg", "textures/earth.png"].into_iter().find_map(|p| assets.find(p))
+        && let Some(earth_map) = texture::Texture::load(&earth_path.to_string_lossy()).map_err(|e| eprintln!("{}", e)).ok()
+        && let Some(earth) = entities.iter_mut().find(|ent| ent.name == "earth")
+    {
+        earth.fshader = FragmentShader::Textured {
+            texture: std::sync::Arc::new(earth_map),
+            normal_map: assets
+                .find("textures/earth_normal.png")
+                .and_then(|path| texture::Texture::load(&path.to_string_lossy()).map_err(|e| eprintln!("{}", e)).ok())
+                .map(std::sync::Arc::new),
+            sampler: texture::Sampler::equirectangular(texture::Filter::Trilinear),
+        };
+    }
 
 
     let mut camera = Camera::new(
@@ -286,7 +676,7 @@ fn main() {
                     entities[i].translation.y = center.y;
                     // entities[i].rotation.y = -theta; // removed
                 }
-                Motion::OrbitAround { .. } => { /* defer to pass 2 */ }
+                Motion::OrbitAround { .. } | Motion::Attached { .. } => { /* defer to pass 2 and 3 */ }
             }
         }
         
@@ -315,6 +705,26 @@ fn main() {
             }
         }
 
+        // Pass 3: imported nodes follow their parent's transform (parents come first in the list)
+        for i in 0..entities.len() {
+            if let Motion::Attached { parent, translation, rotation, scale } = entities[i].motion.clone()
+                && let Some(&pi) = index_by_name.get(parent)
+            {
+                let p = &entities[pi];
+                let (right, up, forward) = (p.right, p.up, p.forward);
+                let to_world = |v: Vector3| right * v.x + up * v.y + forward * v.z;
+                let position = p.translation + to_world(translation) * p.scale;
+                let parent_scale = p.scale;
+
+                let child = &mut entities[i];
+                child.translation = position;
+                child.right = to_world(rotate_by_quaternion(Vector3::new(1.0, 0.0, 0.0), rotation));
+                child.up = to_world(rotate_by_quaternion(Vector3::new(0.0, 1.0, 0.0), rotation));
+                child.forward = to_world(rotate_by_quaternion(Vector3::new(0.0, 0.0, 1.0), rotation));
+                child.scale = parent_scale * scale;
+            }
+        }
+
         // --- Follow camera: lock target to ship position ---
         if let Some(ship) = entities.iter().position(|ent| ent.name == "ship") {
             let speed = 30.0;
@@ -332,7 +742,51 @@ fn main() {
         draw_sky_stars(&mut framebuffer, &skybox, &view, &viewport, &projection);
         draw_shooting_star(&mut framebuffer, time, window_width, window_height);
 
+        // Animación de parámetros; los controles T/G/Y/H solo van al bloque de uniforms del Sol.
+        // El LOD se elige con el radio proyectado en píxeles: r / (d · tan(fov/2)) · alto/2
+        let px_per_unit = window_height as f32 * 0.5 / (FOV_Y * 0.5).tan();
+        for e in entities.iter_mut() {
+            e.animate(time);
+            if !e.lods.is_empty() {
+                let dist = (e.translation - camera.eye).length().max(1e-3);
+                e.select_lod(e.lod_radius * e.scale / dist * px_per_unit);
+            }
+            if e.name == "sun" {
+                // Los controles no pisan un parámetro que el Sol ya anima
+                for (param, value) in [("temp", temp_control), ("intensity", intensity_control)] {
+                    if !e.animations.iter().any(|track| track.param == param) {
+                        e.params.set(param, value);
+                    }
+                }
+            }
+        }
+
+        // Luces de la escena: el Sol, con el color de su temperatura
+        let lights: Vec<Light> = entities
+            .iter()
+            .filter(|ent| ent.name == "sun")
+            .map(|sun| {
+                let (temp, intensity) = sun_params(sun);
+                Light::with_color(sun.translation, temperature_to_rgb(temp), 0.6 + 0.8 * intensity)
+            })
+            .collect();
+
         // --- Render all entities ---
+        // Las que quedan fuera del frustum se descartan antes de la etapa de vértices
+        let ctx = FrameContext {
+            view,
+            projection,
+            viewport,
+            time,
+            resolution,
+            eye: camera.eye,
+            lights: &lights,
+            px_per_unit,
+            frustum: Frustum::new(&view, &projection),
+        };
+        let mut stats = FrameStats::default();
+        // Capas que mezclan con lo de atrás (nubes, anillos): se dibujan al final, de atrás hacia adelante
+        let mut transparent = Vec::new();
         for e in &entities {
 
             let mut rot = e.rotation;
@@ -348,7 +802,7 @@ fn main() {
                         let theta = phase + angular_speed * time;
                         rot.y += -theta;
                     }
-                    Motion::Static => {}
+                    Motion::Static | Motion::Attached { .. } => {}
                 }
             }
 
@@ -356,31 +810,51 @@ fn main() {
             rot.y += e.spin.y * time;
             rot.z += e.spin.z * time;
 
-            let basis = if e.name == "ship" {
-                Some((e.right, e.up, e.forward))
-            } else {
-                None
+            let placement = Placement {
+                translation: e.translation,
+                scale: e.scale,
+                rotation: rot,
+                basis: if e.uses_basis { Some((e.right, e.up, e.forward)) } else { None },
             };
 
-            render(
-                &mut framebuffer,
-                e.translation,
-                e.scale,
-                rot,
-                basis,
-                &e.vertices,
-                &e.vshader,
-                &e.fshader,
-                &view,
-                &projection,
-                &viewport,
-                time,
-                resolution,
-                temp_control,
-                intensity_control,
-            );
+            // El volumen se agranda con lo que puede mover el vertex shader; si no se sabe, se dibuja
+            if let Some(margin) = e.vshader.max_displacement()
+                && !ctx.frustum.intersects_bounds(&placement.model_matrix(), &e.bounds.expanded(margin))
+            {
+                stats.entities_culled += 1;
+                continue;
+            }
+            stats.entities_drawn += 1;
+
+            for (mesh, fshader) in e.draw_list() {
+                if fshader.is_transparent() {
+                    transparent.push(((e.translation - camera.eye).length(), placement, e, mesh, fshader));
+                } else {
+                    render(&mut framebuffer, &ctx, &placement, mesh, &e.vshader, fshader, &e.params);
+                }
+            }
+        }
+
+        render_instances(&mut framebuffer, &ctx, &belt, &mut stats);
+
+        transparent.sort_by(|a, b| b.0.total_cmp(&a.0));
+        for (_, placement, e, mesh, fshader) in &transparent {
+            render(&mut framebuffer, &ctx, placement, mesh, &e.vshader, fshader, &e.params);
+        }
+
+        // --- Protuberancias en el limbo solar ---
+        if let Some(sun) = entities.iter().find(|ent| ent.name == "sun") {
+            let (temp, intensity) = sun_params(sun);
+            let disc = SunDisc {
+                position: sun.translation,
+                radius: sun.lod_radius * sun.scale, // radio envolvente de la esfera, sin la llamarada
+                temp,
+                intensity,
+            };
+            draw_prominences(&mut framebuffer, &ctx, &disc);
         }
 
-        framebuffer.swap_buffers(&mut window, &raylib_thread);
+        let overlay = stats.overlay(window.get_fps());
+        framebuffer.swap_buffers(&mut window, &raylib_thread, &overlay);
     }
 }
@@ -0,0 +1,171 @@
+// src/material.rs
+// Material PBR (metallic/roughness) y su construcción a partir de entradas MTL.
+use std::path::Path;
+use std::sync::Arc;
+
+use raylib::prelude::*;
+
+use crate::texture::Texture;
+
+#[derive(Clone)]
+pub struct Material {
+    pub name: String,
+    pub albedo: Vector3,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub specular: f32, // reflectancia F0 de la parte dieléctrica
+    pub emissive: Vector3,
+    pub ao: f32,
+    pub opacity: f32,
+    pub alpha_cutoff: Option<f32>, // recorte (MASK de glTF): opaco si alfa >= corte, si no se descarta
+    pub albedo_map: Option<Arc<Texture>>,
+    pub roughness_map: Option<Arc<Texture>>,
+    pub metallic_map: Option<Arc<Texture>>,
+    pub emissive_map: Option<Arc<Texture>>,
+    pub ao_map: Option<Arc<Texture>>,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material {
+            name: String::from("default"),
+            albedo: Vector3::new(0.7, 0.7, 0.7),
+            metallic: 0.0,
+            roughness: 0.5,
+            specular: 0.04,
+            emissive: Vector3::new(0.0, 0.0, 0.0),
+            ao: 1.0,
+            opacity: 1.0,
+            alpha_cutoff: None,
+            albedo_map: None,
+            roughness_map: None,
+            metallic_map: None,
+            emissive_map: None,
+            ao_map: None,
+        }
+    }
+}
+
+fn parse_vec3(s: &str) -> Option<Vector3> {
+    let mut it = s.split_whitespace().map(|t| t.parse::<f32>());
+    match (it.next(), it.next(), it.next()) {
+        (Some(Ok(x)), Some(Ok(y)), Some(Ok(z))) => Some(Vector3::new(x, y, z)),
+        (Some(Ok(x)), None, None) => Some(Vector3::new(x, x, x)),
+        _ => None,
+    }
+}
+
+fn load_map(base_dir: &Path, file: Option<&String>) -> Option<Arc<Texture>> {
+    let file = file?;
+    let path = base_dir.join(file);
+    match Texture::load(&path.to_string_lossy()) {
+        Ok(texture) => Some(Arc::new(texture)),
+        Err(e) => {
+            eprintln!("material: {}", e);
+            None
+        }
+    }
+}
+
+impl Material {
+    /// Maps a Wavefront MTL material onto the metallic/roughness model:
+    /// - `Kd` → albedo, `d` → opacity, `Ke` → emissive.
+    /// - `Pr`/`Pm` (PBR extension) are used as-is when present.
+    /// - Otherwise roughness comes from the Blinn-Phong exponent `Ns` (α = √(2/(Ns+2)), roughness = √α),
+    ///   and `Ks` counts as metallic only for reflective illumination models (`illum` 3 and up).
+    /// - Below `illum` 3, `Ks` sets the dielectric reflectance instead (Blender's specular 0.5 → F0 0.04).
+    /// - `map_Kd`, `map_Pr`/`map_Ns`, `map_Pm`, `map_Ke` and `map_Ka` (as AO) are loaded relative to `base_dir`.
+    pub fn from_mtl(m: &tobj::Material, base_dir: &Path) -> Self {
+        let defaults = Material::default();
+        let param = |key: &str| m.unknown_param.get(key);
+
+        let albedo = m.diffuse.map(|d| Vector3::new(d[0], d[1], d[2])).unwrap_or(defaults.albedo);
+
+        let roughness = match param("Pr").and_then(|v| v.trim().parse::<f32>().ok()) {
+            Some(r) => r,
+            None => match m.shininess {
+                Some(ns) => (2.0 / (ns.max(0.0) + 2.0)).sqrt().sqrt(),
+                None => defaults.roughness,
+            },
+        };
+
+        let metallic = match param("Pm").and_then(|v| v.trim().parse::<f32>().ok()) {
+            Some(pm) => pm,
+            None => match (m.illumination_model, m.specular) {
+                (Some(illum), Some(ks)) if illum >= 3 => ks[0].max(ks[1]).max(ks[2]),
+                _ => 0.0,
+            },
+        };
+
+        let specular = match (m.illumination_model, m.specular) {
+            (Some(illum), Some(ks)) if illum < 3 => 0.08 * ks[0].max(ks[1]).max(ks[2]),
+            _ => defaults.specular,
+        };
+
+        let emissive = param("Ke").and_then(|v| parse_vec3(v)).unwrap_or(defaults.emissive);
+
+        Material {
+            name: m.name.clone(),
+            albedo,
+            metallic: metallic.clamp(0.0, 1.0),
+            roughness: roughness.clamp(0.04, 1.0),
+            specular: specular.clamp(0.0, 0.08),
+            emissive,
+            ao: 1.0,
+            opacity: m.dissolve.unwrap_or(1.0).clamp(0.0, 1.0),
+            alpha_cutoff: None,
+            albedo_map: load_map(base_dir, m.diffuse_texture.as_ref()),
+            roughness_map: load_map(base_dir, param("map_Pr").or(m.shininess_texture.as_ref())),
+            metallic_map: load_map(base_dir, param("map_Pm")),
+            emissive_map: load_map(base_dir, param("map_Ke")),
+            ao_map: load_map(base_dir, m.ambient_texture.as_ref()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    // Lo que exporta Blender sin extensiones PBR: solo `Ns`, `Ks` e `illum`
+    fn blender(illum: u8) -> tobj::Material {
+        tobj::Material {
+            name: "Hull".into(),
+            shininess: Some(250.0),
+            specular: Some([0.5, 0.5, 0.5]),
+            illumination_model: Some(illum),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn from_mtl_derives_pbr_from_ns_ks_and_illum() {
+        let dir = Path::new("no-such-dir");
+        let roughness = (2.0f32 / 252.0).sqrt().sqrt();
+
+        let plastic = Material::from_mtl(&blender(2), dir);
+        assert_eq!(plastic.name, "Hull");
+        assert!(close(plastic.roughness, roughness));
+        assert_eq!(plastic.metallic, 0.0);
+        assert!(close(plastic.specular, 0.04));
+        assert_eq!(plastic.albedo, Material::default().albedo);
+        assert_eq!(plastic.opacity, 1.0);
+        assert!(plastic.albedo_map.is_none() && plastic.roughness_map.is_none());
+
+        let mirror = Material::from_mtl(&blender(3), dir);
+        assert!(close(mirror.roughness, roughness));
+        assert!(close(mirror.metallic, 0.5));
+        assert_eq!(mirror.specular, Material::default().specular);
+
+        // `Pr`/`Pm` ganan sobre lo derivado
+        let mut explicit = blender(3);
+        explicit.unknown_param.insert("Pr".into(), "0.35".into());
+        explicit.unknown_param.insert("Pm".into(), " 0.8".into());
+        let pbr = Material::from_mtl(&explicit, dir);
+        assert!(close(pbr.roughness, 0.35) && close(pbr.metallic, 0.8));
+    }
+}
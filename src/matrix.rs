@@ -98,6 +98,45 @@ pub fn create_model_matrix(translation: Vector3, scale: f32, rotation: Vector3)
     let mrst = multiply_matrix_matrix(&mt, &mrs);
     mrst
 }
+/// Model matrix for an entity oriented by an explicit basis (columns right, up, forward)
+pub fn create_basis_model_matrix(translation: Vector3, scale: f32, right: Vector3, up: Vector3, forward: Vector3) -> Matrix {
+    let r = right * scale;
+    let u = up * scale;
+    let f = forward * scale;
+
+    Matrix {
+        // Column 0: right
+        m0: r.x, m1: r.y, m2: r.z, m3: 0.0,
+        // Column 1: up
+        m4: u.x, m5: u.y, m6: u.z, m7: 0.0,
+        // Column 2: forward
+        m8: f.x, m9: f.y, m10: f.z, m11: 0.0,
+        // Column 3: translation
+        m12: translation.x, m13: translation.y, m14: translation.z, m15: 1.0,
+    }
+}
+
+/// Applies the inverse of the rotation used by `create_model_matrix` (Rz · Ry · Rx)
+/// to a direction, i.e. takes a world-space direction into object space.
+pub fn inverse_rotate_vector(v: Vector3, rotation: Vector3) -> Vector3 {
+    // Rz^T
+    let (szn, czn) = rotation.z.sin_cos();
+    let v = Vector3::new(czn * v.x + szn * v.y, -szn * v.x + czn * v.y, v.z);
+    // Ry^T
+    let (syn, cyn) = rotation.y.sin_cos();
+    let v = Vector3::new(cyn * v.x - syn * v.z, v.y, syn * v.x + cyn * v.z);
+    // Rx^T
+    let (sxn, cxn) = rotation.x.sin_cos();
+    Vector3::new(v.x, cxn * v.y + sxn * v.z, -sxn * v.y + cxn * v.z)
+}
+
+/// Rotates `v` by the unit quaternion `q` (x, y, z, w), the form glTF uses for node rotations.
+pub fn rotate_by_quaternion(v: Vector3, q: Vector4) -> Vector3 {
+    let u = Vector3::new(q.x, q.y, q.z);
+    let t = u.cross(v) * 2.0;
+    v + t * q.w + u.cross(t)
+}
+
 /// Creates a view matrix using camera position, target, and up vector
 /// This implements a lookAt matrix for camera transformations
 pub fn create_view_matrix(eye: Vector3, target: Vector3, up: Vector3) -> Matrix {
@@ -0,0 +1,244 @@
+// src/mesh.rs
+// Malla indexada: buffer de vértices únicos + buffer de índices (3 por triángulo).
+use std::collections::HashMap;
+
+use raylib::prelude::*;
+
+use crate::procedural::smooth_normals;
+use crate::shaders::{apply_vertex_shader, VertexShader};
+
+/// Object-space bounding volumes: an AABB and a sphere around its centre.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Bounds {
+    pub min: Vector3,
+    pub max: Vector3,
+    pub center: Vector3,
+    pub radius: f32,
+}
+
+impl Bounds {
+    /// Smallest box holding both, with a sphere holding both spheres.
+    pub fn union(&self, other: &Bounds) -> Bounds {
+        let min = Vector3::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y), self.min.z.min(other.min.z));
+        let max = Vector3::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y), self.max.z.max(other.max.z));
+        let center = (min + max) * 0.5;
+        let radius = ((self.center - center).length() + self.radius).max((other.center - center).length() + other.radius);
+        Bounds { min, max, center, radius }
+    }
+
+    /// Grown by `margin` in every direction (e.g. what a vertex shader may displace).
+    pub fn expanded(&self, margin: f32) -> Bounds {
+        let m = Vector3::new(margin, margin, margin);
+        Bounds { min: self.min - m, max: self.max + m, center: self.center, radius: self.radius + margin }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Mesh {
+    pub positions: Vec<Vector3>,
+    pub normals: Vec<Vector3>, // vacío o uno por posición
+    pub uvs: Vec<Vector2>,     // vacío o uno por posición
+    pub indices: Vec<u32>,
+}
+
+impl Mesh {
+    /// Welds a triangle soup (3-by-3) into unique vertices. Corners are merged only when
+    /// position, normal and UV are bit-identical, so hard edges and UV seams survive.
+    /// `normals`/`uvs` may be empty; otherwise they are parallel to `vertices`.
+    pub fn from_soup(vertices: &[Vector3], normals: &[Vector3], uvs: &[Vector2]) -> Self {
+        let has_normals = normals.len() == vertices.len();
+        let has_uvs = uvs.len() == vertices.len();
+        let tri_corners = vertices.len() / 3 * 3;
+
+        // `+ 0.0` convierte -0.0 en 0.0 para que ambos den la misma clave
+        let bits = |x: f32| (x + 0.0).to_bits();
+        let mut lookup: HashMap<[u32; 8], u32> = HashMap::with_capacity(tri_corners / 4);
+        let mut mesh = Mesh::default();
+        mesh.indices.reserve(tri_corners);
+
+        for i in 0..tri_corners {
+            let p = vertices[i];
+            let n = if has_normals { normals[i] } else { Vector3::new(0.0, 0.0, 0.0) };
+            let uv = if has_uvs { uvs[i] } else { Vector2::new(0.0, 0.0) };
+            let key = [bits(p.x), bits(p.y), bits(p.z), bits(n.x), bits(n.y), bits(n.z), bits(uv.x), bits(uv.y)];
+            let index = *lookup.entry(key).or_insert_with(|| {
+                mesh.positions.push(p);
+                if has_normals {
+                    mesh.normals.push(n);
+                }
+                if has_uvs {
+                    mesh.uvs.push(uv);
+                }
+                (mesh.positions.len() - 1) as u32
+            });
+            mesh.indices.push(index);
+        }
+        mesh
+    }
+
+    /// Soup with smooth normals (hard edges above `crease_angle`) and optional UVs.
+    pub fn from_soup_smooth(vertices: &[Vector3], uvs: &[Vector2], crease_angle: f32) -> Self {
+        Mesh::from_soup(vertices, &smooth_normals(vertices, crease_angle), uvs)
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    pub fn has_uvs(&self) -> bool {
+        !self.uvs.is_empty() && self.uvs.len() == self.positions.len()
+    }
+
+    /// Expands the index buffer back into a triangle soup of positions.
+    pub fn soup(&self) -> Vec<Vector3> {
+        self.indices.iter().map(|&i| self.positions[i as usize]).collect()
+    }
+
+    /// Replaces the UVs from values given per soup corner (same order as `soup()`).
+    pub fn with_soup_uvs(self, uvs: &[Vector2]) -> Self {
+        let soup = self.soup();
+        let normals: Vec<Vector3> = if self.normals.len() == self.positions.len() {
+            self.indices.iter().map(|&i| self.normals[i as usize]).collect()
+        } else {
+            Vec::new()
+        };
+        Mesh::from_soup(&soup, &normals, uvs)
+    }
+
+    /// Smooth normals for displaced `positions` (parallel to `self.positions`), e.g. after a
+    /// vertex shader. Corners are welded by position as in `smooth_normals` and averaged back
+    /// into each vertex.
+    pub fn recompute_normals(&self, positions: &[Vector3], crease_angle: f32) -> Vec<Vector3> {
+        let soup: Vec<Vector3> = self.indices.iter().map(|&i| positions[i as usize]).collect();
+        let corner_normals = smooth_normals(&soup, crease_angle);
+
+        let mut normals = vec![Vector3::new(0.0, 0.0, 0.0); positions.len()];
+        for (&i, n) in self.indices.iter().zip(corner_normals) {
+            normals[i as usize] += n;
+        }
+        for n in &mut normals {
+            if n.length() > 0.0 {
+                *n = n.normalized();
+            }
+        }
+        normals
+    }
+
+    /// Copy with the positions moved by a time-independent `vshader` and normals recomputed for
+    /// the new relief, so the displacement is paid once instead of every frame.
+    pub fn displaced(&self, vshader: &VertexShader, crease_angle: f32) -> Mesh {
+        let positions: Vec<Vector3> = self.positions.iter().map(|&p| apply_vertex_shader(p, vshader, 0.0)).collect();
+        let normals = self.recompute_normals(&positions, crease_angle);
+        Mesh { positions, normals, uvs: self.uvs.clone(), indices: self.indices.clone() }
+    }
+
+    /// AABB of the vertices and the sphere around its centre that holds them all; an empty
+    /// mesh gives a point at the origin.
+    pub fn bounds(&self) -> Bounds {
+        let Some(&first) = self.positions.first() else {
+            return Bounds::default();
+        };
+        let (min, max) = self.positions.iter().fold((first, first), |(lo, hi), p| {
+            (Vector3::new(lo.x.min(p.x), lo.y.min(p.y), lo.z.min(p.z)), Vector3::new(hi.x.max(p.x), hi.y.max(p.y), hi.z.max(p.z)))
+        });
+        let center = (min + max) * 0.5;
+        let radius = self.positions.iter().map(|p| (*p - center).length()).fold(0.0, f32::max);
+        Bounds { min, max, center, radius }
+    }
+
+    /// Largest distance from the object-space origin to a vertex.
+    pub fn bounding_radius(&self) -> f32 {
+        self.positions.iter().map(|v| v.length()).fold(0.0, f32::max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(x: f32, y: f32, z: f32) -> Vector3 {
+        Vector3::new(x, y, z)
+    }
+
+    #[test]
+    fn bounds_union_holds_both() {
+        let a = Mesh::from_soup(&[v(-1.0, 0.0, 0.0), v(1.0, 0.0, 0.0), v(0.0, 1.0, 0.0)], &[], &[]).bounds();
+        assert_eq!((a.min, a.max, a.center), (v(-1.0, 0.0, 0.0), v(1.0, 1.0, 0.0), v(0.0, 0.5, 0.0)));
+        assert!((a.radius - 1.25f32.sqrt()).abs() < 1e-6);
+
+        let b = Bounds { min: v(4.0, -1.0, -1.0), max: v(6.0, 1.0, 1.0), center: v(5.0, 0.0, 0.0), radius: 3.0f32.sqrt() };
+        let u = a.union(&b);
+        assert_eq!((u.min, u.max), (v(-1.0, -1.0, -1.0), v(6.0, 1.0, 1.0)));
+        assert_eq!(u.center, v(2.5, 0.0, 0.0));
+        for s in [a, b] {
+            assert!((s.center - u.center).length() + s.radius <= u.radius + 1e-5);
+        }
+        assert_eq!(a.union(&a), a);
+
+        let e = a.expanded(0.5);
+        assert_eq!((e.min, e.max, e.center, e.radius), (v(-1.5, -0.5, -0.5), v(1.5, 1.5, 0.5), a.center, a.radius + 0.5));
+    }
+
+    #[test]
+    fn from_soup_welds_shared_corners_but_keeps_seams() {
+        // Un quad como dos triángulos: comparten la diagonal
+        let quad = [v(0.0, 0.0, 0.0), v(1.0, 0.0, 0.0), v(1.0, 1.0, 0.0), v(0.0, 0.0, 0.0), v(1.0, 1.0, 0.0), v(0.0, 1.0, 0.0)];
+        let mesh = Mesh::from_soup(&quad, &[], &[]);
+        assert_eq!((mesh.positions.len(), mesh.indices.as_slice()), (4, &[0, 1, 2, 0, 2, 3][..]));
+        assert!(mesh.normals.is_empty() && !mesh.has_uvs());
+        assert_eq!(mesh.soup(), quad);
+
+        // La misma posición con otra UV es una costura y no se suelda
+        let uv = |u: f32| Vector2::new(u, 0.0);
+        let seam = Mesh::from_soup(&quad, &[], &[uv(0.0), uv(1.0), uv(1.0), uv(0.5), uv(1.0), uv(0.0)]);
+        assert_eq!(seam.positions.len(), 5);
+        assert!(seam.has_uvs());
+
+        // -0.0 y 0.0 son el mismo vértice
+        let signed = [v(-0.0, 0.0, 0.0), v(1.0, 0.0, 0.0), v(0.0, 1.0, 0.0), v(0.0, -0.0, 0.0), v(0.0, 1.0, 0.0), v(-1.0, 0.0, 0.0)];
+        assert_eq!(Mesh::from_soup(&signed, &[], &[]).positions.len(), 4);
+
+        // Un resto que no llega a triángulo se ignora
+        assert_eq!(Mesh::from_soup(&quad[..5], &[], &[]).triangle_count(), 1);
+    }
+
+    #[test]
+    fn with_soup_uvs_splits_vertices_and_keeps_normals() {
+        let quad = [v(0.0, 0.0, 0.0), v(1.0, 0.0, 0.0), v(1.0, 1.0, 0.0), v(0.0, 0.0, 0.0), v(1.0, 1.0, 0.0), v(0.0, 1.0, 0.0)];
+        let mesh = Mesh::from_soup(&quad, &[v(0.0, 0.0, 1.0); 6], &[]);
+        assert_eq!(mesh.positions.len(), 4);
+
+        let uvs: Vec<Vector2> = (0..6).map(|i| Vector2::new(i as f32, 0.0)).collect();
+        let split = mesh.with_soup_uvs(&uvs);
+        assert_eq!(split.positions.len(), 6);
+        assert_eq!(split.soup(), quad);
+        assert_eq!(split.normals, vec![v(0.0, 0.0, 1.0); 6]);
+        let corner_uvs: Vec<Vector2> = split.indices.iter().map(|&i| split.uvs[i as usize]).collect();
+        assert_eq!(corner_uvs, uvs);
+    }
+
+    #[test]
+    fn recompute_normals_follows_displaced_positions() {
+        let quad = [v(0.0, 0.0, 0.0), v(1.0, 0.0, 0.0), v(1.0, 1.0, 0.0), v(0.0, 0.0, 0.0), v(1.0, 1.0, 0.0), v(0.0, 1.0, 0.0)];
+        let mesh = Mesh::from_soup(&quad, &[], &[]);
+        for n in mesh.recompute_normals(&mesh.positions, 1.0) {
+            assert!((n - v(0.0, 0.0, 1.0)).length() < 1e-6);
+        }
+
+        // Girado 90° alrededor de X, el plano pasa a mirar a -Y
+        let turned: Vec<Vector3> = mesh.positions.iter().map(|p| v(p.x, p.z, p.y)).collect();
+        for n in mesh.recompute_normals(&turned, 1.0) {
+            assert!((n - v(0.0, -1.0, 0.0)).length() < 1e-6, "{:?}", n);
+        }
+
+        // Doblado por la diagonal más que el ángulo de pliegue: los vértices compartidos promedian
+        let mut folded = mesh.positions.clone();
+        folded[3].z = 1.0;
+        let normals = mesh.recompute_normals(&folded, 0.1);
+        assert!((normals[1] - v(0.0, 0.0, 1.0)).length() < 1e-6);
+        assert!(normals[0].z > 0.0 && normals[0].z < 1.0);
+        for n in &normals {
+            assert!((n.length() - 1.0).abs() < 1e-5);
+        }
+    }
+}
@@ -1,40 +1,126 @@
+use std::path::Path;
+use std::sync::Arc;
+
 use raylib::math::{Vector2, Vector3};
 use tobj;
 
+use crate::entity::{MeshPart, CREASE_ANGLE};
+use crate::material::Material;
+use crate::mesh::Mesh;
+use crate::shaders::FragmentShader;
+
+/// Geometry of an OBJ drawn with a single material (all groups/objects that use it).
+pub struct SubMesh {
+    pub name: String,              // grupos u objetos que la forman, separados por '+'
+    pub mesh: Mesh,
+    pub material: Option<usize>,   // índice en `Obj::materials`
+}
+
 pub struct Obj {
-    pub vertices: Vec<Vector3>,
-    pub indices: Vec<u32>,
+    pub submeshes: Vec<SubMesh>,
+    pub materials: Vec<Material>,
 }
 
 impl Obj {
     pub fn load(path: &str) -> Result<Self, tobj::LoadError> {
-        let (models, _materials) = tobj::load_obj(path, &tobj::GPU_LOAD_OPTIONS)?;
+        let (models, mtl) = tobj::load_obj(path, &tobj::GPU_LOAD_OPTIONS)?;
 
-        let mut vertices = Vec::new();
-        let mut indices = Vec::new();
+        // Un .mtl ausente o inválido no impide cargar la geometría
+        let base_dir = Path::new(path).parent().unwrap_or(Path::new(""));
+        let materials: Vec<Material> = match mtl {
+            Ok(mtl) => mtl.iter().map(|m| Material::from_mtl(m, base_dir)).collect(),
+            Err(e) => {
+                eprintln!("obj: materials for '{}' not loaded: {}", path, e);
+                Vec::new()
+            }
+        };
 
+        // tobj entrega un modelo por grupo/objeto y por cambio de `usemtl`: se juntan por material
+        let mut submeshes: Vec<SubMesh> = Vec::new();
         for model in models {
-            let mesh = &model.mesh;
-            let num_vertices = mesh.positions.len() / 3;
-
-            for i in 0..num_vertices {
-                let x = mesh.positions[i * 3];
-                let y = mesh.positions[i * 3 + 1];
-                let z = mesh.positions[i * 3 + 2];
-                let position = Vector3::new(x, y, z);
-                vertices.push(position);
+            let material = model.mesh.material_id.filter(|&id| id < materials.len());
+            let part = mesh_from_tobj(&model.mesh);
+            match submeshes.iter_mut().find(|s| s.material == material) {
+                Some(sub) => {
+                    append(&mut sub.mesh, part);
+                    if !sub.name.split('+').any(|n| n == model.name) {
+                        sub.name = format!("{}+{}", sub.name, model.name);
+                    }
+                }
+                None => submeshes.push(SubMesh { name: model.name, mesh: part, material }),
             }
-            indices.extend_from_slice(&mesh.indices);
         }
 
-        Ok(Obj { vertices, indices })
+        Ok(Obj { submeshes, materials })
+    }
+
+    /// Material of a sub-mesh, or the default one if it has none.
+    pub fn material_of(&self, sub: &SubMesh) -> Material {
+        sub.material.and_then(|id| self.materials.get(id)).cloned().unwrap_or_default()
+    }
+
+    /// One drawable part per sub-mesh, shaded with its material through `Pbr`.
+    pub fn to_parts(&self) -> Vec<MeshPart> {
+        self.submeshes
+            .iter()
+            .map(|sub| MeshPart {
+                name: sub.name.clone(),
+                mesh: Arc::new(sub.mesh.clone()),
+                fshader: FragmentShader::Pbr { material: self.material_of(sub) },
+            })
+            .collect()
     }
 
+    /// All sub-meshes expanded into a single triangle soup of positions.
     pub fn get_vertex_array(&self) -> Vec<Vector3> {
-        let mut vertex_array = Vec::new();
-        for &index in &self.indices {
-            vertex_array.push(self.vertices[index as usize].clone());
+        self.submeshes.iter().flat_map(|s| s.mesh.soup()).collect()
+    }
+}
+
+/// Indexed mesh from one tobj model (single index: positions, normals and texcoords are parallel).
+/// Without `vn` the normals are smoothed from the faces; `vt` is flipped because OBJ puts v=0 at
+/// the bottom of the image and our textures start at the top row.
+fn mesh_from_tobj(m: &tobj::Mesh) -> Mesh {
+    let count = m.positions.len() / 3;
+    let positions: Vec<Vector3> = (0..count)
+        .map(|i| Vector3::new(m.positions[3 * i], m.positions[3 * i + 1], m.positions[3 * i + 2]))
+        .collect();
+    let uvs: Vec<Vector2> = if m.texcoords.len() == count * 2 {
+        (0..count).map(|i| Vector2::new(m.texcoords[2 * i], 1.0 - m.texcoords[2 * i + 1])).collect()
+    } else {
+        Vec::new()
+    };
+
+    if m.normals.len() == count * 3 {
+        let normals = (0..count)
+            .map(|i| {
+                let n = Vector3::new(m.normals[3 * i], m.normals[3 * i + 1], m.normals[3 * i + 2]);
+                if n.length() > 0.0 { n.normalized() } else { n }
+            })
+            .collect();
+        Mesh { positions, normals, uvs, indices: m.indices.clone() }
+    } else {
+        let soup: Vec<Vector3> = m.indices.iter().map(|&i| positions[i as usize]).collect();
+        let soup_uvs: Vec<Vector2> = if uvs.is_empty() { Vec::new() } else { m.indices.iter().map(|&i| uvs[i as usize]).collect() };
+        Mesh::from_soup_smooth(&soup, &soup_uvs, CREASE_ANGLE)
+    }
+}
+
+/// Appends `part` to `mesh`, offsetting its local indices into the combined buffer.
+fn append(mesh: &mut Mesh, part: Mesh) {
+    let base = mesh.positions.len() as u32;
+    // Si solo una de las dos trae UVs, la otra se rellena con ceros para mantenerlas paralelas
+    if mesh.has_uvs() || part.has_uvs() {
+        if !mesh.has_uvs() {
+            mesh.uvs = vec![Vector2::new(0.0, 0.0); mesh.positions.len()];
+        }
+        if part.has_uvs() {
+            mesh.uvs.extend(&part.uvs);
+        } else {
+            mesh.uvs.extend(std::iter::repeat_n(Vector2::new(0.0, 0.0), part.positions.len()));
         }
-        vertex_array
     }
-}
\ No newline at end of file
+    mesh.positions.extend(part.positions);
+    mesh.normals.extend(part.normals);
+    mesh.indices.extend(part.indices.iter().map(|i| i + base));
+}
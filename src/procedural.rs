@@ -2,7 +2,7 @@
 use raylib::prelude::*;
 
 /// Generate a UV-sphere (triangulated) without external models.
-/// Returns a flat Vec of positions grouped in triangles (3-by-3).
+/// Returns a flat Vec of positions grouped in triangles (3-by-3), counter-clockwise seen from outside.
 pub fn generate_uv_sphere(radius: f32, lat_segments: usize, lon_segments: usize) -> Vec<Vector3> {
     let lat = lat_segments.max(3);
     let lon = lon_segments.max(3);
@@ -27,14 +27,167 @@ pub fn generate_uv_sphere(radius: f32, lat_segments: usize, lon_segments: usize)
             let p10 = sph(radius, phi1, theta0);
             let p11 = sph(radius, phi1, theta1);
 
-            // Two triangles per quad (p00, p10, p11) and (p00, p11, p01)
-            out.push(p00); out.push(p10); out.push(p11);
-            out.push(p00); out.push(p11); out.push(p01);
+            // Two triangles per quad (p00, p11, p10) and (p00, p01, p11)
+            out.push(p00); out.push(p11); out.push(p10);
+            out.push(p00); out.push(p01); out.push(p11);
         }
     }
     out
 }
 
+/// Equirectangular texture coordinates for `generate_uv_sphere` with the same segment counts.
+/// Parallel to its output: one UV per emitted vertex, u = longitude, v = 0 at the north pole.
+pub fn generate_uv_sphere_uvs(lat_segments: usize, lon_segments: usize) -> Vec<Vector2> {
+    let lat = lat_segments.max(3);
+    let lon = lon_segments.max(3);
+    let mut out: Vec<Vector2> = Vec::with_capacity(lat * lon * 6);
+
+    for i in 0..lat {
+        let v0 = i as f32 / lat as f32;
+        let v1 = (i + 1) as f32 / lat as f32;
+        for j in 0..lon {
+            // u1 llega a 1.0 en la última columna, así la costura no interpola hacia atrás
+            let u0 = j as f32 / lon as f32;
+            let u1 = (j + 1) as f32 / lon as f32;
+
+            let t00 = Vector2::new(u0, v0);
+            let t01 = Vector2::new(u1, v0);
+            let t10 = Vector2::new(u0, v1);
+            let t11 = Vector2::new(u1, v1);
+
+            out.push(t00); out.push(t11); out.push(t10);
+            out.push(t00); out.push(t01); out.push(t11);
+        }
+    }
+    out
+}
+
+/// Which tessellation to use for a sphere.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SphereKind {
+    Uv,         // latitud/longitud: barato, pero concentra triángulos en los polos
+    Icosphere,  // icosaedro subdividido: triángulos casi iguales
+    CubeSphere, // cubo subdividido y proyectado a la esfera
+}
+
+/// Icosahedron subdivided `subdivisions` times and projected onto the sphere.
+/// Triangle soup (3-by-3), counter-clockwise seen from outside; 60·4^s vertices.
+pub fn generate_icosphere(radius: f32, subdivisions: u32) -> Vec<Vector3> {
+    let t = (1.0 + 5.0f32.sqrt()) / 2.0;
+    let corners = [
+        Vector3::new(-1.0, t, 0.0), Vector3::new(1.0, t, 0.0), Vector3::new(-1.0, -t, 0.0), Vector3::new(1.0, -t, 0.0),
+        Vector3::new(0.0, -1.0, t), Vector3::new(0.0, 1.0, t), Vector3::new(0.0, -1.0, -t), Vector3::new(0.0, 1.0, -t),
+        Vector3::new(t, 0.0, -1.0), Vector3::new(t, 0.0, 1.0), Vector3::new(-t, 0.0, -1.0), Vector3::new(-t, 0.0, 1.0),
+    ]
+    .map(|c| c.normalized());
+    const FACES: [[usize; 3]; 20] = [
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    let mut tris: Vec<[Vector3; 3]> = FACES.iter().map(|f| [corners[f[0]], corners[f[1]], corners[f[2]]]).collect();
+    for _ in 0..subdivisions {
+        // Cada triángulo se parte en 4 con los puntos medios llevados a la esfera
+        let mid = |a: Vector3, b: Vector3| (a + b).normalized();
+        tris = tris
+            .iter()
+            .flat_map(|&[a, b, c]| {
+                let (ab, bc, ca) = (mid(a, b), mid(b, c), mid(c, a));
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    tris.iter().flat_map(|tri| tri.map(|v| v * radius)).collect()
+}
+
+/// Cube with `resolution`x`resolution` quads per face, spherified so cells keep similar area.
+/// Triangle soup (3-by-3), counter-clockwise seen from outside; 36·resolution² vertices.
+pub fn generate_cube_sphere(radius: f32, resolution: usize) -> Vec<Vector3> {
+    let n = resolution.max(1);
+    let x = Vector3::new(1.0, 0.0, 0.0);
+    let y = Vector3::new(0.0, 1.0, 0.0);
+    let z = Vector3::new(0.0, 0.0, 1.0);
+    // (normal, eje u, eje v) con u × v = normal para que el giro quede hacia afuera
+    let faces = [(x, y, z), (-x, z, y), (y, z, x), (-y, x, z), (z, x, y), (-z, y, x)];
+
+    // Proyección "spherified cube": reparte los vértices mejor que normalizar sin más
+    let spherify = |p: Vector3| {
+        let (x2, y2, z2) = (p.x * p.x, p.y * p.y, p.z * p.z);
+        Vector3::new(
+            p.x * (1.0 - y2 / 2.0 - z2 / 2.0 + y2 * z2 / 3.0).sqrt(),
+            p.y * (1.0 - z2 / 2.0 - x2 / 2.0 + z2 * x2 / 3.0).sqrt(),
+            p.z * (1.0 - x2 / 2.0 - y2 / 2.0 + x2 * y2 / 3.0).sqrt(),
+        ) * radius
+    };
+
+    let mut out = Vec::with_capacity(36 * n * n);
+    for (normal, u, v) in faces {
+        let point = |i: usize, j: usize| {
+            let a = 2.0 * i as f32 / n as f32 - 1.0;
+            let b = 2.0 * j as f32 / n as f32 - 1.0;
+            spherify(normal + u * a + v * b)
+        };
+        for i in 0..n {
+            for j in 0..n {
+                let (p00, p10, p11, p01) = (point(i, j), point(i + 1, j), point(i + 1, j + 1), point(i, j + 1));
+                out.push(p00); out.push(p10); out.push(p11);
+                out.push(p00); out.push(p11); out.push(p01);
+            }
+        }
+    }
+    out
+}
+
+/// Outward unit normals for any sphere centered at the origin (parallel to `vertices`).
+pub fn sphere_normals(vertices: &[Vector3]) -> Vec<Vector3> {
+    vertices
+        .iter()
+        .map(|v| if v.length() > 0.0 { v.normalized() } else { Vector3::new(0.0, 1.0, 0.0) })
+        .collect()
+}
+
+/// Equirectangular UVs (same convention as `generate_uv_sphere_uvs`) for a sphere triangle soup.
+/// Triangles crossing the longitude seam get u shifted past 1 so they do not interpolate across
+/// the whole map, and pole vertices take the u of the rest of their triangle.
+pub fn sphere_uvs(vertices: &[Vector3]) -> Vec<Vector2> {
+    let mut out = Vec::with_capacity(vertices.len());
+    for tri in vertices.chunks(3) {
+        let mut uv: Vec<Vector2> = tri
+            .iter()
+            .map(|p| {
+                let d = if p.length() > 0.0 { p.normalized() } else { Vector3::new(0.0, 1.0, 0.0) };
+                let u = d.z.atan2(d.x) / std::f32::consts::TAU;
+                Vector2::new(u - u.floor(), d.y.clamp(-1.0, 1.0).acos() / std::f32::consts::PI)
+            })
+            .collect();
+
+        let is_pole = |p: &Vector3| p.x.abs() < 1e-6 * p.length().max(1.0) && p.z.abs() < 1e-6 * p.length().max(1.0);
+        let regular: Vec<usize> = (0..tri.len()).filter(|&k| !is_pole(&tri[k])).collect();
+
+        let (min_u, max_u) = regular.iter().fold((1.0f32, 0.0f32), |(lo, hi), &k| (lo.min(uv[k].x), hi.max(uv[k].x)));
+        if max_u - min_u > 0.5 {
+            for &k in &regular {
+                if uv[k].x < 0.5 {
+                    uv[k].x += 1.0;
+                }
+            }
+        }
+        if !regular.is_empty() {
+            let mean_u = regular.iter().map(|&k| uv[k].x).sum::<f32>() / regular.len() as f32;
+            for k in 0..tri.len() {
+                if is_pole(&tri[k]) {
+                    uv[k].x = mean_u;
+                }
+            }
+        }
+        out.extend(uv);
+    }
+    out
+}
+
 #[inline]
 fn sph(r: f32, phi: f32, theta: f32) -> Vector3 {
     // Spherical coordinates: phi ∈ [0,PI] from north to south, theta ∈ [0,2PI]
@@ -77,6 +230,37 @@ fn polar(r: f32, ang: f32) -> Vector3 {
     Vector3::new(r * ang.cos(), 0.0, r * ang.sin())
 }
 
+/// Small deterministic generator (splitmix32): every seeded generator in the project (rocks,
+/// ships, the asteroid belt, solar prominences) draws from it, so a seed always gives the same result.
+pub struct Rng(u32);
+
+impl Rng {
+    pub fn new(seed: u32) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9);
+        let mut z = self.0;
+        z = (z ^ (z >> 16)).wrapping_mul(0x85EB_CA6B);
+        z = (z ^ (z >> 13)).wrapping_mul(0xC2B2_AE35);
+        z ^ (z >> 16)
+    }
+
+    /// Uniform in [0, 1).
+    pub fn unit(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    pub fn range(&mut self, lo: f32, hi: f32) -> f32 {
+        lo + (hi - lo) * self.unit()
+    }
+
+    pub fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[(self.next_u32() as usize) % items.len()]
+    }
+}
+
 // --- 3D Value Noise + FBM (no external crates) ---
 #[inline]
 fn hash_u32(mut x: u32) -> u32 {
@@ -153,3 +337,195 @@ pub fn fbm3(mut p: Vector3, octaves: u32, lacunarity: f32, gain: f32) -> f32 {
     }
     if total_amp > 0.0 { sum / total_amp } else { 0.0 }
 }
+
+/// Irregular rock for asteroid belts and small moons: an icosphere with `detail` subdivisions,
+/// stretched into a random ellipsoid, lumped with FBM and stamped with craters. `irregularity`
+/// (0..1) scales all of it, so 0 gives a plain sphere of `radius`. Same seed, same rock.
+/// Triangle soup (3-by-3) like `generate_icosphere`.
+pub fn generate_asteroid(seed: u32, radius: f32, irregularity: f32, detail: u32) -> Vec<Vector3> {
+    let irregularity = irregularity.clamp(0.0, 1.0);
+    let mut rng = Rng::new(seed ^ 0x5EED_A57E);
+    let mut rand = || rng.unit();
+
+    // Semiejes del elipsoide y desplazamiento del ruido propios de la semilla
+    let mut axis = || 1.0 + irregularity * 0.35 * (2.0 * rand() - 1.0);
+    let axes = Vector3::new(axis(), axis(), axis());
+    let offset = Vector3::new(rand(), rand(), rand()) * 100.0;
+
+    // Cráteres: dirección uniforme en la esfera, radio angular y profundidad
+    let crater_count = 4 + (rand() * 8.0) as usize;
+    let craters: Vec<(Vector3, f32)> = (0..crater_count)
+        .map(|_| {
+            let z = 2.0 * rand() - 1.0;
+            let phi = rand() * std::f32::consts::TAU;
+            let s = (1.0 - z * z).sqrt();
+            (Vector3::new(s * phi.cos(), s * phi.sin(), z), 0.15 + rand() * 0.35)
+        })
+        .collect();
+
+    // La altura depende solo de la dirección, así las esquinas repetidas de la sopa coinciden
+    let height = |dir: Vector3| {
+        let lumps = fbm3(dir * 1.6 + offset, 4, 2.0, 0.5) * 0.45;
+        let dents: f32 = craters
+            .iter()
+            .map(|&(center, size)| {
+                let d = dir.dot(center).clamp(-1.0, 1.0).acos() / size;
+                let bowl = if d < 1.0 { d * d - 1.0 } else { 0.0 };
+                let rim = 0.3 * (1.0 - ((d - 1.0) / 0.3).powi(2)).max(0.0);
+                (bowl + rim) * size * 0.35
+            })
+            .sum();
+        (1.0 + irregularity * (lumps + dents)).max(0.3)
+    };
+
+    generate_icosphere(1.0, detail)
+        .into_iter()
+        .map(|dir| Vector3::new(dir.x * axes.x, dir.y * axes.y, dir.z * axes.z) * (height(dir) * radius))
+        .collect()
+}
+
+/// Per-vertex normals for a triangle soup (3-by-3, as every generator here returns).
+/// Corners that share a position are welded and their face normals averaged (area-weighted),
+/// except across edges sharper than `crease_angle` (radians), which stay hard.
+pub fn smooth_normals(vertices: &[Vector3], crease_angle: f32) -> Vec<Vector3> {
+    use std::collections::HashMap;
+
+    let tri_count = vertices.len() / 3;
+    // Normal sin normalizar: su módulo es el doble del área del triángulo
+    let faces: Vec<Vector3> = (0..tri_count)
+        .map(|t| {
+            let (a, b, c) = (vertices[3 * t], vertices[3 * t + 1], vertices[3 * t + 2]);
+            (b - a).cross(c - a)
+        })
+        .collect();
+
+    // Soldar esquinas por posición cuantizada
+    let key = |p: Vector3| ((p.x * 1e4).round() as i64, (p.y * 1e4).round() as i64, (p.z * 1e4).round() as i64);
+    let mut groups: HashMap<(i64, i64, i64), Vec<usize>> = HashMap::new();
+    for (i, v) in vertices.iter().enumerate().take(tri_count * 3) {
+        groups.entry(key(*v)).or_default().push(i / 3);
+    }
+
+    let cos_crease = crease_angle.cos();
+    let unit = |v: Vector3| if v.length() > 0.0 { v.normalized() } else { v };
+    let mut normals = Vec::with_capacity(vertices.len());
+    for (i, v) in vertices.iter().enumerate() {
+        if i >= tri_count * 3 {
+            normals.push(Vector3::new(0.0, 0.0, 0.0));
+            continue;
+        }
+        let own = unit(faces[i / 3]);
+        let mut sum = Vector3::new(0.0, 0.0, 0.0);
+        for &t in &groups[&key(*v)] {
+            if unit(faces[t]).dot(own) >= cos_crease {
+                sum += faces[t];
+            }
+        }
+        normals.push(if sum.length() > 0.0 { sum.normalized() } else { own });
+    }
+    normals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    type Key = (i64, i64, i64);
+
+    fn key(p: Vector3) -> Key {
+        ((p.x * 1e4).round() as i64, (p.y * 1e4).round() as i64, (p.z * 1e4).round() as i64)
+    }
+
+    /// Closed and consistently oriented: every directed edge has exactly one opposite twin.
+    fn assert_closed(vertices: &[Vector3]) {
+        let mut edges: HashMap<(Key, Key), i32> = HashMap::new();
+        for tri in vertices.chunks(3) {
+            for k in 0..3 {
+                *edges.entry((key(tri[k]), key(tri[(k + 1) % 3]))).or_default() += 1;
+            }
+        }
+        for (&(a, b), &count) in &edges {
+            assert_eq!(count, 1, "edge {:?} -> {:?} used {} times", a, b, count);
+            assert_eq!(edges.get(&(b, a)), Some(&1), "edge {:?} -> {:?} has no twin", a, b);
+        }
+    }
+
+    fn assert_on_sphere(vertices: &[Vector3], radius: f32) {
+        for v in vertices {
+            assert!((v.length() - radius).abs() < 1e-4 * radius, "vertex {:?} off the sphere", v);
+        }
+    }
+
+    fn assert_outward(vertices: &[Vector3]) {
+        for tri in vertices.chunks(3) {
+            let n = (tri[1] - tri[0]).cross(tri[2] - tri[0]);
+            // Los polos de la esfera UV repiten vértice: triángulos sin área
+            if n.length() < 1e-6 {
+                continue;
+            }
+            assert!(n.dot(tri[0] + tri[1] + tri[2]) > 0.0, "triangle {:?} faces inward", tri);
+        }
+    }
+
+    #[test]
+    fn icosphere_counts_closed_and_radius() {
+        for s in 0..4 {
+            let mesh = generate_icosphere(2.5, s);
+            assert_eq!(mesh.len(), 60 * 4usize.pow(s));
+            assert_closed(&mesh);
+            assert_on_sphere(&mesh, 2.5);
+            assert_outward(&mesh);
+        }
+    }
+
+    #[test]
+    fn cube_sphere_counts_closed_and_radius() {
+        for n in [1, 2, 5, 8] {
+            let mesh = generate_cube_sphere(0.75, n);
+            assert_eq!(mesh.len(), 36 * n * n);
+            assert_closed(&mesh);
+            assert_on_sphere(&mesh, 0.75);
+            assert_outward(&mesh);
+        }
+    }
+
+    #[test]
+    fn uv_sphere_counts_radius_and_winding() {
+        let mesh = generate_uv_sphere(1.5, 8, 12);
+        assert_eq!(mesh.len(), 8 * 12 * 6);
+        assert_eq!(generate_uv_sphere_uvs(8, 12).len(), mesh.len());
+        assert_on_sphere(&mesh, 1.5);
+        assert_outward(&mesh);
+    }
+
+    #[test]
+    fn sphere_normals_and_uvs() {
+        let mesh = generate_icosphere(3.0, 2);
+        for (v, n) in mesh.iter().zip(sphere_normals(&mesh)) {
+            assert!((n.length() - 1.0).abs() < 1e-5);
+            assert!((n * 3.0 - *v).length() < 1e-4);
+        }
+        let uvs = sphere_uvs(&mesh);
+        assert_eq!(uvs.len(), mesh.len());
+        for tri in uvs.chunks(3) {
+            let (lo, hi) = tri.iter().fold((f32::MAX, f32::MIN), |(lo, hi), t| (lo.min(t.x), hi.max(t.x)));
+            assert!(hi - lo < 0.5, "triangle spans the seam: {:?}", tri);
+            assert!(tri.iter().all(|t| (0.0..=1.0).contains(&t.y)));
+        }
+    }
+
+    #[test]
+    fn asteroid_is_seeded_closed_and_lumpy() {
+        let rock = generate_asteroid(42, 2.0, 0.8, 3);
+        assert_eq!(rock.len(), 60 * 4usize.pow(3));
+        assert_closed(&rock);
+        assert_eq!(rock, generate_asteroid(42, 2.0, 0.8, 3));
+        assert_ne!(rock, generate_asteroid(43, 2.0, 0.8, 3));
+
+        let (lo, hi) = rock.iter().fold((f32::MAX, f32::MIN), |(lo, hi), v| (lo.min(v.length()), hi.max(v.length())));
+        assert!(hi - lo > 0.2 * 2.0, "not lumpy enough: {}..{}", lo, hi);
+
+        assert_on_sphere(&generate_asteroid(42, 2.0, 0.0, 2), 2.0);
+    }
+}
@@ -0,0 +1,138 @@
+// src/prominence.rs
+// Protuberancias y bucles coronales del Sol como partículas en el limbo.
+use raylib::prelude::*;
+
+use crate::{framebuffer::Framebuffer, FrameContext, procedural::Rng, shaders::{fract, temperature_to_rgb}, transform, uniforms::vec3_to_color};
+
+/// Un arco de plasma anclado en el limbo: pies en `angle ± half_width` y altura relativa al radio.
+struct Arc {
+    angle: f32,
+    half_width: f32,
+    height: f32,
+    period: f32,
+    seed: f32,
+    particles: usize,
+    size: f32,    // radio de cada partícula en unidades de mundo
+    opacity: f32,
+}
+
+fn arcs() -> Vec<Arc> {
+    let mut rng = Rng::new(0x5EED_5011);
+    let mut out = Vec::new();
+    // Protuberancias: arcos compactos y brillantes
+    for i in 0..6 {
+        out.push(Arc {
+            angle: i as f32 * 1.05 + rng.range(0.0, 0.6),
+            half_width: rng.range(0.06, 0.12),
+            height: rng.range(0.12, 0.27),
+            period: rng.range(9.0, 17.0),
+            seed: rng.unit(),
+            particles: 60,
+            size: 0.35,
+            opacity: 0.8,
+        });
+    }
+    // Bucles coronales: más grandes, finos y tenues
+    for _ in 0..4 {
+        out.push(Arc {
+            angle: rng.range(0.0, std::f32::consts::TAU),
+            half_width: rng.range(0.18, 0.28),
+            height: rng.range(0.35, 0.6),
+            period: rng.range(20.0, 30.0),
+            seed: rng.unit(),
+            particles: 90,
+            size: 0.22,
+            opacity: 0.35,
+        });
+    }
+    out
+}
+
+/// El Sol tal como lo ven las protuberancias: dónde está, qué tan grande es y sus controles.
+pub struct SunDisc {
+    pub position: Vector3,
+    pub radius: f32,
+    pub temp: f32,      // mismos controles que el shader Star
+    pub intensity: f32,
+}
+
+/// Dibuja las protuberancias alrededor del disco solar visto desde `ctx.eye`.
+/// Los arcos viven en el plano perpendicular a la línea de visión que pasa por el centro del Sol,
+/// así que siempre quedan sobre el limbo sin importar la orientación de la esfera.
+pub fn draw_prominences(framebuffer: &mut Framebuffer, ctx: &FrameContext, sun: &SunDisc) {
+    let SunDisc { position: sun_pos, radius: sun_radius, temp, intensity } = *sun;
+    let (view, projection, viewport, time) = (&ctx.view, &ctx.projection, &ctx.viewport, ctx.time);
+    let to_sun = sun_pos - ctx.eye;
+    if to_sun.length() <= sun_radius {
+        return;
+    }
+    let view_dir = to_sun.normalized();
+    let mut right = view_dir.cross(Vector3::new(0.0, 1.0, 0.0));
+    if right.length() < 1e-6 {
+        right = Vector3::new(1.0, 0.0, 0.0);
+    }
+    let right = right.normalized();
+    let up = right.cross(view_dir);
+
+    // Mismos controles que el shader Star
+    let base = temperature_to_rgb((temp * 0.6 + 0.1).clamp(0.0, 1.0));
+    let gain = intensity.clamp(0.2, 2.0);
+
+    for arc in arcs() {
+        // Ciclo de vida: el arco crece, se mantiene y colapsa
+        let life = fract(time / arc.period + arc.seed);
+        let rise = (life * std::f32::consts::PI).sin();
+        let height = arc.height * rise * (0.6 + 0.4 * gain);
+        if height <= 0.01 {
+            continue;
+        }
+        let sway = (time * 0.3 + arc.seed * 6.0).sin() * 0.03;
+
+        for j in 0..arc.particles {
+            let s = j as f32 / (arc.particles - 1) as f32; // 0..1 de pie a pie
+            let a = arc.angle + sway + (s * 2.0 - 1.0) * arc.half_width;
+            let lift = 1.0 + height * (s * std::f32::consts::PI).sin();
+            let world = sun_pos + (right * a.cos() + up * a.sin()) * (sun_radius * lift);
+
+            let Some(screen) = transform(world, Vector3::new(0.0, 0.0, 0.0), 1.0, Vector3::new(0.0, 0.0, 0.0), view, projection, viewport) else {
+                continue;
+            };
+
+            // Nudos de plasma que fluyen a lo largo del arco
+            let knots = 0.6 + 0.4 * ((s * 3.0 - time * 0.4 + arc.seed) * std::f32::consts::TAU).sin();
+            let alpha = (arc.opacity * knots * rise).clamp(0.0, 1.0);
+            let color = base * (0.7 + 0.5 * knots) * gain;
+
+            // Tamaño en pantalla: proyectar un punto desplazado `size` en el eje up
+            let radius_px = transform(world + up * arc.size, Vector3::new(0.0, 0.0, 0.0), 1.0, Vector3::new(0.0, 0.0, 0.0), view, projection, viewport)
+                .map(|o| ((o.x - screen.x).powi(2) + (o.y - screen.y).powi(2)).sqrt())
+                .unwrap_or(1.0)
+                .clamp(1.0, 6.0);
+
+            splat(framebuffer, screen, radius_px, vec3_to_color(color), alpha);
+        }
+    }
+}
+
+/// Disco con caída suave de opacidad, mezclado sobre el framebuffer con test de profundidad.
+fn splat(framebuffer: &mut Framebuffer, screen: Vector3, radius: f32, color: Color, alpha: f32) {
+    let r = radius.ceil() as i32;
+    let cx = screen.x as i32;
+    let cy = screen.y as i32;
+    framebuffer.set_current_color(color);
+    for dy in -r..=r {
+        for dx in -r..=r {
+            let x = cx + dx;
+            let y = cy + dy;
+            if x < 0 || y < 0 || x >= framebuffer.width as i32 || y >= framebuffer.height as i32 {
+                continue;
+            }
+            let d = ((dx * dx + dy * dy) as f32).sqrt() / radius;
+            if d > 1.0 {
+                continue;
+            }
+            let falloff = 1.0 - d * d;
+            framebuffer.blend_pixel(x as u32, y as u32, screen.z, alpha * falloff);
+        }
+    }
+}
@@ -1,7 +1,12 @@
 use std::f32::consts::PI;
+use std::sync::Arc;
 
 use raylib::prelude::*;
+use crate::dsl::Program;
 use crate::fragment::Fragment;
+use crate::material::Material;
+use crate::simd::{self, F32x4, Vec3x4, LANES};
+use crate::texture::{Filter, Sampler, Texture, Wrap};
 use crate::uniforms::Uniforms;
 
 #[derive(Clone)]
@@ -9,6 +14,9 @@ pub enum VertexShader {
     Identity,
     SolarFlare,
     DisplacePlanarY  { amp: f32, freq: f32, octaves: u32, lacunarity: f32, gain: f32, time_amp: f32 },
+    Volcanic { amp: f32, freq: f32 },
+    TerrainDisplace { seed: u32, amplitude: f32, frequency: f32, octaves: i32, crater_density: f32 },
+    Expr { program: Arc<Program> }, // bloque `vertex` de un material en el DSL
 }
 
 #[derive(Clone)]
@@ -17,14 +25,96 @@ pub enum FragmentShader {
     Solid { color: Vector3 },
     Rocky { color: Vector3 },
     Strips { angle: f32 },
-    AlienShip
+    Volcanic { crust: Vector3, lava: Vector3, flow: f32 },
+    Icy { tint: Vector3, crack_density: f32 },
+    GasGiant { palette: Vec<Vector3>, bands: u32, wind: f32, storms: Vec<Storm> },
+    Earth { ocean: Vector3, land: Vector3, cloud_coverage: f32, cloud_spin: f32 },
+    Clouds { coverage: f32 },
+    Ring { inner: f32, outer: f32, color: Vector3, planet_radius: f32 },
+    Textured { texture: Arc<Texture>, normal_map: Option<Arc<Texture>>, sampler: Sampler },
+    Baked { source: Box<FragmentShader>, map: Arc<Texture> },
+    Pbr { material: Material },
+    Expr { program: Arc<Program> }, // bloque `fragment` de un material en el DSL
 }
 
+/// Vórtice fijo en (lat, lon) del planeta, en radianes. `radius` es angular.
+#[derive(Clone)]
+pub struct Storm {
+    pub lat: f32,
+    pub lon: f32,
+    pub radius: f32,
+    pub color: Vector3,
+    pub spin: f32, // velocidad de giro del remolino (rad/s), el signo da el sentido
+}
+
+/// Writes `value` into channel `r|g|b` (or `x|y|z`) of `v`.
+fn set_channel(v: &mut Vector3, channel: &str, value: f32) -> bool {
+    match channel {
+        "r" | "x" => v.x = value,
+        "g" | "y" => v.y = value,
+        "b" | "z" => v.z = value,
+        _ => return false,
+    }
+    true
+}
+
+impl FragmentShader {
+    /// Sets a scalar material parameter by name (`"coverage"`, `"roughness"`, `"color.r"`, …),
+    /// used by the animation tracks. Returns false when the shader has no such parameter.
+    pub fn set_param(&mut self, name: &str, value: f32) -> bool {
+        let (field, channel) = match name.split_once('.') {
+            Some((f, c)) => (f, Some(c)),
+            None => (name, None),
+        };
+        match (self, field, channel) {
+            (FragmentShader::Solid { color } | FragmentShader::Rocky { color }, "color", Some(c)) => set_channel(color, c, value),
+            (FragmentShader::Strips { angle }, "angle", None) => { *angle = value; true },
+            (FragmentShader::Volcanic { flow, .. }, "flow", None) => { *flow = value; true },
+            (FragmentShader::Volcanic { crust, .. }, "crust", Some(c)) => set_channel(crust, c, value),
+            (FragmentShader::Volcanic { lava, .. }, "lava", Some(c)) => set_channel(lava, c, value),
+            (FragmentShader::Icy { crack_density, .. }, "crack_density", None) => { *crack_density = value; true },
+            (FragmentShader::Icy { tint, .. }, "tint", Some(c)) => set_channel(tint, c, value),
+            (FragmentShader::GasGiant { wind, .. }, "wind", None) => { *wind = value; true },
+            (FragmentShader::Earth { cloud_coverage, .. }, "cloud_coverage", None) => { *cloud_coverage = value; true },
+            (FragmentShader::Earth { cloud_spin, .. }, "cloud_spin", None) => { *cloud_spin = value; true },
+            (FragmentShader::Clouds { coverage }, "coverage", None) => { *coverage = value; true },
+            (FragmentShader::Ring { inner, .. }, "inner", None) => { *inner = value; true },
+            (FragmentShader::Ring { outer, .. }, "outer", None) => { *outer = value; true },
+            (FragmentShader::Ring { color, .. }, "color", Some(c)) => set_channel(color, c, value),
+            (FragmentShader::Pbr { material }, "metallic", None) => { material.metallic = value; true },
+            (FragmentShader::Pbr { material }, "specular", None) => { material.specular = value; true },
+            (FragmentShader::Pbr { material }, "roughness", None) => { material.roughness = value; true },
+            (FragmentShader::Pbr { material }, "ao", None) => { material.ao = value; true },
+            (FragmentShader::Pbr { material }, "albedo", Some(c)) => set_channel(&mut material.albedo, c, value),
+            (FragmentShader::Pbr { material }, "emissive", Some(c)) => set_channel(&mut material.emissive, c, value),
+            // Lo horneado es fijo; solo cambian las capas que se siguen evaluando
+            (FragmentShader::Baked { source, .. }, _, _) => source.set_param(name, value),
+            _ => false,
+        }
+    }
+
+    /// True if `fragment_alpha` can return something between 0 and 1: such layers blend over
+    /// what is behind them, so they are drawn after every opaque mesh, back to front.
+    pub fn is_transparent(&self) -> bool {
+        match self {
+            FragmentShader::Clouds { .. } | FragmentShader::Ring { .. } => true,
+            FragmentShader::Pbr { material } => {
+                material.alpha_cutoff.is_none() && (material.opacity < 1.0 || material.albedo_map.is_some())
+            }
+            _ => false,
+        }
+    }
+}
+
+const ROCKY_BUMP: f32 = 0.06;
+const ROCKY_BUMP_STEP: f32 = 0.004; // paso del bump procedural sobre la esfera unidad
+const BAKED_SAMPLER: Sampler = Sampler { filter: Filter::Bilinear, wrap_u: Wrap::Repeat, wrap_v: Wrap::Clamp };
+
 #[inline]
 fn dot3(a: Vector3, b: Vector3) -> f32 { a.x*b.x + a.y*b.y + a.z*b.z }
 
 #[inline]
-fn fract(x: f32) -> f32 { x - x.floor() }
+pub fn fract(x: f32) -> f32 { x - x.floor() }
 
 #[inline]
 fn lerp(a: f32, b: f32, t: f32) -> f32 { a + t * (b - a) }
@@ -38,7 +128,7 @@ fn hash3(p: Vector3) -> f32 {
     fract((n.sin() * 43758.5453).sin() * 143758.5453)
 }
 
-fn value_noise3(mut p: Vector3) -> f32 {
+pub fn value_noise3(mut p: Vector3) -> f32 {
     let i = Vector3::new(p.x.floor(), p.y.floor(), p.z.floor());
     let f = Vector3::new(p.x - i.x, p.y - i.y, p.z - i.z);
 
@@ -64,7 +154,7 @@ fn value_noise3(mut p: Vector3) -> f32 {
     lerp(nxy0, nxy1, u.z)
 }
 
-fn fbm(mut p: Vector3, octaves: i32, lacunarity: f32, gain: f32) -> f32 {
+pub fn fbm(mut p: Vector3, octaves: i32, lacunarity: f32, gain: f32) -> f32 {
     let mut amp = 0.5;
     let mut freq = 1.0;
     let mut sum = 0.0;
@@ -76,7 +166,145 @@ fn fbm(mut p: Vector3, octaves: i32, lacunarity: f32, gain: f32) -> f32 {
     sum
 }
 
-fn temperature_to_rgb(t: f32) -> Vector3 {
+#[inline]
+fn smoothstep(e0: f32, e1: f32, x: f32) -> f32 {
+    let t = ((x - e0) / (e1 - e0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[inline]
+fn hash33(p: Vector3) -> Vector3 {
+    Vector3::new(
+        hash3(p),
+        hash3(p + Vector3::new(19.19, 7.13, 3.71)),
+        hash3(p + Vector3::new(5.37, 41.3, 13.9)),
+    )
+}
+
+/// Worley (cellular) noise: distances to the nearest and second nearest feature point (F1, F2).
+pub fn worley3(p: Vector3) -> (f32, f32) {
+    let i = Vector3::new(p.x.floor(), p.y.floor(), p.z.floor());
+    let mut f1 = f32::MAX;
+    let mut f2 = f32::MAX;
+    for z in -1..=1 {
+        for y in -1..=1 {
+            for x in -1..=1 {
+                let cell = i + Vector3::new(x as f32, y as f32, z as f32);
+                let d = cell + hash33(cell) - p;
+                let dist = dot3(d, d);
+                if dist < f1 {
+                    f2 = f1;
+                    f1 = dist;
+                } else if dist < f2 {
+                    f2 = dist;
+                }
+            }
+        }
+    }
+    (f1.sqrt(), f2.sqrt())
+}
+
+/// Ridged FBM: folds each octave around 0.5 so crests become sharp ridges. ~[0,1]
+pub fn ridged_fbm(p: Vector3, octaves: i32, lacunarity: f32, gain: f32) -> f32 {
+    let mut amp = 0.5;
+    let mut freq = 1.0;
+    let mut sum = 0.0;
+    let mut total = 0.0;
+    for _ in 0..octaves {
+        let n = 1.0 - (value_noise3(p * freq) * 2.0 - 1.0).abs();
+        sum += amp * n * n;
+        total += amp;
+        freq *= lacunarity;
+        amp *= gain;
+    }
+    if total > 0.0 { sum / total } else { 0.0 }
+}
+
+/// Long linear cracks: iso-lines of noise stretched along one axis (anisotropic). 1 = crack
+fn crevasses(p: Vector3, density: f32) -> f32 {
+    // Dos familias de grietas: una casi paralela al ecuador y otra girada ~60°
+    let (s, c) = (1.05f32).sin_cos();
+    let q = Vector3::new(c*p.x - s*p.y, s*p.x + c*p.y, p.z);
+
+    let mut cracks = 0.0f32;
+    for (dir, seed) in [(p, 0.0), (q, 17.0)] {
+        let n = value_noise3(Vector3::new(dir.x*1.5 + seed, dir.y*density, dir.z*1.5));
+        let jitter = fbm(dir * (density*0.5) + Vector3::new(seed, 0.0, 0.0), 3, 2.0, 0.5) * 0.08;
+        let line = 1.0 - smoothstep(0.0, 0.025, (n + jitter - 0.5).abs());
+        cracks = cracks.max(line);
+    }
+    cracks
+}
+
+/// Rotates `v` around the unit axis `k` (Rodrigues).
+#[inline]
+fn rotate_axis(v: Vector3, k: Vector3, angle: f32) -> Vector3 {
+    let (s, c) = angle.sin_cos();
+    v * c + k.cross(v) * s + k * (dot3(k, v) * (1.0 - c))
+}
+
+/// Swirling storm on a gas giant: returns (mask, color) for the direction `p`.
+fn storm_sample(p: Vector3, storm: &Storm, time: f32) -> (f32, Vector3) {
+    let (slat, clat) = storm.lat.sin_cos();
+    let center = Vector3::new(clat * storm.lon.cos(), slat, clat * storm.lon.sin());
+    let d = dot3(p, center).clamp(-1.0, 1.0).acos();
+    if d > storm.radius * 1.8 {
+        return (0.0, storm.color);
+    }
+
+    // Marco tangente local en el centro del vórtice (este, norte)
+    let mut east = Vector3::new(0.0, 1.0, 0.0).cross(center);
+    if east.length() < 1e-6 { east = Vector3::new(1.0, 0.0, 0.0); }
+    let east = east.normalized();
+    let north = center.cross(east);
+
+    // Óvalo alargado en longitud, como la Gran Mancha Roja
+    let x = dot3(p, east) / (storm.radius * 1.6);
+    let y = dot3(p, north) / storm.radius;
+    let r = (x*x + y*y).sqrt();
+
+    // Remolino: el ángulo de giro crece hacia el centro (domain warp rotacional)
+    let twist = storm.spin * time + 4.0 * (1.0 - r).max(0.0);
+    let (s, c) = twist.sin_cos();
+    let q = Vector3::new(c*x - s*y, s*x + c*y, 0.0);
+    let warp = fbm(q * 2.0 + Vector3::new(0.0, 0.0, time*0.05), 3, 2.0, 0.5);
+    let swirl = fbm(q * 3.0 + Vector3::new(warp, -warp, 0.0) * 1.5, 4, 2.0, 0.5);
+
+    let mask = 1.0 - smoothstep(0.7, 1.0, r + (swirl - 0.5) * 0.3);
+    let ring = smoothstep(0.55, 0.85, r) * mask; // borde más claro
+    let color = storm.color * (0.65 + 0.6*swirl) + Vector3::new(0.25, 0.2, 0.15) * ring;
+    (mask, color)
+}
+
+/// Cloud density in [0,1] for a direction expressed in the cloud shell's object space.
+/// Shared by `Clouds` (opacity) and `Earth` (cloud shadows) so both see the same clouds.
+fn cloud_density(p: Vector3, coverage: f32, time: f32) -> f32 {
+    let drift = Vector3::new(0.0, time*0.01, 0.0);
+    let warp = fbm(p * 2.0 + drift, 3, 2.0, 0.5);
+    let n = fbm(Vector3::new(p.x*4.0, p.y*7.0, p.z*4.0) + Vector3::new(warp, 0.0, -warp), 5, 2.1, 0.5);
+    smoothstep(1.0 - coverage, 1.0 - coverage + 0.25, n)
+}
+
+/// 1D optical density of a planetary ring, `t` = 0 at the inner edge and 1 at the outer edge.
+fn ring_density(t: f32) -> f32 {
+    if !(0.0..=1.0).contains(&t) {
+        return 0.0;
+    }
+    // Anillos anchos (C, B, A) con densidades distintas
+    let broad = if t < 0.22 { 0.35 } else if t < 0.6 { 0.95 } else { 0.7 };
+    // Ringlets finos: ruido 1D de alta frecuencia
+    let fine = value_noise3(Vector3::new(t * 140.0, 0.5, 0.5)) * 0.5
+        + value_noise3(Vector3::new(t * 37.0, 3.5, 0.5)) * 0.5;
+    let mut d = broad * (0.6 + 0.5*fine);
+    // Divisiones: Cassini (ancha) y Encke (estrecha)
+    d *= smoothstep(0.0, 0.02, (t - 0.63).abs() - 0.03);
+    d *= smoothstep(0.0, 0.004, (t - 0.9).abs() - 0.006);
+    // Bordes suaves
+    d *= smoothstep(0.0, 0.03, t) * smoothstep(1.0, 0.97, t);
+    d.clamp(0.0, 1.0)
+}
+
+pub fn temperature_to_rgb(t: f32) -> Vector3 {
     // t in [0,1]: 0 = red/orange, 1 = white/blue
     // simple 3-point gradient: red -> yellow -> white
     let t = t.clamp(0.0, 1.0);
@@ -91,6 +319,255 @@ fn temperature_to_rgb(t: f32) -> Vector3 {
     }
 }
 
+#[inline]
+fn sphere_dir(p: Vector3) -> Vector3 {
+    let len = (p.x*p.x + p.y*p.y + p.z*p.z).sqrt();
+    if len > 0.0 {
+        Vector3::new(p.x/len, p.y/len, p.z/len) // dirección en la esfera
+    } else {
+        p
+    }
+}
+
+/// Static part of `Rocky`: rock albedo with craters for the sphere direction `p`.
+pub fn rocky_albedo(p: Vector3, color: Vector3) -> Vector3 {
+    rocky_albedo_from(fbm(p * 4.0, 4, 2.0, 0.5), fbm(p * 12.0, 3, 2.4, 0.55), fbm(p * 16.0, 3, 2.2, 0.5), color)
+}
+
+/// `rocky_albedo` from its three noise layers: `fbm` of `p` at 4 (base), 12 and 16 (craters).
+fn rocky_albedo_from(base: f32, base2: f32, crater_noise: f32, color: Vector3) -> Vector3 {
+    // Base de roca: fbm de baja frecuencia (0..~1)
+    let rocky = (base*0.7 + base2*0.3).clamp(0.0, 1.0);
+
+    // Color rocoso (marrón/gris)
+    let albedo = Vector3::new(
+        color.x + 0.25*rocky,
+        color.y + 0.2*rocky,
+        color.z + 0.15*rocky,
+    );
+
+    // Cráteres: patrón de “huecos” oscuros fijos en el objeto
+    // Usamos un ruido de alta frecuencia y lo umbralizamos
+    let mut crater_mask = (crater_noise - 0.55) * 8.0; // valores por debajo generan hoyos
+    crater_mask = crater_mask.clamp(0.0, 1.0);
+    // invertimos: 1 = superficie, 0 = cráter
+    let crater = 1.0 - crater_mask;
+
+    let crater_dark = 0.35; // qué tan oscuros son los cráteres
+    Vector3::new(
+        albedo.x * (crater_dark + (1.0-crater_dark)*crater),
+        albedo.y * (crater_dark + (1.0-crater_dark)*crater),
+        albedo.z * (crater_dark + (1.0-crater_dark)*crater),
+    )
+}
+
+/// Height of the `Rocky` surface: rolling rock, crater bowls and raised rims. ~[0,1]
+pub fn rocky_height(p: Vector3) -> f32 {
+    rocky_height_from(fbm(p * 4.0, 4, 2.0, 0.5), fbm(p * 16.0, 3, 2.2, 0.5))
+}
+
+/// `rocky_height` from the same base and crater layers as `rocky_albedo_from`.
+fn rocky_height_from(base: f32, crater_noise: f32) -> f32 {
+    let bowl = ((crater_noise - 0.55) * 8.0).clamp(0.0, 1.0);
+    let rim = smoothstep(0.47, 0.55, crater_noise) * (1.0 - bowl);
+    (0.5 + 0.3*(base - 0.5) + 0.25*rim - 0.45*bowl).clamp(0.0, 1.0)
+}
+
+/// Tangent frame (t, b) at the sphere normal `n` and the three directions where `bump_normal`
+/// measures the height: `n` itself and one step of `eps` along each tangent.
+fn bump_taps(n: Vector3, eps: f32) -> (Vector3, Vector3, [Vector3; 3]) {
+    let mut t = n.cross(Vector3::new(0.0, 1.0, 0.0));
+    if t.length() < 1e-6 {
+        t = Vector3::new(1.0, 0.0, 0.0);
+    }
+    let t = t.normalized();
+    let b = t.cross(n);
+    (t, b, [n, (n + t * eps).normalized(), (n + b * eps).normalized()])
+}
+
+/// Normal `n` tilted against the height gradient, from the heights `h` at the `bump_taps`.
+fn tilt_normal(n: Vector3, t: Vector3, b: Vector3, h: [f32; 3], strength: f32, eps: f32) -> Vector3 {
+    let dh_t = (h[1] - h[0]) / eps;
+    let dh_b = (h[2] - h[0]) / eps;
+
+    let bumped = n - (t * dh_t + b * dh_b) * strength;
+    if bumped.length() > 0.0 { bumped.normalized() } else { n }
+}
+
+/// Bump mapping by finite differences: tilts the sphere normal `n` against the gradient of
+/// `height` measured along the local tangent frame. `eps` is the step on the unit sphere.
+fn bump_normal(n: Vector3, strength: f32, eps: f32, height: impl Fn(Vector3) -> f32) -> Vector3 {
+    let (t, b, taps) = bump_taps(n, eps);
+    tilt_normal(n, t, b, taps.map(height), strength, eps)
+}
+
+/// Adds the tilt of the interpolated geometric normal (displaced terrain) relative to the
+/// sphere direction `p` to the shading normal `n`. No-op when the fragment carries no normal.
+fn follow_geometry(fragment: &Fragment, p: Vector3, n: Vector3) -> Vector3 {
+    if fragment.normal.length() == 0.0 {
+        return n;
+    }
+    let tilted = n + (fragment.normal - p);
+    if tilted.length() > 0.0 { tilted.normalized() } else { n }
+}
+
+fn shade_rocky(u: &Uniforms, normal: Vector3, color: Vector3) -> Vector3 {
+    // Lambert con la normal perturbada: los bordes de cráter captan la luz del Sol
+    let ndotl = dot3(normal, u.sun_dir).max(0.0);
+    let diffuse = 0.08 + 0.92*ndotl;
+
+    Vector3::new(
+        (color.x * diffuse).clamp(0.0, 1.0),
+        (color.y * diffuse).clamp(0.0, 1.0),
+        (color.z * diffuse).clamp(0.0, 1.0),
+    )
+}
+
+/// Static part of `Strips`: storm spots as (rgb, mask).
+pub fn strips_spots(p: Vector3) -> Vector4 {
+    // Opcional: pequeñas manchas (spots) de tormentas, fijas o casi fijas
+    let spots = fbm(Vector3::new(p.x*20.0, p.y*20.0, p.z*20.0), 3, 2.0, 0.5);
+    let mut spots_mask = (spots - 0.75) * 6.0;
+    spots_mask = spots_mask.clamp(0.0, 1.0);
+    Vector4::new(0.8, 0.4, 0.2, spots_mask)
+}
+
+fn shade_strips(fragment: &Fragment, u: &Uniforms, p: Vector3, spots: Vector4) -> Vector3 {
+    // latitud en [-1,1]
+    let lat = p.y;
+
+    // Distorsión de las bandas por ruido (animado)
+    let t = u.time * 0.15;
+    let warp = fbm(
+        Vector3::new(p.x*6.0, p.y*6.0, p.z*6.0 + t),
+        4,
+        2.1,
+        0.5,
+    );
+    let lat_warped = lat + (warp - 0.5) * 0.25; // distorsión suave
+
+    // Periodicidad de bandas: usamos varias “zonas”
+    // stripe = sin(k * lat_warped) → alterna claro/oscuro
+    let k = 14.0; // número de bandas
+    let stripe_val = (k * lat_warped).sin();
+
+    // Mapear a 0..1 y hacer más duras las franjas
+    let bands = (stripe_val * 1.2).tanh(); // transiciones suavizadas pero no tan lisas
+    let bands01 = (bands * 0.5 + 0.5).clamp(0.0, 1.0);
+
+    // Dos colores base tipo Júpiter
+    let band_light = Vector3::new(0.95, 0.9, 0.78);
+    let band_dark  = Vector3::new(0.82, 0.6, 0.45);
+
+    let mut color = Vector3::new(
+        band_dark.x + (band_light.x - band_dark.x) * bands01,
+        band_dark.y + (band_light.y - band_dark.y) * bands01,
+        band_dark.z + (band_light.z - band_dark.z) * bands01,
+    );
+
+    // Añadir turbulencia en “nubes” usando ruido
+    let clouds = fbm(Vector3::new(p.x*10.0 + t*0.7, p.y*18.0, p.z*10.0 - t*0.5), 5, 2.1, 0.5);
+    let clouds_mask = (clouds - 0.4).max(0.0) * 1.8;
+    let clouds_mask = clouds_mask.clamp(0.0, 1.0);
+
+    let cloud_tint = Vector3::new(1.0, 0.98, 0.95);
+    color = Vector3::new(
+        color.x + (cloud_tint.x - color.x) * clouds_mask,
+        color.y + (cloud_tint.y - color.y) * clouds_mask,
+        color.z + (cloud_tint.z - color.z) * clouds_mask,
+    );
+
+    // Manchas estáticas (posiblemente horneadas)
+    let spots_mask = spots.w;
+    color = Vector3::new(
+        color.x*(1.0-spots_mask) + spots.x*spots_mask,
+        color.y*(1.0-spots_mask) + spots.y*spots_mask,
+        color.z*(1.0-spots_mask) + spots.z*spots_mask,
+    );
+
+    // Simple iluminación desde el sol en el origen
+    let light_dir = Vector3::new(0.0, 0.0, 0.0) - fragment.obj_position;
+    let l_len = (light_dir.x*light_dir.x + light_dir.y*light_dir.y + light_dir.z*light_dir.z).sqrt();
+    let ndotl = if l_len > 0.0 {
+        let l = Vector3::new(light_dir.x/l_len, light_dir.y/l_len, light_dir.z/l_len);
+        let n = p;
+        (n.x*l.x + n.y*l.y + n.z*l.z).max(0.0)
+    } else { 1.0 };
+
+    let diffuse = 0.8 + 0.2*ndotl;
+
+    Vector3::new(
+        (color.x * diffuse).clamp(0.0, 1.0),
+        (color.y * diffuse).clamp(0.0, 1.0),
+        (color.z * diffuse).clamp(0.0, 1.0),
+    )
+}
+
+/// Offset of the noise domain for `seed`, so each planet gets its own terrain.
+fn seed_offset(seed: u32) -> Vector3 {
+    let s = seed as f32;
+    Vector3::new(
+        fract((s * 12.9898).sin() * 4375.85) * 512.0,
+        fract((s * 78.233).sin() * 4375.85) * 512.0,
+        fract((s * 37.719).sin() * 4375.85) * 512.0,
+    )
+}
+
+/// Terrain height for `TerrainDisplace` at sphere direction `dir`, ~[-1, 1]: ridged mountains
+/// plus crater bowls with raised rims, in patches controlled by a low-frequency mask.
+fn terrain_height(dir: Vector3, seed: u32, frequency: f32, octaves: i32, crater_density: f32) -> f32 {
+    let offset = seed_offset(seed);
+
+    // Cordilleras: ridged FBM al cuadrado para picos afilados y llanuras amplias
+    let ridges = ridged_fbm(dir * frequency + offset, octaves, 2.0, 0.5);
+    let mountains = ridges * ridges;
+
+    if crater_density <= 0.0 {
+        return mountains;
+    }
+
+    // Cráteres: distancia a la celda Worley más cercana → cuenco + borde elevado
+    let (f1, _) = worley3(dir * crater_density + offset * 0.5);
+    let d = f1 / 0.45;
+    let bowl = if d < 1.0 { d * d - 1.0 } else { 0.0 };
+    let rim = (-((d - 1.0) / 0.25).powi(2)).exp() * 0.35;
+    let mask = smoothstep(0.4, 0.65, value_noise3(dir * 2.0 + offset * 0.25));
+
+    mountains * (1.0 - 0.5 * mask) + (bowl + rim) * mask
+}
+
+impl VertexShader {
+    /// True if the displacement changes with `time`; otherwise it can be applied to the mesh
+    /// once (`Mesh::displaced`) instead of every frame.
+    pub fn is_animated(&self) -> bool {
+        match self {
+            VertexShader::Identity | VertexShader::Volcanic { .. } | VertexShader::TerrainDisplace { .. } => false,
+            VertexShader::SolarFlare => true,
+            VertexShader::DisplacePlanarY { time_amp, .. } => *time_amp != 0.0,
+            VertexShader::Expr { program } => program.uses_time(),
+        }
+    }
+
+    /// Upper bound on how far (object units) the shader can move a vertex, for growing bounding
+    /// volumes; `None` when it cannot be known (DSL programs).
+    pub fn max_displacement(&self) -> Option<f32> {
+        match self {
+            VertexShader::Identity => Some(0.0),
+            // `value_noise3` está en [0, 1), así que el fbm de 4 octavas en [0, 0.9375] y
+            // `(n*2 - 1) * 0.35` en [-0.35, 0.31]
+            VertexShader::SolarFlare => Some(0.35),
+            // `fbm3` está normalizado a [-1, 1]
+            VertexShader::DisplacePlanarY { amp, .. } => Some(amp.abs()),
+            // `ridged_fbm` está normalizado a [0, 1]
+            VertexShader::Volcanic { amp, .. } => Some(amp.abs()),
+            // `terrain_height`: montañas en [0, 1], cuenco en [-1, 0] y borde ≤ 0.35 mezclados
+            // por una máscara en [0, 1], así que el resultado queda en [-1, 1]
+            VertexShader::TerrainDisplace { amplitude, .. } => Some(amplitude.abs()),
+            VertexShader::Expr { .. } => None,
+        }
+    }
+}
+
 pub fn apply_vertex_shader(v: Vector3, shader: &VertexShader, time: f32) -> Vector3 {
     match shader {
         VertexShader::Identity => v,
@@ -108,6 +585,20 @@ pub fn apply_vertex_shader(v: Vector3, shader: &VertexShader, time: f32) -> Vect
             let h = crate::procedural::fbm3(p, *octaves, *lacunarity, *gain); // ~[-1,1]
             let disp = *amp * h;
             Vector3::new(v.x, v.y + disp, v.z)
+        },
+        VertexShader::Volcanic { amp, freq } => {
+            // Montañas: mismo ridged FBM que la corteza del fragment shader Volcanic
+            let dir = if v.length() > 0.0 { v.normalized() } else { Vector3::new(0.0,0.0,1.0) };
+            let h = ridged_fbm(dir * *freq, 5, 2.0, 0.5);
+            v + dir * (*amp * h)
+        }
+        VertexShader::TerrainDisplace { seed, amplitude, frequency, octaves, crater_density } => {
+            let dir = if v.length() > 0.0 { v.normalized() } else { Vector3::new(0.0,0.0,1.0) };
+            v + dir * (*amplitude * terrain_height(dir, *seed, *frequency, *octaves, *crater_density))
+        }
+        VertexShader::Expr { program } => {
+            // `sun_dir` no existe en la etapa de vértices (el compilador lo rechaza)
+            program.eval(time, v, sphere_dir(v), Vector3::new(0.0, 0.0, 0.0))
         }
     }
 }
@@ -116,35 +607,19 @@ pub fn fragment_shader(fragment: &Fragment, u: &Uniforms, shader: &FragmentShade
     match shader {
         FragmentShader::Star => {
             // Use object-space direction for stable texturing on the sphere surface
-            let mut dir = fragment.obj_position;
-            let len = (dir.x*dir.x + dir.y*dir.y + dir.z*dir.z).sqrt();
-            if len > 0.0 { dir = Vector3::new(dir.x/len, dir.y/len, dir.z/len); }
+            let dir = sphere_dir(fragment.obj_position);
 
             // FBM turbulence driven by object-space, time-cycled
             let tloop = (u.time % 8.0) / 8.0;
             let p3 = Vector3::new(dir.x*3.0, dir.y*3.0, tloop*8.0);
             let turb = fbm(p3, 5, 2.0, 0.55);
 
-            // Core intensity based on how close to the disc center it projects (approx with dir.z)
-            // dir.z ~ facing viewer if camera looks down -Z; use abs to be camera-agnostic
-            let facing = dir.z.abs();
-            let base_core = facing.clamp(0.0, 1.0);
-
-            // User controls: temp in [0,1], intensity scaler ~ [0,2]
-            let intensity = ((base_core * 0.7 + turb * 0.6) * u.intensity).clamp(0.0, 1.0);
-
-            // Temperature affects gradient selection
-            let color_base = temperature_to_rgb(((intensity + u.temp*0.8)*0.7).clamp(0.0,1.0));
+            // Granulación convectiva: celdas Worley que evolucionan lentamente
+            let drift = Vector3::new(u.time*0.05, -u.time*0.03, u.time*0.04);
+            let (f1, f2) = worley3(dir * 18.0 + drift);
 
-            // Emission spikes add energetic flicker
-            let spikes = (value_noise3(Vector3::new(dir.x*10.0 + u.time*1.7, dir.y*10.0 - u.time*1.3, u.time*0.5))*2.0-1.0).abs();
-            let emission = (0.6*intensity + 0.8*spikes).clamp(0.0, 1.5);
-
-            Vector3::new(
-                (color_base.x * emission).clamp(0.0, 1.0),
-                (color_base.y * emission).clamp(0.0, 1.0),
-                (color_base.z * emission).clamp(0.0, 1.0),
-            )
+            let flicker = value_noise3(Vector3::new(dir.x*10.0 + u.time*1.7, dir.y*10.0 - u.time*1.3, u.time*0.5));
+            shade_star(fragment, u, dir, turb, f1, f2, flicker)
         },
         FragmentShader::Solid { color } => {
             let pos = fragment.position;
@@ -164,134 +639,162 @@ pub fn fragment_shader(fragment: &Fragment, u: &Uniforms, shader: &FragmentShade
 
         },
         FragmentShader::Rocky { color } => {
+            let p = sphere_dir(fragment.obj_position);
+            let normal = follow_geometry(fragment, p, bump_normal(p, ROCKY_BUMP, ROCKY_BUMP_STEP, rocky_height));
+            shade_rocky(u, normal, rocky_albedo(p, *color))
+        },
+        FragmentShader::Strips { .. } => {
+            let p = sphere_dir(fragment.obj_position);
+            shade_strips(fragment, u, p, strips_spots(p))
+        },
+        FragmentShader::Baked { source, map } => {
+            // Capa estática desde el mapa horneado; lo animado se sigue evaluando
+            let p = sphere_dir(fragment.obj_position);
+            let baked = map.sample(crate::bake::equirect_uv(p), fragment.uv_lod, &BAKED_SAMPLER);
+            match source.as_ref() {
+                FragmentShader::Rocky { .. } => {
+                    // La altura va horneada en el canal alfa; paso de ~1 texel del mapa
+                    let eps = std::f32::consts::TAU / map.width() as f32;
+                    let normal = follow_geometry(fragment, p, bump_normal(p, ROCKY_BUMP, eps, |q| {
+                        map.sample(crate::bake::equirect_uv(q), fragment.uv_lod, &BAKED_SAMPLER).w
+                    }));
+                    shade_rocky(u, normal, Vector3::new(baked.x, baked.y, baked.z))
+                },
+                FragmentShader::Strips { .. } => shade_strips(fragment, u, p, baked),
+                other => fragment_shader(fragment, u, other),
+            }
+        },
+        FragmentShader::Volcanic { crust, lava, flow } => {
             let mut p = fragment.obj_position;
             let len = (p.x*p.x + p.y*p.y + p.z*p.z).sqrt();
             if len > 0.0 {
-                p = Vector3::new(p.x/len, p.y/len, p.z/len); // dirección en la esfera
+                p = Vector3::new(p.x/len, p.y/len, p.z/len);
             }
 
-            // Base de roca: fbm de baja frecuencia
-            let base = fbm(p * 4.0, 4, 2.0, 0.5);  // 0..~1
-            let base2 = fbm(p * 12.0, 3, 2.4, 0.55);
-            let rocky = (base*0.7 + base2*0.3).clamp(0.0, 1.0);
-
-            // Color rocoso (marrón/gris)
-            let albedo = Vector3::new(
-              //  0.25 + 0.25*rocky, // R
-                //0.2  + 0.2*rocky,  // G
-                //0.18 + 0.15*rocky, // B
-                color.x + 0.25*rocky,
-                color.y +0.2*rocky,
-                color.z + 0.15*rocky,
-            );
+            // Corteza de basalto: ridged noise (crestas claras, valles oscuros)
+            let ridges = ridged_fbm(p * 3.0, 5, 2.0, 0.5);
+            let grain = fbm(p * 24.0, 2, 2.0, 0.5);
+            let basalt = *crust * (0.45 + 0.55*ridges + 0.15*grain);
 
-            // Cráteres: patrón de “huecos” oscuros fijos en el objeto
-            // Usamos un ruido de alta frecuencia y lo umbralizamos
-            let crater_noise = fbm(p * 16.0, 3, 2.2, 0.5);
-            let mut crater_mask = (crater_noise - 0.55) * 8.0; // valores por debajo generan hoyos
-            crater_mask = crater_mask.clamp(0.0, 1.0);
-            // invertimos: 1 = superficie, 0 = cráter
-            let crater = 1.0 - crater_mask;
-
-            let crater_dark = 0.35; // qué tan oscuros son los cráteres
-            let color = Vector3::new(
-                albedo.x * (crater_dark + (1.0-crater_dark)*crater),
-                albedo.y * (crater_dark + (1.0-crater_dark)*crater),
-                albedo.z * (crater_dark + (1.0-crater_dark)*crater),
+            // Ríos de lava: bordes de celdas Worley, deformados y animados con el tiempo
+            let t = u.time * *flow;
+            let warp = fbm(p * 2.5 + Vector3::new(t*0.3, 0.0, -t*0.2), 3, 2.0, 0.5);
+            let (f1, f2) = worley3(p * 5.0 + Vector3::new(warp, warp*0.7, -warp) * 1.5);
+            let edge = f2 - f1;
+            let rivers = 1.0 - smoothstep(0.0, 0.12, edge);
+            // La lava corre por los valles, no por las crestas
+            let rivers = (rivers * (1.2 - ridges)).clamp(0.0, 1.0);
+
+            // Pulso de calor que recorre los ríos
+            let pulse = value_noise3(p * 8.0 + Vector3::new(0.0, -t*1.5, t)) * 0.6 + 0.7;
+            let heat = (rivers * pulse).clamp(0.0, 1.0);
+            // Núcleo más amarillo donde la lava está más caliente
+            let glow = Vector3::new(
+                lava.x,
+                lava.y + (1.0 - lava.y) * heat * heat * 0.6,
+                lava.z + (1.0 - lava.z) * heat * heat * 0.2,
             );
 
-            // Un toquecito de iluminación básica tipo lambert con el Sol en el origen:
-            let light_dir = Vector3::new(0.0, 0.0, 0.0) - fragment.obj_position;
-            let l_len = (light_dir.x*light_dir.x + light_dir.y*light_dir.y + light_dir.z*light_dir.z).sqrt();
-            let ndotl = if l_len > 0.0 {
-                let l = Vector3::new(light_dir.x/l_len, light_dir.y/l_len, light_dir.z/l_len);
-                let n = p;
-                (n.x*l.x + n.y*l.y + n.z*l.z).max(0.0)
-            } else {
-                1.0
-            };
+            // Lambert con el Sol solo para la corteza, con relieve de las crestas
+            let normal = bump_normal(p, 0.08, 0.004, |q| ridged_fbm(q * 3.0, 5, 2.0, 0.5));
+            let ndotl = dot3(normal, u.sun_dir).max(0.0);
+            let diffuse = 0.08 + 0.92*ndotl;
+            let lit = basalt * (diffuse * (1.0 - rivers));
 
-            let diffuse = 0.65 + 0.35*ndotl;
+            // Emisión: no depende de ndotl, así que brilla también en el lado nocturno
+            let emission = glow * (heat * 1.4);
 
             Vector3::new(
-                (color.x * diffuse).clamp(0.0, 1.0),
-                (color.y * diffuse).clamp(0.0, 1.0),
-                (color.z * diffuse).clamp(0.0, 1.0),
+                (lit.x + emission.x).clamp(0.0, 1.0),
+                (lit.y + emission.y).clamp(0.0, 1.0),
+                (lit.z + emission.z).clamp(0.0, 1.0),
             )
         },
-        FragmentShader::Strips { angle } => {
+        FragmentShader::Icy { tint, crack_density } => {
             let mut p = fragment.obj_position;
             let len = (p.x*p.x + p.y*p.y + p.z*p.z).sqrt();
             if len > 0.0 {
                 p = Vector3::new(p.x/len, p.y/len, p.z/len);
             }
 
-            // latitud en [-1,1]
-            let lat = p.y;
+            // Albedo alto con leves variaciones de escarcha
+            let frost = fbm(p * 6.0, 4, 2.0, 0.5);
+            let albedo = Vector3::new(0.88, 0.92, 0.95) * (0.9 + 0.15*frost);
 
-            // Distorsión de las bandas por ruido (animado)
-            let t = u.time * 0.15;
-            let warp = fbm(
-                Vector3::new(p.x*6.0, p.y*6.0, p.z*6.0 + t),
-                4,
-                2.1,
-                0.5,
-            );
-            let lat_warped = lat + (warp - 0.5) * 0.25; // distorsión suave
+            // Grietas largas y lineales; dentro de ellas el hielo se ve más profundo (más tinte)
+            let cracks = crevasses(p, *crack_density);
+            let crack_color = Vector3::new(tint.x*0.55, tint.y*0.5, tint.z*0.6);
 
-            // Periodicidad de bandas: usamos varias “zonas”
-            // stripe = sin(k * lat_warped) → alterna claro/oscuro
-            let k = 14.0; // número de bandas
-            let stripe_val = (k * lat_warped).sin();
+            // Wrap lighting: la luz "se envuelve" más allá del terminador, como dispersión subsuperficial
+            let ndotl = dot3(p, u.sun_dir);
+            let wrap = 0.5;
+            let lambert = ndotl.max(0.0);
+            let wrapped = ((ndotl + wrap) / (1.0 + wrap)).max(0.0);
+            let subsurface = (wrapped - lambert).max(0.0);
 
-            // Mapear a 0..1 y hacer más duras las franjas
-            let bands = (stripe_val * 1.2).tanh(); // transiciones suavizadas pero no tan lisas
-            let bands01 = (bands * 0.5 + 0.5).clamp(0.0, 1.0);
+            let surface = albedo * (1.0 - cracks) + crack_color * cracks;
+            let mut color = surface * (0.05 + 0.85*lambert) + *tint * (subsurface * 0.6);
 
-            // Dos colores base tipo Júpiter
-            let band_light = Vector3::new(0.95, 0.9, 0.78);
-            let band_dark  = Vector3::new(0.82, 0.6, 0.45);
+            // Borde Fresnel (Schlick) hacia la cámara
+            let to_cam = u.camera_pos - fragment.obj_position;
+            let c_len = to_cam.length();
+            if c_len > 0.0 {
+                let v = to_cam / c_len;
+                let f0 = 0.02;
+                let fresnel = f0 + (1.0 - f0) * (1.0 - dot3(p, v).max(0.0)).powi(5);
+                color += Vector3::new(0.75, 0.88, 1.0) * (fresnel * (0.25 + 0.75*wrapped));
+            }
 
-            let mut color = Vector3::new(
-                band_dark.x + (band_light.x - band_dark.x) * bands01,
-                band_dark.y + (band_light.y - band_dark.y) * bands01,
-                band_dark.z + (band_light.z - band_dark.z) * bands01,
-            );
+            Vector3::new(
+                color.x.clamp(0.0, 1.0),
+                color.y.clamp(0.0, 1.0),
+                color.z.clamp(0.0, 1.0),
+            )
+        },
+        FragmentShader::GasGiant { palette, bands, wind, storms } => {
+            let mut p = fragment.obj_position;
+            let len = (p.x*p.x + p.y*p.y + p.z*p.z).sqrt();
+            if len > 0.0 {
+                p = Vector3::new(p.x/len, p.y/len, p.z/len);
+            }
 
-            // Añadir turbulencia en “nubes” usando ruido
-            let clouds = fbm(Vector3::new(p.x*10.0 + t*0.7, p.y*18.0, p.z*10.0 - t*0.5), 5, 2.1, 0.5);
-            let clouds_mask = (clouds - 0.4).max(0.0) * 1.8;
-            let clouds_mask = clouds_mask.clamp(0.0, 1.0);
+            let lat = p.y.clamp(-1.0, 1.0).asin();
+            let n_bands = (*bands).max(1) as f32;
 
-            let cloud_tint = Vector3::new(1.0, 0.98, 0.95);
-            color = Vector3::new(
-                color.x + (cloud_tint.x - color.x) * clouds_mask,
-                color.y + (cloud_tint.y - color.y) * clouds_mask,
-                color.z + (cloud_tint.z - color.z) * clouds_mask,
-            );
+            // Coordenada de banda (0..bands) con bordes ondulados
+            let edge_warp = fbm(p * 5.0, 3, 2.0, 0.5) - 0.5;
+            let b = (lat / PI + 0.5) * n_bands + edge_warp * 0.6;
+            let k = b.floor();
+            let frac = b - k;
 
-            // Opcional: pequeñas manchas (spots) de tormentas, fijas o casi fijas
-            let spots = fbm(Vector3::new(p.x*20.0, p.y*20.0, p.z*20.0), 3, 2.0, 0.5);
-            let mut spots_mask = (spots - 0.75) * 6.0;
-            spots_mask = spots_mask.clamp(0.0, 1.0);
-            let spot_color = Vector3::new(0.8, 0.4, 0.2);
+            // Viento zonal por banda: alterna sentido y se debilita hacia los polos
+            let band_rand = hash3(Vector3::new(k, 3.7, 1.3));
+            let dir_sign = if (k as i32) % 2 == 0 { 1.0 } else { -0.7 };
+            let zonal = *wind * dir_sign * (0.5 + band_rand) * lat.cos();
+            let q = rotate_axis(p, Vector3::new(0.0, 1.0, 0.0), zonal * u.time);
 
-            color = Vector3::new(
-                color.x*(1.0-spots_mask) + spot_color.x*spots_mask,
-                color.y*(1.0-spots_mask) + spot_color.y*spots_mask,
-                color.z*(1.0-spots_mask) + spot_color.z*spots_mask,
-            );
+            // Paleta: cada banda toma un color y se mezcla suavemente con la siguiente
+            let count = palette.len().max(1);
+            let idx = (k.max(0.0) as usize) % count;
+            let c0 = palette.get(idx).copied().unwrap_or(Vector3::new(0.8, 0.7, 0.5));
+            let c1 = palette.get((idx + 1) % count).copied().unwrap_or(c0);
+            let blend = smoothstep(0.8, 1.0, frac);
+            let mut color = c0 + (c1 - c0) * blend;
 
-            // Simple iluminación desde el sol en el origen
-            let light_dir = Vector3::new(0.0, 0.0, 0.0) - fragment.obj_position;
-            let l_len = (light_dir.x*light_dir.x + light_dir.y*light_dir.y + light_dir.z*light_dir.z).sqrt();
-            let ndotl = if l_len > 0.0 {
-                let l = Vector3::new(light_dir.x/l_len, light_dir.y/l_len, light_dir.z/l_len);
-                let n = p;
-                (n.x*l.x + n.y*l.y + n.z*l.z).max(0.0)
-            } else { 1.0 };
+            // Turbulencia que viaja con el viento de su banda
+            let turb = fbm(Vector3::new(q.x*8.0, q.y*20.0, q.z*8.0), 5, 2.1, 0.5);
+            color *= 0.8 + 0.4*turb;
+            let clouds_mask = ((turb - 0.55) * 3.0).clamp(0.0, 1.0);
+            color += (Vector3::new(1.0, 0.98, 0.95) - color) * (clouds_mask * 0.5);
+
+            // Vórtices fijos en latitud/longitud del planeta
+            for storm in storms {
+                let (mask, storm_color) = storm_sample(p, storm, u.time);
+                color = color * (1.0 - mask) + storm_color * mask;
+            }
 
-            let diffuse = 0.8 + 0.2*ndotl;
+            let ndotl = dot3(p, u.sun_dir).max(0.0);
+            let diffuse = 0.12 + 0.88*ndotl;
 
             Vector3::new(
                 (color.x * diffuse).clamp(0.0, 1.0),
@@ -299,19 +802,439 @@ pub fn fragment_shader(fragment: &Fragment, u: &Uniforms, shader: &FragmentShade
                 (color.z * diffuse).clamp(0.0, 1.0),
             )
         },
-        FragmentShader::AlienShip => {
+        FragmentShader::Earth { ocean, land, cloud_coverage, cloud_spin } => {
             let mut p = fragment.obj_position;
             let len = (p.x*p.x + p.y*p.y + p.z*p.z).sqrt();
             if len > 0.0 {
                 p = Vector3::new(p.x/len, p.y/len, p.z/len);
             }
-            // latitud en [-1,1]
-            let lat = p.y;
-            if lat >= -0.5 && lat <= 0.27 || lat >= 0.43{
-                Vector3::new(0.7, 0.7, 0.7)
+
+            // Continentes: FBM umbralizado; hielo en los polos
+            let height = fbm(p * 2.2, 5, 2.0, 0.5);
+            let is_land = smoothstep(0.5, 0.53, height);
+            let relief = fbm(p * 10.0, 3, 2.0, 0.5);
+            let land_col = *land * (0.75 + 0.5*relief);
+            let ocean_col = *ocean * (0.7 + 0.6*height);
+            let mut albedo = ocean_col + (land_col - ocean_col) * is_land;
+            let ice = smoothstep(0.82, 0.9, p.y.abs() + relief*0.08);
+            albedo = albedo + (Vector3::new(0.95, 0.97, 1.0) - albedo) * ice;
+
+            // Sombra de nubes: la capa gira aparte (cloud_spin relativo a la superficie),
+            // así que llevamos el punto, desplazado hacia el Sol, al espacio de las nubes
+            let toward_sun = (p + u.sun_dir * 0.05).normalized();
+            let cloud_p = rotate_axis(toward_sun, Vector3::new(0.0, 1.0, 0.0), -*cloud_spin * u.time);
+            let shadow = 1.0 - 0.55 * cloud_density(cloud_p, *cloud_coverage, u.time);
+
+            let ndotl = dot3(p, u.sun_dir);
+            let diffuse = 0.04 + 0.96*ndotl.max(0.0) * shadow;
+            let lit = albedo * diffuse;
+
+            // Luces de ciudades: solo en tierra firme y del lado nocturno del terminador
+            let population = fbm(p * 6.0 + Vector3::new(7.0, 0.0, 0.0), 3, 2.0, 0.5);
+            let clusters = value_noise3(p * 60.0);
+            let cities = smoothstep(0.55, 0.75, clusters) * smoothstep(0.45, 0.65, population) * is_land * (1.0 - ice);
+            let night = smoothstep(0.05, -0.2, ndotl);
+            let emission = Vector3::new(1.0, 0.78, 0.42) * (cities * night * 1.2);
+
+            Vector3::new(
+                (lit.x + emission.x).clamp(0.0, 1.0),
+                (lit.y + emission.y).clamp(0.0, 1.0),
+                (lit.z + emission.z).clamp(0.0, 1.0),
+            )
+        },
+        FragmentShader::Clouds { .. } => {
+            // El color solo depende de la luz; la densidad va en fragment_alpha
+            let mut p = fragment.obj_position;
+            let len = (p.x*p.x + p.y*p.y + p.z*p.z).sqrt();
+            if len > 0.0 {
+                p = Vector3::new(p.x/len, p.y/len, p.z/len);
+            }
+            let ndotl = dot3(p, u.sun_dir);
+            let wrapped = ((ndotl + 0.2) / 1.2).clamp(0.0, 1.0);
+            let c = 0.03 + 0.97*wrapped;
+            Vector3::new(c, c, c * 1.02)
+        },
+        FragmentShader::Ring { inner, outer, color, planet_radius } => {
+            let p = fragment.obj_position;
+            let r = (p.x*p.x + p.z*p.z).sqrt();
+            let t = (r - *inner) / (*outer - *inner).max(1e-6);
+            let density = ring_density(t);
+
+            // Variación de color radial
+            let tint = value_noise3(Vector3::new(t * 23.0, 9.0, 0.0));
+            let albedo = *color * (0.75 + 0.35*tint);
+
+            let l = u.sun_dir;
+            let to_cam = u.camera_pos - p;
+            let v = if to_cam.length() > 0.0 { to_cam.normalized() } else { Vector3::new(0.0, 1.0, 0.0) };
+
+            // Cara iluminada si cámara y Sol están del mismo lado del plano del anillo (normal ±Y)
+            let lit_side = l.y * v.y >= 0.0;
+            let reflect = l.y.abs().sqrt();
+            // Dispersión hacia delante (Henyey-Greenstein) cuando se mira contra el Sol
+            let g: f32 = 0.6;
+            let cos_t = dot3(-v, l);
+            let hg = (1.0 - g*g) / (1.0 + g*g - 2.0*g*cos_t).max(1e-4).powf(1.5) / (4.0 * PI);
+            let scatter = hg * 4.0 * (1.0 - density) * density;
+            let light = if lit_side {
+                0.1 + 0.9*reflect + scatter
+            } else {
+                // Cara en sombra: solo llega lo que atraviesa las partículas
+                0.05 + reflect * (1.0 - density) * 0.6 + scatter * 2.0
+            };
+
+            // Sombra del planeta: el rayo hacia el Sol pasa cerca del centro
+            let b = dot3(p, l);
+            let shadow = if b < 0.0 {
+                let closest = (dot3(p, p) - b*b).max(0.0).sqrt();
+                0.15 + 0.85 * smoothstep(*planet_radius * 0.95, *planet_radius * 1.05, closest)
             } else {
-                Vector3::new(0.0, 1.0, 0.0)
+                1.0
+            };
+
+            let c = albedo * (light * shadow);
+            Vector3::new(
+                c.x.clamp(0.0, 1.0),
+                c.y.clamp(0.0, 1.0),
+                c.z.clamp(0.0, 1.0),
+            )
+        },
+        FragmentShader::Textured { texture, normal_map, sampler } => {
+            let albedo = texture.sample(fragment.uv, fragment.uv_lod, sampler);
+
+            let mut n = fragment.obj_position;
+            if n.length() > 0.0 {
+                n = n.normalized();
+            }
+            if let Some(nm) = normal_map {
+                // Normal map en espacio tangente (convención OpenGL: verde = +V hacia arriba/norte).
+                // Base TBN de la esfera UV: T sigue a u (este), el "arriba" de la textura apunta al norte.
+                let mut t = n.cross(Vector3::new(0.0, 1.0, 0.0));
+                if t.length() < 1e-6 {
+                    t = Vector3::new(1.0, 0.0, 0.0);
+                }
+                let t = t.normalized();
+                let north = t.cross(n);
+                let texel = nm.sample(fragment.uv, fragment.uv_lod, sampler);
+                let tn = Vector3::new(texel.x * 2.0 - 1.0, texel.y * 2.0 - 1.0, texel.z * 2.0 - 1.0);
+                let perturbed = t * tn.x + north * tn.y + n * tn.z;
+                if perturbed.length() > 0.0 {
+                    n = perturbed.normalized();
+                }
+            }
+            let ndotl = dot3(n, u.sun_dir).max(0.0);
+            let diffuse = 0.06 + 0.94*ndotl;
+
+            Vector3::new(
+                (albedo.x * diffuse).clamp(0.0, 1.0),
+                (albedo.y * diffuse).clamp(0.0, 1.0),
+                (albedo.z * diffuse).clamp(0.0, 1.0),
+            )
+        },
+        FragmentShader::Pbr { material } => shade_pbr(fragment, u, material),
+        FragmentShader::Expr { program } => {
+            let normal = if fragment.normal.length() > 0.0 { fragment.normal } else { sphere_dir(fragment.obj_position) };
+            let c = program.eval(u.time, fragment.obj_position, normal, u.sun_dir);
+            Vector3::new(c.x.clamp(0.0, 1.0), c.y.clamp(0.0, 1.0), c.z.clamp(0.0, 1.0))
+        },
+    }
+}
+
+/// Star color from its noise layers: turbulence, Worley granulation (F1, F2) and flicker noise.
+fn shade_star(fragment: &Fragment, u: &Uniforms, dir: Vector3, turb: f32, f1: f32, f2: f32, flicker: f32) -> Vector3 {
+    // Oscurecimiento al limbo: mu = cos del ángulo entre la normal y la dirección a la cámara
+    let to_cam = u.camera_pos - fragment.obj_position;
+    let mu = if to_cam.length() > 0.0 { dot3(dir, to_cam.normalized()).clamp(0.0, 1.0) } else { 1.0 };
+    let k = 1.0 - mu;
+    let limb = (1.0 - 0.55*k - 0.25*k*k).clamp(0.0, 1.0); // ley cuadrática
+
+    let lanes = smoothstep(0.0, 0.18, f2 - f1); // 0 en los bordes oscuros entre gránulos
+    let granules = 0.75 + 0.25*lanes*(1.0 - f1*0.5);
+
+    // User controls: temp in [0,1], intensity scaler ~ [0,2]
+    let intensity = ((limb * 0.7 * granules + turb * 0.6) * u.param("intensity", 0.5)).clamp(0.0, 1.0);
+
+    // Temperature affects gradient selection; el limbo se ve más frío (más rojo)
+    let color_base = temperature_to_rgb(((intensity + u.param("temp", 0.1)*0.8)*0.7 * (0.75 + 0.25*limb)).clamp(0.0,1.0));
+
+    // Emission spikes add energetic flicker
+    let spikes = (flicker*2.0-1.0).abs();
+    let emission = ((0.6*intensity + 0.8*spikes*granules) * (0.35 + 0.65*limb)).clamp(0.0, 1.5);
+
+    Vector3::new(
+        (color_base.x * emission).clamp(0.0, 1.0),
+        (color_base.y * emission).clamp(0.0, 1.0),
+        (color_base.z * emission).clamp(0.0, 1.0),
+    )
+}
+
+/// Cook-Torrance (GGX + Smith-Schlick + Schlick Fresnel) over every scene light, with the
+/// material's maps sampled at the fragment UV.
+fn shade_pbr(fragment: &Fragment, u: &Uniforms, m: &Material) -> Vector3 {
+    let sampler = Sampler { filter: Filter::Trilinear, wrap_u: Wrap::Repeat, wrap_v: Wrap::Repeat };
+    let sample = |map: &Option<Arc<Texture>>| map.as_ref().map(|t| t.sample(fragment.uv, fragment.uv_lod, &sampler));
+
+    let mut albedo = m.albedo;
+    if let Some(t) = sample(&m.albedo_map) {
+        albedo = Vector3::new(albedo.x * t.x, albedo.y * t.y, albedo.z * t.z);
+    }
+    let roughness = sample(&m.roughness_map).map_or(m.roughness, |t| m.roughness * t.y).clamp(0.04, 1.0);
+    let metallic = sample(&m.metallic_map).map_or(m.metallic, |t| m.metallic * t.z).clamp(0.0, 1.0);
+    let ao = sample(&m.ao_map).map_or(m.ao, |t| m.ao * t.x);
+    let mut emissive = m.emissive;
+    if let Some(t) = sample(&m.emissive_map) {
+        emissive = Vector3::new(emissive.x * t.x, emissive.y * t.y, emissive.z * t.z);
+    }
+
+    let v = u.camera_pos - fragment.obj_position;
+    let v = if v.length() > 0.0 { v.normalized() } else { Vector3::new(0.0, 0.0, 1.0) };
+    let mut n = fragment.normal;
+    if n.length() == 0.0 {
+        n = v;
+    }
+    // Las mallas importadas no garantizan el sentido de giro: se sombrea por ambas caras
+    if dot3(n, v) < 0.0 {
+        n = -n;
+    }
+    let ndotv = dot3(n, v).max(1e-4);
+
+    let f0 = Vector3::new(
+        lerp(m.specular, albedo.x, metallic),
+        lerp(m.specular, albedo.y, metallic),
+        lerp(m.specular, albedo.z, metallic),
+    );
+    let a = roughness * roughness;
+    let a2 = a * a;
+    let k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+
+    let mut color = Vector3::new(0.0, 0.0, 0.0);
+    for light in &u.lights {
+        let l = light.dir;
+        let ndotl = dot3(n, l);
+        if ndotl <= 0.0 {
+            continue;
+        }
+        let h = (v + l).normalized();
+        let ndoth = dot3(n, h).max(0.0);
+        let vdoth = dot3(v, h).max(0.0);
+
+        let d = a2 / (PI * ((ndoth * ndoth) * (a2 - 1.0) + 1.0).powi(2)).max(1e-6);
+        let g = (ndotv / (ndotv * (1.0 - k) + k)) * (ndotl / (ndotl * (1.0 - k) + k));
+        let fw = (1.0 - vdoth).powi(5);
+        let f = Vector3::new(f0.x + (1.0 - f0.x) * fw, f0.y + (1.0 - f0.y) * fw, f0.z + (1.0 - f0.z) * fw);
+
+        let spec = d * g / (4.0 * ndotv * ndotl).max(1e-4);
+        let kd = (1.0 - metallic) / PI;
+        let brdf = Vector3::new(
+            (1.0 - f.x) * kd * albedo.x + f.x * spec,
+            (1.0 - f.y) * kd * albedo.y + f.y * spec,
+            (1.0 - f.z) * kd * albedo.z + f.z * spec,
+        );
+        // La radiancia se multiplica por π para que una luz blanca de intensidad 1 dé albedo pleno
+        let r = light.radiance * (PI * ndotl);
+        color += Vector3::new(brdf.x * r.x, brdf.y * r.y, brdf.z * r.z);
+    }
+
+    let ambient = 0.04 * ao;
+    color += albedo * ambient + emissive;
+    Vector3::new(color.x.clamp(0.0, 1.0), color.y.clamp(0.0, 1.0), color.z.clamp(0.0, 1.0))
+}
+
+/// Shades `fragments` in packets of `LANES` (SoA) and appends one color per fragment to `out`,
+/// in order. `Star`, `Rocky` and baked `Rocky` run vectorized kernels; every other shader falls
+/// back to `fragment_shader`. `Star` and `Rocky` match `fragment_shader` exactly; baked `Rocky`
+/// looks its map up through the approximate `simd::atan2`/`acos`, a few 1e-7 off in UV.
+pub fn fragment_shader_batch(fragments: &[Fragment], u: &Uniforms, shader: &FragmentShader, out: &mut Vec<Vector3>) {
+    out.reserve(fragments.len());
+    match shader {
+        FragmentShader::Star => for_each_packet(fragments, out, |packet| star_packet(packet, u)),
+        FragmentShader::Rocky { color } => for_each_packet(fragments, out, |packet| rocky_packet(packet, u, *color)),
+        FragmentShader::Baked { source, map } if matches!(source.as_ref(), FragmentShader::Rocky { .. }) => {
+            for_each_packet(fragments, out, |packet| baked_rocky_packet(packet, u, map))
+        }
+        _ => out.extend(fragments.iter().map(|f| fragment_shader(f, u, shader))),
+    }
+}
+
+fn for_each_packet(fragments: &[Fragment], out: &mut Vec<Vector3>, kernel: impl Fn([&Fragment; LANES]) -> [Vector3; LANES]) {
+    for chunk in fragments.chunks(LANES) {
+        // El último paquete se completa repitiendo su último fragmento
+        let packet = std::array::from_fn(|i| &chunk[i.min(chunk.len() - 1)]);
+        out.extend_from_slice(&kernel(packet)[..chunk.len()]);
+    }
+}
+
+fn star_packet(packet: [&Fragment; LANES], u: &Uniforms) -> [Vector3; LANES] {
+    let dirs = packet.map(|f| sphere_dir(f.obj_position));
+    let d = Vec3x4::from_lanes(dirs);
+
+    let tloop = (u.time % 8.0) / 8.0;
+    let turb = simd::fbm(Vec3x4::new(d.x * 3.0, d.y * 3.0, F32x4::splat(tloop * 8.0)), 5, 2.0, 0.55);
+
+    let drift = Vector3::new(u.time*0.05, -u.time*0.03, u.time*0.04);
+    let (f1, f2) = simd::worley3((d * 18.0).offset(drift));
+
+    let flicker = simd::value_noise3(Vec3x4::new(
+        d.x * 10.0 + u.time*1.7,
+        d.y * 10.0 - u.time*1.3,
+        F32x4::splat(u.time*0.5),
+    ));
+
+    std::array::from_fn(|i| shade_star(packet[i], u, dirs[i], turb.0[i], f1.0[i], f2.0[i], flicker.0[i]))
+}
+
+/// `Rocky` for 4 fragments. The tangent frames are built per lane and the noise runs as packets;
+/// base and crater noise at the fragment serve both the albedo and the centre height, so each
+/// lane costs 7 fbm evaluations instead of the scalar 9.
+fn rocky_packet(packet: [&Fragment; LANES], u: &Uniforms, color: Vector3) -> [Vector3; LANES] {
+    let dirs = packet.map(|f| sphere_dir(f.obj_position));
+    let frames = dirs.map(|p| bump_taps(p, ROCKY_BUMP_STEP));
+    let tap = |k: usize| Vec3x4::from_lanes(std::array::from_fn(|i| frames[i].2[k]));
+
+    let base = |p: Vec3x4| simd::fbm(p * 4.0, 4, 2.0, 0.5);
+    let crater = |p: Vec3x4| simd::fbm(p * 16.0, 3, 2.2, 0.5);
+    let p = tap(0);
+    let (base0, crater0) = (base(p), crater(p));
+    let base2 = simd::fbm(p * 12.0, 3, 2.4, 0.55);
+    let (t, b) = (tap(1), tap(2));
+    let (base_t, crater_t, base_b, crater_b) = (base(t), crater(t), base(b), crater(b));
+
+    std::array::from_fn(|i| {
+        let h = [
+            rocky_height_from(base0.0[i], crater0.0[i]),
+            rocky_height_from(base_t.0[i], crater_t.0[i]),
+            rocky_height_from(base_b.0[i], crater_b.0[i]),
+        ];
+        let (tangent, bitangent, _) = frames[i];
+        let normal = follow_geometry(packet[i], dirs[i], tilt_normal(dirs[i], tangent, bitangent, h, ROCKY_BUMP, ROCKY_BUMP_STEP));
+        shade_rocky(u, normal, rocky_albedo_from(base0.0[i], base2.0[i], crater0.0[i], color))
+    })
+}
+
+/// Baked `Rocky` for 4 fragments: the three equirectangular lookups of each lane (albedo and
+/// height at the fragment, heights one texel along each tangent) get their UVs as packets; the
+/// centre texel is sampled once for both albedo and height.
+fn baked_rocky_packet(packet: [&Fragment; LANES], u: &Uniforms, map: &Texture) -> [Vector3; LANES] {
+    let eps = std::f32::consts::TAU / map.width() as f32;
+    let dirs = packet.map(|f| sphere_dir(f.obj_position));
+    let frames = dirs.map(|p| bump_taps(p, eps));
+    let uv_lod = packet.map(|f| f.uv_lod);
+    let texels: [[Vector4; LANES]; 3] = std::array::from_fn(|k| {
+        let (u, v) = crate::bake::equirect_uv_x4(Vec3x4::from_lanes(std::array::from_fn(|i| frames[i].2[k])));
+        map.sample_bilinear_x4(u, v, uv_lod, &BAKED_SAMPLER)
+    });
+
+    std::array::from_fn(|i| {
+        let centre = texels[0][i];
+        let h = [centre.w, texels[1][i].w, texels[2][i].w];
+        let (tangent, bitangent, _) = frames[i];
+        let normal = follow_geometry(packet[i], dirs[i], tilt_normal(dirs[i], tangent, bitangent, h, ROCKY_BUMP, eps));
+        shade_rocky(u, normal, Vector3::new(centre.x, centre.y, centre.z))
+    })
+}
+
+/// Opacity of a fragment in [0,1]. Every shader is opaque except clouds and rings.
+pub fn fragment_alpha(fragment: &Fragment, u: &Uniforms, shader: &FragmentShader) -> f32 {
+    match shader {
+        FragmentShader::Clouds { coverage } => {
+            let p = fragment.obj_position;
+            let p = if p.length() > 0.0 { p.normalized() } else { p };
+            cloud_density(p, *coverage, u.time) * 0.85
+        },
+        FragmentShader::Ring { inner, outer, .. } => {
+            let p = fragment.obj_position;
+            let r = (p.x*p.x + p.z*p.z).sqrt();
+            ring_density((r - *inner) / (*outer - *inner).max(1e-6))
+        },
+        // `d` del .mtl por el alfa del mapa difuso; con recorte queda opaco o descartado
+        FragmentShader::Pbr { material } => {
+            let sampler = Sampler { filter: Filter::Bilinear, wrap_u: Wrap::Repeat, wrap_v: Wrap::Repeat };
+            let map_alpha = material.albedo_map.as_ref().map_or(1.0, |t| t.sample(fragment.uv, fragment.uv_lod, &sampler).w);
+            let alpha = (material.opacity * map_alpha).clamp(0.0, 1.0);
+            match material.alpha_cutoff {
+                Some(cutoff) => if alpha >= cutoff { 1.0 } else { 0.0 },
+                None => alpha,
+            }
+        },
+        _ => 1.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uniforms::ShaderParams;
+
+    // Puntos repartidos sobre la esfera unidad; 203 no es múltiplo de LANES, así que
+    // también se ejercita la cola del último paquete.
+    fn sphere_fragments(count: usize) -> Vec<Fragment> {
+        let golden = PI * (3.0 - 5.0f32.sqrt());
+        (0..count)
+            .map(|i| {
+                let y = 1.0 - 2.0 * (i as f32 + 0.5) / count as f32;
+                let r = (1.0 - y * y).sqrt();
+                let theta = golden * i as f32;
+                let p = Vector3::new(r * theta.cos(), y, r * theta.sin());
+                Fragment::new(i as f32, 0.0, Vector3::new(1.0, 1.0, 1.0), 0.5, p).with_normal(p)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn max_displacement_bounds_the_shaders() {
+        let shaders = [
+            VertexShader::SolarFlare,
+            VertexShader::DisplacePlanarY { amp: 0.3, freq: 2.0, octaves: 4, lacunarity: 2.0, gain: 0.5, time_amp: 0.1 },
+            VertexShader::Volcanic { amp: 0.08, freq: 3.0 },
+            VertexShader::TerrainDisplace { seed: 7, amplitude: 0.06, frequency: 2.5, octaves: 5, crater_density: 6.0 },
+        ];
+        for shader in &shaders {
+            let bound = shader.max_displacement().unwrap();
+            for f in sphere_fragments(2000) {
+                for time in [0.0, 1.3, 57.0] {
+                    let moved = (apply_vertex_shader(f.obj_position, shader, time) - f.obj_position).length();
+                    assert!(moved <= bound + 1e-5, "moved {} past the bound {}", moved, bound);
+                }
             }
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn batched_shading_matches_scalar() {
+        // 203 = 50 paquetes y una cola de 3; la mitad sin normal interpolada, el resto inclinada
+        // como en un terreno desplazado, y con huellas UV distintas para elegir mip
+        let fragments: Vec<Fragment> = sphere_fragments(203)
+            .into_iter()
+            .enumerate()
+            .map(|(i, f)| {
+                let p = f.obj_position;
+                let f = if i % 2 == 0 { f.with_normal(Vector3::zero()) } else { f.with_normal((p + Vector3::new(0.05, -0.03, 0.02)).normalized()) };
+                f.with_uv(Vector2::zero(), -9.0 + (i % 5) as f32)
+            })
+            .collect();
+        assert_ne!(fragments.len() % LANES, 0);
+        let uniforms = Uniforms {
+            time: 3.7,
+            resolution: Vector2::new(1300.0, 600.0),
+            world: Matrix::identity(),
+            sun_dir: Vector3::new(0.6, 0.3, 0.74).normalized(),
+            camera_pos: Vector3::new(0.0, 0.0, 4.0),
+            lights: Vec::new(),
+            params: &ShaderParams::default(),
+        };
+
+        let rocky = FragmentShader::Rocky { color: Vector3::new(0.55, 0.48, 0.4) };
+        let baked = FragmentShader::Baked { source: Box::new(rocky.clone()), map: Arc::new(crate::bake::bake(&rocky, 128, 64).unwrap()) };
+        // Star y Rocky son exactos; el mapa horneado se consulta con UVs aproximadas
+        for (name, shader, tolerance) in [("Star", FragmentShader::Star, 0.0), ("Rocky", rocky, 0.0), ("Baked Rocky", baked, 1e-5)] {
+            let mut batched = Vec::new();
+            fragment_shader_batch(&fragments, &uniforms, &shader, &mut batched);
+            assert_eq!(batched.len(), fragments.len());
+            for (f, b) in fragments.iter().zip(&batched) {
+                let s = fragment_shader(f, &uniforms, &shader);
+                let diff = (s.x - b.x).abs().max((s.y - b.y).abs()).max((s.z - b.z).abs());
+                assert!(diff <= tolerance, "{} at {:?}: scalar {:?} vs batched {:?}", name, f.obj_position, s, b);
+            }
+        }
+    }
+}
@@ -0,0 +1,283 @@
+// src/shipgen.rs
+// Generador de naves con semilla: casco, alas, motores y cabina a partir de primitivas
+// paramétricas (torneado y prismas), con un id de material por triángulo.
+// La nave apunta a +Z (el `forward` de las entidades) con +Y arriba.
+use std::f32::consts::TAU;
+
+use raylib::prelude::*;
+
+use crate::material::Material;
+use crate::mesh::Mesh;
+use crate::obj::{Obj, SubMesh};
+use crate::procedural::Rng;
+
+pub const MAT_HULL: usize = 0;
+pub const MAT_TRIM: usize = 1;   // alas y aletas
+pub const MAT_ENGINE: usize = 2;
+pub const MAT_GLOW: usize = 3;   // toberas encendidas
+pub const MAT_GLASS: usize = 4;  // cabina
+const MATERIAL_COUNT: usize = 5;
+
+const SEGMENTS: usize = 14;
+
+pub struct GeneratedShip {
+    pub vertices: Vec<Vector3>,   // sopa de triángulos (3 por cara), como `ship_vertices`
+    pub material_ids: Vec<usize>, // un `MAT_*` por triángulo
+    pub materials: Vec<Material>, // indexado por `MAT_*`
+}
+
+#[derive(Default)]
+struct Builder {
+    vertices: Vec<Vector3>,
+    ids: Vec<usize>,
+}
+
+impl Builder {
+    fn tri(&mut self, a: Vector3, b: Vector3, c: Vector3, id: usize) {
+        // Las puntas de los perfiles torneados dejan triángulos sin área: se omiten
+        if (b - a).cross(c - a).length() <= 1e-9 {
+            return;
+        }
+        self.vertices.extend([a, b, c]);
+        self.ids.push(id);
+    }
+
+    /// Quad `a b c d` facing away from `inside`.
+    fn quad_outward(&mut self, [a, b, c, d]: [Vector3; 4], inside: Vector3, id: usize) {
+        let center = (a + b + c + d) / 4.0;
+        if (b - a).cross(c - a).dot(center - inside) >= 0.0 {
+            self.tri(a, b, c, id);
+            self.tri(a, c, d, id);
+        } else {
+            self.tri(a, c, b, id);
+            self.tri(a, d, c, id);
+        }
+    }
+
+    /// Surface of revolution around the Z axis: `profile` is (z, radius) from back to front,
+    /// the cross-section is an ellipse `radius` wide and `radius * squash` tall. Optional caps
+    /// close the first and last rings.
+    fn lathe(&mut self, profile: &[(f32, f32)], squash: f32, offset: Vector3, id: usize, caps: (Option<usize>, Option<usize>)) {
+        let ring = |(z, r): (f32, f32), j: usize| {
+            let a = TAU * j as f32 / SEGMENTS as f32;
+            offset + Vector3::new(r * a.cos(), r * a.sin() * squash, z)
+        };
+        for w in profile.windows(2) {
+            for j in 0..SEGMENTS {
+                let (a, b) = (ring(w[0], j), ring(w[0], j + 1));
+                let (c, d) = (ring(w[1], j + 1), ring(w[1], j));
+                self.tri(a, b, c, id);
+                self.tri(a, c, d, id);
+            }
+        }
+        if let (Some(cap), Some(&first)) = (caps.0, profile.first()) {
+            let center = offset + Vector3::new(0.0, 0.0, first.0);
+            for j in 0..SEGMENTS {
+                self.tri(center, ring(first, j + 1), ring(first, j), cap);
+            }
+        }
+        if let (Some(cap), Some(&last)) = (caps.1, profile.last()) {
+            let center = offset + Vector3::new(0.0, 0.0, last.0);
+            for j in 0..SEGMENTS {
+                self.tri(center, ring(last, j), ring(last, j + 1), cap);
+            }
+        }
+    }
+
+    /// Closed hexahedron from its bottom and top quads (same corner order), e.g. a wing panel.
+    fn prism(&mut self, bottom: [Vector3; 4], top: [Vector3; 4], id: usize) {
+        let inside = (bottom.iter().chain(top.iter()).fold(Vector3::new(0.0, 0.0, 0.0), |s, &v| s + v)) / 8.0;
+        self.quad_outward(bottom, inside, id);
+        self.quad_outward(top, inside, id);
+        for k in 0..4 {
+            let n = (k + 1) % 4;
+            self.quad_outward([bottom[k], bottom[n], top[n], top[k]], inside, id);
+        }
+    }
+}
+
+/// Builds the ship for `seed`: same seed, same ship.
+pub fn generate_ship(seed: u32) -> GeneratedShip {
+    let mut rng = Rng::new(seed ^ 0x5EED_5417);
+    let mut b = Builder::default();
+
+    // --- Casco: perfil torneado de la cola (-z) a la nariz (+z) ---
+    let length = rng.range(3.6, 5.5);
+    let radius = rng.range(0.45, 0.8);
+    let squash = rng.range(0.55, 0.95);
+    let tail_z = -length * 0.5;
+    let hull: Vec<(f32, f32)> = [
+        (0.0, rng.range(0.45, 0.7)),
+        (rng.range(0.15, 0.3), 1.0),
+        (rng.range(0.45, 0.6), rng.range(0.85, 1.0)),
+        (rng.range(0.75, 0.85), rng.range(0.45, 0.65)),
+        (rng.range(0.92, 0.97), rng.range(0.15, 0.3)),
+        (1.0, 0.0),
+    ]
+    .iter()
+    .map(|&(t, r)| (tail_z + t * length, r * radius))
+    .collect();
+    b.lathe(&hull, squash, Vector3::new(0.0, 0.0, 0.0), MAT_HULL, (Some(MAT_HULL), None));
+
+    // --- Cabina: elipsoide asomando sobre la parte delantera del casco ---
+    let cockpit_len = length * rng.range(0.14, 0.22);
+    let cockpit_w = radius * rng.range(0.4, 0.6);
+    let cockpit_z = tail_z + length * rng.range(0.58, 0.7);
+    let cockpit: Vec<(f32, f32)> = (0..=8)
+        .map(|k| {
+            let t = -1.0 + 2.0 * k as f32 / 8.0;
+            (t * cockpit_len, (1.0 - t * t).max(0.0).sqrt() * cockpit_w)
+        })
+        .collect();
+    let cockpit_at = Vector3::new(0.0, radius * squash * rng.range(0.55, 0.75), cockpit_z);
+    b.lathe(&cockpit, 0.75, cockpit_at, MAT_GLASS, (None, None));
+
+    // --- Alas: panel con flecha, diedro y estrechamiento, reflejado a ambos lados ---
+    let span = rng.range(1.4, 2.6);
+    let root_chord = length * rng.range(0.28, 0.42);
+    let tip_chord = root_chord * rng.range(0.2, 0.6);
+    let sweep = rng.range(-0.2, 0.9);
+    let dihedral = rng.range(-0.25, 0.35);
+    let thickness = rng.range(0.05, 0.12);
+    let wing_z = tail_z + length * rng.range(0.25, 0.4);
+    let wing_y = radius * squash * rng.range(-0.4, 0.2);
+    for side in [-1.0f32, 1.0] {
+        let root_x = side * radius * 0.7;
+        let tip_x = side * (radius + span);
+        let tip_y = wing_y + span * dihedral;
+        let tip_back = wing_z - sweep;
+        let corners = |dy: f32, taper: f32| {
+            [
+                Vector3::new(root_x, wing_y + dy, wing_z),
+                Vector3::new(root_x, wing_y + dy, wing_z + root_chord),
+                Vector3::new(tip_x, tip_y + dy * taper, tip_back + tip_chord),
+                Vector3::new(tip_x, tip_y + dy * taper, tip_back),
+            ]
+        };
+        b.prism(corners(-thickness, 0.5), corners(thickness, 0.5), MAT_TRIM);
+    }
+
+    // --- Aleta dorsal (no todas las naves la llevan) ---
+    if rng.unit() < 0.6 {
+        let fin_h = radius * rng.range(0.8, 1.6);
+        let fin_chord = length * rng.range(0.18, 0.3);
+        let base_y = radius * squash * 0.8;
+        let fin_back = rng.range(0.2, 0.6);
+        let t = 0.04;
+        let side = |x: f32| {
+            [
+                Vector3::new(x, base_y, tail_z + 0.1),
+                Vector3::new(x, base_y, tail_z + 0.1 + fin_chord),
+                Vector3::new(x * 0.5, base_y + fin_h, tail_z + 0.1 + fin_chord * 0.35 - fin_back),
+                Vector3::new(x * 0.5, base_y + fin_h, tail_z + 0.1 - fin_back),
+            ]
+        };
+        b.prism(side(-t), side(t), MAT_TRIM);
+    }
+
+    // --- Motores: góndolas torneadas en la cola con la tobera encendida ---
+    let engines = rng.pick(&[1usize, 2, 2, 3]);
+    let engine_r = radius * rng.range(0.28, 0.45);
+    let engine_len = length * rng.range(0.25, 0.4);
+    let spread = radius + engine_r * 0.6;
+    let positions: Vec<f32> = match engines {
+        1 => vec![0.0],
+        2 => vec![-spread, spread],
+        _ => vec![-spread, 0.0, spread],
+    };
+    for x in positions {
+        let y = if x == 0.0 { 0.0 } else { wing_y };
+        let back = tail_z - engine_len * 0.2;
+        let nacelle = [
+            (back, engine_r * 0.85),
+            (back + 0.08, engine_r),
+            (back + engine_len * 0.8, engine_r),
+            (back + engine_len, engine_r * 0.5),
+        ];
+        b.lathe(&nacelle, 1.0, Vector3::new(x, y, 0.0), MAT_ENGINE, (Some(MAT_GLOW), Some(MAT_ENGINE)));
+    }
+
+    GeneratedShip { vertices: b.vertices, material_ids: b.ids, materials: ship_materials(&mut rng) }
+}
+
+/// Seeded palette: hull paint, trim accent, bare metal, exhaust glow and cockpit glass.
+fn ship_materials(rng: &mut Rng) -> Vec<Material> {
+    let hull = rng.pick(&[
+        Vector3::new(0.78, 0.78, 0.8),
+        Vector3::new(0.9, 0.88, 0.82),
+        Vector3::new(0.3, 0.34, 0.42),
+        Vector3::new(0.42, 0.46, 0.32),
+        Vector3::new(0.55, 0.22, 0.16),
+    ]);
+    let trim = rng.pick(&[
+        Vector3::new(0.85, 0.55, 0.1),
+        Vector3::new(0.15, 0.35, 0.75),
+        Vector3::new(0.7, 0.1, 0.12),
+        Vector3::new(0.2, 0.2, 0.22),
+    ]);
+    let glow = rng.pick(&[Vector3::new(0.3, 0.8, 1.0), Vector3::new(1.0, 0.55, 0.15), Vector3::new(0.7, 0.4, 1.0)]);
+
+    let mut materials = vec![Material::default(); MATERIAL_COUNT];
+    materials[MAT_HULL] = Material { name: "Hull".into(), albedo: hull, metallic: 0.6, roughness: rng.range(0.3, 0.55), ..Material::default() };
+    materials[MAT_TRIM] = Material { name: "Trim".into(), albedo: trim, metallic: 0.3, roughness: 0.45, ..Material::default() };
+    materials[MAT_ENGINE] = Material { name: "Engine".into(), albedo: Vector3::new(0.35, 0.35, 0.37), metallic: 0.9, roughness: 0.3, ..Material::default() };
+    materials[MAT_GLOW] = Material { name: "Glow".into(), albedo: Vector3::new(0.05, 0.05, 0.05), emissive: glow, roughness: 0.9, ..Material::default() };
+    materials[MAT_GLASS] = Material { name: "Glass".into(), albedo: Vector3::new(0.05, 0.1, 0.16), metallic: 0.0, roughness: 0.08, specular: 0.08, ..Material::default() };
+    materials
+}
+
+impl GeneratedShip {
+    /// Same shape as an imported OBJ: one sub-mesh per material with smooth normals.
+    pub fn to_obj(&self) -> Obj {
+        let submeshes = (0..self.materials.len())
+            .filter_map(|id| {
+                let soup: Vec<Vector3> = self
+                    .material_ids
+                    .iter()
+                    .enumerate()
+                    .filter(|&(_, &m)| m == id)
+                    .flat_map(|(t, _)| self.vertices[3 * t..3 * t + 3].iter().copied())
+                    .collect();
+                if soup.is_empty() {
+                    return None;
+                }
+                let mesh = Mesh::from_soup_smooth(&soup, &[], crate::entity::CREASE_ANGLE);
+                Some(SubMesh { name: self.materials[id].name.clone(), mesh, material: Some(id) })
+            })
+            .collect();
+        Obj { submeshes, materials: self.materials.clone() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ship_is_seeded_and_well_formed() {
+        for seed in [0, 7, 1000, 0xDEAD_BEEF] {
+            let ship = generate_ship(seed);
+            assert_eq!(ship.vertices.len(), 3 * ship.material_ids.len());
+            assert_eq!(ship.materials.len(), MATERIAL_COUNT);
+            assert!(ship.material_ids.iter().all(|&id| id < MATERIAL_COUNT));
+            for tri in ship.vertices.chunks_exact(3) {
+                assert!((tri[1] - tri[0]).cross(tri[2] - tri[0]).length() > 1e-9, "degenerate triangle {:?}", tri);
+            }
+
+            let again = generate_ship(seed);
+            assert_eq!((&again.vertices, &again.material_ids), (&ship.vertices, &ship.material_ids));
+            let albedos = |s: &GeneratedShip| s.materials.iter().map(|m| m.albedo).collect::<Vec<_>>();
+            assert_eq!(albedos(&again), albedos(&ship));
+
+            // Casco, motores y toberas siempre están; una sub-malla por material usado
+            for id in [MAT_HULL, MAT_ENGINE, MAT_GLOW] {
+                assert!(ship.material_ids.contains(&id));
+            }
+            let mut used = ship.material_ids.clone();
+            used.sort_unstable();
+            used.dedup();
+            assert_eq!(ship.to_obj().submeshes.len(), used.len());
+        }
+        assert_ne!(generate_ship(1).vertices, generate_ship(2).vertices);
+    }
+}
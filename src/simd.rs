@@ -0,0 +1,357 @@
+// src/simd.rs
+// Tipos de 4 carriles (SoA) y versiones vectorizadas del ruido de `shaders.rs`.
+// Son arreglos alineados que LLVM convierte en SSE/NEON; cada función reproduce exactamente
+// las operaciones de su versión escalar, así que el resultado es idéntico carril a carril.
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use raylib::prelude::*;
+
+pub const LANES: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C, align(16))]
+pub struct F32x4(pub [f32; LANES]);
+
+impl F32x4 {
+    #[inline]
+    pub fn splat(v: f32) -> Self {
+        F32x4([v; LANES])
+    }
+
+    #[inline]
+    pub fn from_fn(f: impl FnMut(usize) -> f32) -> Self {
+        F32x4(std::array::from_fn(f))
+    }
+
+    #[inline]
+    pub fn map(self, f: impl Fn(f32) -> f32) -> Self {
+        F32x4([f(self.0[0]), f(self.0[1]), f(self.0[2]), f(self.0[3])])
+    }
+
+    #[inline]
+    fn zip(self, o: Self, f: impl Fn(f32, f32) -> f32) -> Self {
+        F32x4([f(self.0[0], o.0[0]), f(self.0[1], o.0[1]), f(self.0[2], o.0[2]), f(self.0[3], o.0[3])])
+    }
+
+    /// Floor sin llamada a libm (x86-64 base no tiene `roundps`); exacto para |x| < 2^22,
+    /// muy por encima de las coordenadas de ruido que se usan.
+    #[inline]
+    pub fn floor(self) -> Self {
+        const ROUND: f32 = 12582912.0; // 1.5·2^23
+        self.map(|v| {
+            let r = (v + ROUND) - ROUND;
+            if r > v { r - 1.0 } else { r }
+        })
+    }
+
+    #[inline]
+    pub fn sqrt(self) -> Self {
+        self.map(f32::sqrt)
+    }
+
+    /// Seno carril a carril con `f32::sin`, como el hash escalar: el hash amplifica cualquier
+    /// diferencia de redondeo, así que una aproximación polinómica cambiaría todo el ruido.
+    #[inline]
+    pub fn sin(self) -> Self {
+        self.map(f32::sin)
+    }
+
+    #[inline]
+    pub fn min(self, o: Self) -> Self {
+        self.zip(o, f32::min)
+    }
+
+    #[inline]
+    pub fn max(self, o: Self) -> Self {
+        self.zip(o, f32::max)
+    }
+
+    #[inline]
+    pub fn clamp(self, lo: f32, hi: f32) -> Self {
+        self.map(|v| v.clamp(lo, hi))
+    }
+
+    #[inline]
+    pub fn abs(self) -> Self {
+        self.map(f32::abs)
+    }
+
+    /// `a` where `mask` is true, `b` elsewhere.
+    #[inline]
+    pub fn select(mask: [bool; LANES], a: Self, b: Self) -> Self {
+        F32x4::from_fn(|i| if mask[i] { a.0[i] } else { b.0[i] })
+    }
+
+    #[inline]
+    pub fn lt(self, o: f32) -> [bool; LANES] {
+        self.0.map(|v| v < o)
+    }
+
+    #[inline]
+    pub fn gt(self, o: f32) -> [bool; LANES] {
+        self.0.map(|v| v > o)
+    }
+}
+
+macro_rules! lane_op {
+    ($tr:ident, $f:ident, $op:tt) => {
+        impl $tr for F32x4 {
+            type Output = F32x4;
+            #[inline]
+            fn $f(self, o: F32x4) -> F32x4 {
+                F32x4([self.0[0] $op o.0[0], self.0[1] $op o.0[1], self.0[2] $op o.0[2], self.0[3] $op o.0[3]])
+            }
+        }
+        impl $tr<f32> for F32x4 {
+            type Output = F32x4;
+            #[inline]
+            fn $f(self, o: f32) -> F32x4 {
+                F32x4([self.0[0] $op o, self.0[1] $op o, self.0[2] $op o, self.0[3] $op o])
+            }
+        }
+    };
+}
+
+lane_op!(Add, add, +);
+lane_op!(Sub, sub, -);
+lane_op!(Mul, mul, *);
+lane_op!(Div, div, /);
+
+impl Neg for F32x4 {
+    type Output = F32x4;
+    #[inline]
+    fn neg(self) -> F32x4 {
+        self.map(|v| -v)
+    }
+}
+
+/// Paquete de 4 vectores en SoA.
+#[derive(Clone, Copy, Debug)]
+pub struct Vec3x4 {
+    pub x: F32x4,
+    pub y: F32x4,
+    pub z: F32x4,
+}
+
+impl Vec3x4 {
+    #[inline]
+    pub fn new(x: F32x4, y: F32x4, z: F32x4) -> Self {
+        Vec3x4 { x, y, z }
+    }
+
+    #[inline]
+    pub fn splat(v: Vector3) -> Self {
+        Vec3x4 { x: F32x4::splat(v.x), y: F32x4::splat(v.y), z: F32x4::splat(v.z) }
+    }
+
+    /// Transposes 4 AoS vectors into one packet.
+    #[inline]
+    pub fn from_lanes(v: [Vector3; LANES]) -> Self {
+        Vec3x4 {
+            x: F32x4::from_fn(|i| v[i].x),
+            y: F32x4::from_fn(|i| v[i].y),
+            z: F32x4::from_fn(|i| v[i].z),
+        }
+    }
+
+    #[inline]
+    pub fn lane(&self, i: usize) -> Vector3 {
+        Vector3::new(self.x.0[i], self.y.0[i], self.z.0[i])
+    }
+
+    #[inline]
+    pub fn floor(self) -> Self {
+        Vec3x4 { x: self.x.floor(), y: self.y.floor(), z: self.z.floor() }
+    }
+
+    #[inline]
+    pub fn dot(self, o: Self) -> F32x4 {
+        self.x * o.x + self.y * o.y + self.z * o.z
+    }
+
+    #[inline]
+    pub fn offset(self, v: Vector3) -> Self {
+        Vec3x4 { x: self.x + v.x, y: self.y + v.y, z: self.z + v.z }
+    }
+}
+
+impl Add for Vec3x4 {
+    type Output = Vec3x4;
+    #[inline]
+    fn add(self, o: Vec3x4) -> Vec3x4 {
+        Vec3x4 { x: self.x + o.x, y: self.y + o.y, z: self.z + o.z }
+    }
+}
+
+impl Sub for Vec3x4 {
+    type Output = Vec3x4;
+    #[inline]
+    fn sub(self, o: Vec3x4) -> Vec3x4 {
+        Vec3x4 { x: self.x - o.x, y: self.y - o.y, z: self.z - o.z }
+    }
+}
+
+impl Mul<f32> for Vec3x4 {
+    type Output = Vec3x4;
+    #[inline]
+    fn mul(self, s: f32) -> Vec3x4 {
+        Vec3x4 { x: self.x * s, y: self.y * s, z: self.z * s }
+    }
+}
+
+impl Mul<F32x4> for Vec3x4 {
+    type Output = Vec3x4;
+    #[inline]
+    fn mul(self, s: F32x4) -> Vec3x4 {
+        Vec3x4 { x: self.x * s, y: self.y * s, z: self.z * s }
+    }
+}
+
+// --- Ruido vectorizado (mismas constantes y orden de operaciones que shaders.rs) ---
+
+#[inline]
+pub fn fract(x: F32x4) -> F32x4 {
+    x - x.floor()
+}
+
+#[inline]
+pub fn lerp(a: F32x4, b: F32x4, t: F32x4) -> F32x4 {
+    a + t * (b - a)
+}
+
+#[inline]
+fn fade(t: F32x4) -> F32x4 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+#[inline]
+pub fn smoothstep(e0: f32, e1: f32, x: F32x4) -> F32x4 {
+    let t = ((x - e0) / (e1 - e0)).clamp(0.0, 1.0);
+    t * t * (F32x4::splat(3.0) - t * 2.0)
+}
+
+#[inline]
+pub fn hash3(p: Vec3x4) -> F32x4 {
+    // Mismos f32 que 43758.5453 y 143758.5453 del hash escalar de shaders.rs.
+    let n = p.x * 127.1 + p.y * 311.7 + p.z * 74.7;
+    fract((n.sin() * 43_758.547).sin() * 143_758.55)
+}
+
+pub fn value_noise3(p: Vec3x4) -> F32x4 {
+    let i = p.floor();
+    let f = p - i;
+
+    let n000 = hash3(i);
+    let n100 = hash3(i.offset(Vector3::new(1.0, 0.0, 0.0)));
+    let n010 = hash3(i.offset(Vector3::new(0.0, 1.0, 0.0)));
+    let n110 = hash3(i.offset(Vector3::new(1.0, 1.0, 0.0)));
+    let n001 = hash3(i.offset(Vector3::new(0.0, 0.0, 1.0)));
+    let n101 = hash3(i.offset(Vector3::new(1.0, 0.0, 1.0)));
+    let n011 = hash3(i.offset(Vector3::new(0.0, 1.0, 1.0)));
+    let n111 = hash3(i.offset(Vector3::new(1.0, 1.0, 1.0)));
+
+    let ux = fade(f.x);
+    let uy = fade(f.y);
+    let uz = fade(f.z);
+
+    let nx00 = lerp(n000, n100, ux);
+    let nx10 = lerp(n010, n110, ux);
+    let nx01 = lerp(n001, n101, ux);
+    let nx11 = lerp(n011, n111, ux);
+
+    let nxy0 = lerp(nx00, nx10, uy);
+    let nxy1 = lerp(nx01, nx11, uy);
+
+    lerp(nxy0, nxy1, uz)
+}
+
+pub fn fbm(p: Vec3x4, octaves: i32, lacunarity: f32, gain: f32) -> F32x4 {
+    let mut amp = 0.5;
+    let mut freq = 1.0;
+    let mut sum = F32x4::splat(0.0);
+    for _ in 0..octaves {
+        sum = sum + value_noise3(p * freq) * amp;
+        freq *= lacunarity;
+        amp *= gain;
+    }
+    sum
+}
+
+/// Worley noise (F1, F2) for 4 points at once.
+pub fn worley3(p: Vec3x4) -> (F32x4, F32x4) {
+    let i = p.floor();
+    let mut f1 = F32x4::splat(f32::MAX);
+    let mut f2 = F32x4::splat(f32::MAX);
+    for z in -1..=1 {
+        for y in -1..=1 {
+            for x in -1..=1 {
+                let cell = i.offset(Vector3::new(x as f32, y as f32, z as f32));
+                let h = Vec3x4::new(
+                    hash3(cell),
+                    hash3(cell.offset(Vector3::new(19.19, 7.13, 3.71))),
+                    hash3(cell.offset(Vector3::new(5.37, 41.3, 13.9))),
+                );
+                let d = cell + h - p;
+                let dist = d.dot(d);
+                // Inserción ordenada sin saltos: f2 = min(f2, max(f1, d)), f1 = min(f1, d)
+                f2 = f2.min(f1.max(dist));
+                f1 = f1.min(dist);
+            }
+        }
+    }
+    (f1.sqrt(), f2.sqrt())
+}
+
+// --- Trigonometría inversa aproximada ---
+// A diferencia del ruido, no reproduce `f32::atan2`/`acos` bit a bit (error < 5e-7 rad): solo
+// sirve donde el resultado no se amplifica, como las coordenadas UV de un mapa.
+
+/// atan(x) for x ≥ 0 (Cephes `atanf`: reduction to |x| ≤ tan(π/8) and a degree-9 polynomial).
+fn atan_positive(x: F32x4) -> F32x4 {
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+    let big = x.gt(2.414_213_5); // tan(3π/8)
+    let mid = x.gt(0.414_213_57); // tan(π/8)
+    let offset = F32x4::select(big, F32x4::splat(FRAC_PI_2), F32x4::select(mid, F32x4::splat(FRAC_PI_4), F32x4::splat(0.0)));
+    let x = F32x4::select(big, -(F32x4::splat(1.0) / x), F32x4::select(mid, (x - 1.0) / (x + 1.0), x));
+    let z = x * x;
+    offset + (((z * 8.053_744_5e-2 - 1.387_768_6e-1) * z + 1.997_771_1e-1) * z - 3.333_295e-1) * z * x + x
+}
+
+/// Four-quadrant arctangent of `y / x`, 0 when both are 0.
+pub fn atan2(y: F32x4, x: F32x4) -> F32x4 {
+    let (ax, ay) = (x.abs(), y.abs());
+    let origin: [bool; LANES] = std::array::from_fn(|i| x.0[i] == 0.0 && y.0[i] == 0.0);
+    let a = atan_positive(F32x4::select(origin, F32x4::splat(0.0), ay / ax));
+    let a = F32x4::select(x.lt(0.0), F32x4::splat(std::f32::consts::PI) - a, a);
+    F32x4::select(y.lt(0.0), -a, a)
+}
+
+/// Arccosine of `x` clamped to [-1, 1].
+pub fn acos(x: F32x4) -> F32x4 {
+    let x = x.clamp(-1.0, 1.0);
+    atan2(((F32x4::splat(1.0) - x) * (x + 1.0)).max(F32x4::splat(0.0)).sqrt(), x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverse_trig_matches_libm() {
+        let mut worst = 0.0f32;
+        for i in 0..2000 {
+            let a = i as f32 * 0.0031 - 3.1;
+            let r = 0.5 + (i % 7) as f32;
+            let (y, x) = (F32x4::splat(r * a.sin()), F32x4::splat(r * a.cos()));
+            worst = worst.max((atan2(y, x).0[0] - y.0[0].atan2(x.0[0])).abs());
+            let c = i as f32 / 1000.0 - 1.0;
+            worst = worst.max((acos(F32x4::splat(c)).0[0] - c.acos()).abs());
+        }
+        assert!(worst < 5e-7, "max error {}", worst);
+        for (y, x) in [(0.0, 0.0), (0.0, 1.0), (0.0, -1.0), (1.0, 0.0), (-1.0, 0.0)] {
+            assert!((atan2(F32x4::splat(y), F32x4::splat(x)).0[0] - f32::atan2(y, x)).abs() < 1e-6, "atan2({}, {})", y, x);
+        }
+        assert_eq!(acos(F32x4::splat(1.0)).0[0], 0.0);
+        assert!((acos(F32x4::splat(-1.5)).0[0] - std::f32::consts::PI).abs() < 1e-6);
+    }
+}
@@ -1,7 +1,7 @@
 use std::{f32::consts::PI, time::Instant};
 
 use raylib::prelude::*;
-use crate::{framebuffer::Framebuffer, light::Light, matrix::{create_projection_matrix, create_viewport_matrix}, procedural::generate_uv_sphere, skybox, transform, triangle::{triangle, triangle_sky}, uniforms::{color_to_vec3, vec3_to_color}};
+use crate::{framebuffer::Framebuffer, light::Light, mesh::Mesh, matrix::{create_projection_matrix, create_viewport_matrix}, procedural::generate_uv_sphere, skybox, transform, triangle::{triangle, triangle_sky}, uniforms::{color_to_vec3, vec3_to_color}};
 
 // --- 3D Skybox / Nebula / Stars ---
 fn fract(x: f32) -> f32 { x - x.floor() }
@@ -125,20 +125,20 @@ fn sample_sky(dir: Vector3, time: f32) -> Color {
 }
 
 pub struct Skybox{
-    pub vertices: Vec<Vector3>,   // para la nebulosa
-    pub colors:   Vec<Vector3>,   // color nebula por vértice
+    pub mesh: Mesh,               // esfera de la nebulosa (indexada)
+    pub colors:   Vec<Vector3>,   // color nebula por vértice único de `mesh`
 
     pub star_dirs: Vec<Vector3>,  // direcciones unitarias de estrellas
     pub star_brightness: Vec<f32> // brillo de cada estrell
 }
 impl Skybox {
     pub fn new() -> Self { 
-        let vertices = generate_uv_sphere(10000.0, 200, 200);
+        let mesh = Mesh::from_soup(&generate_uv_sphere(10000.0, 200, 200), &[], &[]);
 
         // Nebulosa precomputada
         let mut colors = Vec::new();
         let time = Instant::now().elapsed().as_secs_f32();
-        for vtx in vertices.iter() {
+        for vtx in mesh.positions.iter() {
             // sample_sky SOLO debería devolver nebulosa ahora
             colors.push(color_to_vec3(sample_sky(*vtx, time)));
         }
@@ -167,7 +167,7 @@ impl Skybox {
         }
 
         Skybox {
-            vertices,
+            mesh,
             colors,
             star_dirs,
             star_brightness,
@@ -177,28 +177,24 @@ impl Skybox {
 
 
 pub fn draw_sky_sphere(framebuffer: &mut Framebuffer, skybox: &Skybox, view: &Matrix, viewport: &Matrix, projection: &Matrix){
-    let mut transformed_vertices = Vec::with_capacity(skybox.vertices.len());
-    for (vtx, col) in skybox.vertices.iter().zip(skybox.colors.iter()) {
-        let tv = transform(vtx.clone(), Vector3::new(0.0, 0.0, 0.0), 1.0, Vector3::new(0.0, 0.0, 0.0), &view, &projection, &viewport);
+    // Cada vértice único se proyecta una vez; los triángulos lo leen por índice
+    let mut transformed_vertices = Vec::with_capacity(skybox.mesh.positions.len());
+    for vtx in skybox.mesh.positions.iter() {
+        let tv = transform(*vtx, Vector3::new(0.0, 0.0, 0.0), 1.0, Vector3::new(0.0, 0.0, 0.0), &view, &projection, &viewport);
         transformed_vertices.push(tv);
-
-        
     }
      // Primitive Assembly Stage
     let mut fragcols = Vec::new();
     let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 >= transformed_vertices.len() {
-            break;
-        }
-
+    for tri in skybox.mesh.indices.chunks_exact(3) {
+        let (i0, i1, i2) = (tri[0] as usize, tri[1] as usize, tri[2] as usize);
         if let (Some(v0), Some(v1), Some(v2)) = (
-            transformed_vertices[i],
-            transformed_vertices[i + 1],
-            transformed_vertices[i + 2],
+            transformed_vertices[i0],
+            transformed_vertices[i1],
+            transformed_vertices[i2],
         ) {
             triangles.push([v0, v1, v2]);
-            fragcols.push([skybox.colors[i], skybox.colors[i+1], skybox.colors[i+2]]);
+            fragcols.push([skybox.colors[i0], skybox.colors[i1], skybox.colors[i2]]);
         }
     }
 
@@ -0,0 +1,374 @@
+// src/texture.rs
+// Texturas en CPU: carga vía raylib `Image`, cadena de mipmaps y muestreo con filtrado.
+use raylib::prelude::*;
+
+use crate::simd::{F32x4, LANES};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Filter {
+    Nearest,
+    Bilinear,
+    Trilinear,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Wrap {
+    Repeat,
+    Clamp,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Sampler {
+    pub filter: Filter,
+    pub wrap_u: Wrap,
+    pub wrap_v: Wrap,
+}
+
+impl Sampler {
+    /// Equirectangular maps wrap around in longitude and clamp at the poles.
+    pub fn equirectangular(filter: Filter) -> Self {
+        Sampler { filter, wrap_u: Wrap::Repeat, wrap_v: Wrap::Clamp }
+    }
+}
+
+#[derive(Debug)]
+pub enum TextureError {
+    Load { path: String, message: String },
+    Empty { path: String },
+}
+
+impl std::fmt::Display for TextureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TextureError::Load { path, message } => write!(f, "failed to load texture '{}': {}", path, message),
+            TextureError::Empty { path } => write!(f, "texture '{}' has no pixels", path),
+        }
+    }
+}
+
+impl std::error::Error for TextureError {}
+
+/// Un nivel de la cadena de mipmaps; texels RGBA en 0..1, fila por fila.
+pub struct MipLevel {
+    pub width: usize,
+    pub height: usize,
+    pub texels: Vec<Vector4>,
+}
+
+impl MipLevel {
+    #[inline]
+    fn texel(&self, x: usize, y: usize) -> Vector4 {
+        self.texels[y * self.width + x]
+    }
+}
+
+/// Textura con su cadena de mipmaps completa (nivel 0 = resolución original).
+pub struct Texture {
+    pub levels: Vec<MipLevel>,
+}
+
+#[inline]
+fn mix4(a: Vector4, b: Vector4, t: f32) -> Vector4 {
+    Vector4::new(
+        a.x + (b.x - a.x) * t,
+        a.y + (b.y - a.y) * t,
+        a.z + (b.z - a.z) * t,
+        a.w + (b.w - a.w) * t,
+    )
+}
+
+#[inline]
+fn wrap_index(i: i32, size: usize, wrap: Wrap) -> usize {
+    match wrap {
+        Wrap::Repeat => i.rem_euclid(size as i32) as usize,
+        Wrap::Clamp => i.clamp(0, size as i32 - 1) as usize,
+    }
+}
+
+#[inline]
+fn wrap_coord(t: f32, wrap: Wrap) -> f32 {
+    match wrap {
+        Wrap::Repeat => t - t.floor(),
+        Wrap::Clamp => t.clamp(0.0, 1.0),
+    }
+}
+
+impl Texture {
+    /// Loads a PNG/JPG (anything raylib's `LoadImage` understands) and builds its mip chain.
+    pub fn load(path: &str) -> Result<Self, TextureError> {
+        let image = Image::load_image(path).map_err(|e| TextureError::Load {
+            path: path.to_string(),
+            message: e.to_string(),
+        })?;
+        Texture::from_image(&image, path)
+    }
+
+    /// Decodes an encoded image held in memory (e.g. embedded in a GLB). `file_type` is the
+    /// extension raylib uses to pick the decoder (".png", ".jpg"); `label` names it in errors.
+    pub fn load_from_memory(bytes: &[u8], file_type: &str, label: &str) -> Result<Self, TextureError> {
+        let image = Image::load_image_from_mem(file_type, bytes).map_err(|e| TextureError::Load {
+            path: label.to_string(),
+            message: e.to_string(),
+        })?;
+        Texture::from_image(&image, label)
+    }
+
+    fn from_image(image: &Image, path: &str) -> Result<Self, TextureError> {
+        let width = image.width().max(0) as usize;
+        let height = image.height().max(0) as usize;
+        if width == 0 || height == 0 {
+            return Err(TextureError::Empty { path: path.to_string() });
+        }
+
+        let colors = image.get_image_data();
+        let texels = colors
+            .iter()
+            .map(|c| Vector4::new(c.r as f32 / 255.0, c.g as f32 / 255.0, c.b as f32 / 255.0, c.a as f32 / 255.0))
+            .collect();
+
+        Ok(Texture::from_texels(width, height, texels))
+    }
+
+    /// Builds a texture (and its mip chain) from raw RGBA texels in row-major order.
+    pub fn from_texels(width: usize, height: usize, texels: Vec<Vector4>) -> Self {
+        assert_eq!(texels.len(), width * height, "texel count does not match {}x{}", width, height);
+        let mut levels = vec![MipLevel { width, height, texels }];
+
+        // Cada nivel promedia bloques 2x2 del anterior hasta llegar a 1x1
+        while let Some(prev) = levels.last() {
+            if prev.width == 1 && prev.height == 1 {
+                break;
+            }
+            let w = (prev.width / 2).max(1);
+            let h = (prev.height / 2).max(1);
+            let mut texels = Vec::with_capacity(w * h);
+            for y in 0..h {
+                for x in 0..w {
+                    let x0 = (x * 2).min(prev.width - 1);
+                    let x1 = (x * 2 + 1).min(prev.width - 1);
+                    let y0 = (y * 2).min(prev.height - 1);
+                    let y1 = (y * 2 + 1).min(prev.height - 1);
+                    let a = prev.texel(x0, y0);
+                    let b = prev.texel(x1, y0);
+                    let c = prev.texel(x0, y1);
+                    let d = prev.texel(x1, y1);
+                    texels.push(Vector4::new(
+                        (a.x + b.x + c.x + d.x) * 0.25,
+                        (a.y + b.y + c.y + d.y) * 0.25,
+                        (a.z + b.z + c.z + d.z) * 0.25,
+                        (a.w + b.w + c.w + d.w) * 0.25,
+                    ));
+                }
+            }
+            levels.push(MipLevel { width: w, height: h, texels });
+        }
+
+        Texture { levels }
+    }
+
+    pub fn width(&self) -> usize {
+        self.levels[0].width
+    }
+
+    pub fn height(&self) -> usize {
+        self.levels[0].height
+    }
+
+    /// Mip level for a fragment whose UV footprint is `uv_lod` (log2 of UV units per pixel,
+    /// see `Fragment::uv_lod`).
+    pub fn mip_level(&self, uv_lod: f32) -> f32 {
+        let size = (self.width() * self.height()) as f32;
+        (uv_lod + 0.5 * size.log2()).clamp(0.0, (self.levels.len() - 1) as f32)
+    }
+
+    fn sample_nearest(&self, level: usize, uv: Vector2, sampler: &Sampler) -> Vector4 {
+        let mip = &self.levels[level];
+        let u = wrap_coord(uv.x, sampler.wrap_u);
+        let v = wrap_coord(uv.y, sampler.wrap_v);
+        let x = wrap_index((u * mip.width as f32).floor() as i32, mip.width, sampler.wrap_u);
+        let y = wrap_index((v * mip.height as f32).floor() as i32, mip.height, sampler.wrap_v);
+        mip.texel(x, y)
+    }
+
+    fn sample_bilinear(&self, level: usize, uv: Vector2, sampler: &Sampler) -> Vector4 {
+        let mip = &self.levels[level];
+        // Centros de texel en (i + 0.5) / size
+        let fx = wrap_coord(uv.x, sampler.wrap_u) * mip.width as f32 - 0.5;
+        let fy = wrap_coord(uv.y, sampler.wrap_v) * mip.height as f32 - 0.5;
+        let x0 = fx.floor();
+        let y0 = fy.floor();
+        let tx = fx - x0;
+        let ty = fy - y0;
+
+        let xa = wrap_index(x0 as i32, mip.width, sampler.wrap_u);
+        let xb = wrap_index(x0 as i32 + 1, mip.width, sampler.wrap_u);
+        let ya = wrap_index(y0 as i32, mip.height, sampler.wrap_v);
+        let yb = wrap_index(y0 as i32 + 1, mip.height, sampler.wrap_v);
+
+        let top = mix4(mip.texel(xa, ya), mip.texel(xb, ya), tx);
+        let bottom = mix4(mip.texel(xa, yb), mip.texel(xb, yb), tx);
+        mix4(top, bottom, ty)
+    }
+
+    /// Bilinear `sample` of 4 lookups at once (one per fragment of a packet): mip selection,
+    /// wrapping and filter weights run as packets, only the texel fetches go lane by lane.
+    /// Gives exactly what `sample` gives with `Filter::Bilinear`.
+    pub fn sample_bilinear_x4(&self, u: F32x4, v: F32x4, uv_lod: [f32; LANES], sampler: &Sampler) -> [Vector4; LANES] {
+        let levels = uv_lod.map(|lod| self.mip_level(lod).round() as usize);
+        let width = F32x4::from_fn(|i| self.levels[levels[i]].width as f32);
+        let height = F32x4::from_fn(|i| self.levels[levels[i]].height as f32);
+        let wrap = |t: F32x4, mode: Wrap| match mode {
+            Wrap::Repeat => t - t.floor(),
+            Wrap::Clamp => t.clamp(0.0, 1.0),
+        };
+
+        // Centros de texel en (i + 0.5) / size
+        let fx = wrap(u, sampler.wrap_u) * width - 0.5;
+        let fy = wrap(v, sampler.wrap_v) * height - 0.5;
+        let (x0, y0) = (fx.floor(), fy.floor());
+        let (tx, ty) = (fx - x0, fy - y0);
+
+        std::array::from_fn(|i| {
+            let mip = &self.levels[levels[i]];
+            let (x0, y0) = (x0.0[i] as i32, y0.0[i] as i32);
+            let xa = wrap_index(x0, mip.width, sampler.wrap_u);
+            let xb = wrap_index(x0 + 1, mip.width, sampler.wrap_u);
+            let ya = wrap_index(y0, mip.height, sampler.wrap_v);
+            let yb = wrap_index(y0 + 1, mip.height, sampler.wrap_v);
+
+            let top = mix4(mip.texel(xa, ya), mip.texel(xb, ya), tx.0[i]);
+            let bottom = mix4(mip.texel(xa, yb), mip.texel(xb, yb), tx.0[i]);
+            mix4(top, bottom, ty.0[i])
+        })
+    }
+
+    /// Samples the texture at `uv` with the given sampler. `uv_lod` selects the mip level:
+    /// the nearest one for `Nearest` and `Bilinear`, a blend of the two around it for `Trilinear`.
+    pub fn sample(&self, uv: Vector2, uv_lod: f32, sampler: &Sampler) -> Vector4 {
+        match sampler.filter {
+            Filter::Nearest => {
+                let level = self.mip_level(uv_lod).round() as usize;
+                self.sample_nearest(level, uv, sampler)
+            }
+            Filter::Bilinear => {
+                let level = self.mip_level(uv_lod).round() as usize;
+                self.sample_bilinear(level, uv, sampler)
+            }
+            Filter::Trilinear => {
+                let lod = self.mip_level(uv_lod);
+                let l0 = lod.floor() as usize;
+                let l1 = (l0 + 1).min(self.levels.len() - 1);
+                let a = self.sample_bilinear(l0, uv, sampler);
+                if l1 == l0 {
+                    return a;
+                }
+                let b = self.sample_bilinear(l1, uv, sampler);
+                mix4(a, b, lod - l0 as f32)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gray(v: f32) -> Vector4 {
+        Vector4::new(v, v, v, 1.0)
+    }
+
+    fn sampler(filter: Filter, wrap: Wrap) -> Sampler {
+        Sampler { filter, wrap_u: wrap, wrap_v: wrap }
+    }
+
+    fn close(a: Vector4, v: f32) -> bool {
+        (a.x - v).abs() < 1e-5 && (a.w - 1.0).abs() < 1e-5
+    }
+
+    // Tablero de ajedrez negro/blanco de `w`x`h`
+    fn checker(w: usize, h: usize) -> Texture {
+        Texture::from_texels(w, h, (0..w * h).map(|i| gray(((i % w + i / w) % 2) as f32)).collect())
+    }
+
+    #[test]
+    fn mip_chain_halves_down_to_one_texel() {
+        let sizes = |t: &Texture| t.levels.iter().map(|l| (l.width, l.height)).collect::<Vec<_>>();
+        assert_eq!(sizes(&checker(8, 4)), vec![(8, 4), (4, 2), (2, 1), (1, 1)]);
+        assert_eq!(sizes(&checker(5, 3)), vec![(5, 3), (2, 1), (1, 1)]);
+        assert_eq!(sizes(&checker(1, 1)), vec![(1, 1)]);
+
+        // Cada nivel promedia 2x2: el tablero se vuelve gris
+        let t = checker(4, 4);
+        assert!(t.levels[1].texels.iter().all(|&c| close(c, 0.5)));
+
+        // Un texel de textura por píxel → nivel 0; dos por píxel → nivel 1
+        let t = checker(8, 8);
+        assert_eq!(t.mip_level((1.0f32 / 8.0).log2()), 0.0);
+        assert_eq!(t.mip_level((2.0f32 / 8.0).log2()), 1.0);
+        assert_eq!(t.mip_level(10.0), 3.0);
+        assert_eq!(t.mip_level(-10.0), 0.0);
+    }
+
+    #[test]
+    fn nearest_and_bilinear_sampling() {
+        let ramp = Texture::from_texels(2, 1, vec![gray(0.0), gray(1.0)]);
+        let at = |u: f32, s: Sampler| ramp.sample(Vector2::new(u, 0.5), -16.0, &s);
+
+        let nearest = sampler(Filter::Nearest, Wrap::Clamp);
+        assert!(close(at(0.3, nearest), 0.0) && close(at(0.7, nearest), 1.0));
+
+        // Centros de texel en 0.25 y 0.75; en medio se mezclan
+        let bilinear = sampler(Filter::Bilinear, Wrap::Clamp);
+        assert!(close(at(0.25, bilinear), 0.0));
+        assert!(close(at(0.5, bilinear), 0.5));
+        assert!(close(at(0.625, bilinear), 0.75));
+
+        // Con una huella grande, `Nearest` también baja de nivel
+        let t = checker(4, 4);
+        assert!(close(t.sample(Vector2::new(0.1, 0.1), (2.0f32 / 4.0).log2(), &nearest), 0.5));
+        assert!(close(t.sample(Vector2::new(0.1, 0.1), -16.0, &nearest), 0.0));
+    }
+
+    #[test]
+    fn trilinear_blends_neighbouring_levels() {
+        let t = checker(4, 4);
+        let trilinear = sampler(Filter::Trilinear, Wrap::Repeat);
+        let uv = Vector2::new(0.125, 0.125); // centro de un texel negro
+        let lod = |level: f32| level - 0.5 * 16.0f32.log2();
+        assert!(close(t.sample(uv, lod(0.0), &trilinear), 0.0));
+        assert!(close(t.sample(uv, lod(1.0), &trilinear), 0.5));
+        assert!(close(t.sample(uv, lod(0.5), &trilinear), 0.25));
+    }
+
+    #[test]
+    fn repeat_wraps_and_clamp_holds_the_edge() {
+        let ramp = Texture::from_texels(2, 1, vec![gray(0.0), gray(1.0)]);
+        let at = |u: f32, filter: Filter, wrap: Wrap| ramp.sample(Vector2::new(u, 0.5), -16.0, &sampler(filter, wrap));
+
+        assert!(close(at(1.25, Filter::Nearest, Wrap::Repeat), 0.0));
+        assert!(close(at(-0.25, Filter::Nearest, Wrap::Repeat), 1.0));
+        assert!(close(at(1.25, Filter::Nearest, Wrap::Clamp), 1.0));
+        assert!(close(at(-0.25, Filter::Nearest, Wrap::Clamp), 0.0));
+
+        // En el borde, `Repeat` mezcla con el texel del lado opuesto y `Clamp` no
+        assert!(close(at(0.0, Filter::Bilinear, Wrap::Repeat), 0.5));
+        assert!(close(at(0.0, Filter::Bilinear, Wrap::Clamp), 0.0));
+        assert!(close(at(1.0, Filter::Bilinear, Wrap::Clamp), 1.0));
+    }
+
+    #[test]
+    fn packet_bilinear_matches_sample() {
+        let texture = Texture::from_texels(16, 8, (0..128).map(|i| Vector4::new((i % 16) as f32 / 15.0, (i / 16) as f32 / 7.0, (i % 5) as f32 * 0.2, 1.0)).collect());
+        let sampler = Sampler::equirectangular(Filter::Bilinear);
+        for k in 0..64 {
+            let t = k as f32;
+            let u = F32x4::from_fn(|i| t * 0.173 - 3.0 + i as f32 * 0.31);
+            let v = F32x4::from_fn(|i| t * 0.029 - 0.4 + i as f32 * 0.07);
+            let lod = std::array::from_fn(|i| -7.0 + (k + i) as f32 % 6.0);
+            let packet = texture.sample_bilinear_x4(u, v, lod, &sampler);
+            for i in 0..LANES {
+                assert_eq!(packet[i], texture.sample(Vector2::new(u.0[i], v.0[i]), lod[i], &sampler), "lane {} of lookup {}", i, k);
+            }
+        }
+    }
+}
@@ -25,8 +25,33 @@ fn barycentric_coordinates(p_x: f32, p_y: f32, a: &Vector3, b: &Vector3, c: &Vec
     (w, v, u)
 }
 
-pub fn triangle(v1: &Vector3, v2: &Vector3, v3: &Vector3, obj1: &Vector3, obj2: &Vector3, obj3: &Vector3, light: &Light) -> Vec<Fragment> {
+/// One corner as the rasterizer sees it: screen position (x, y, depth) plus the object-space
+/// attributes interpolated across the face.
+#[derive(Clone, Copy)]
+pub struct Vertex {
+    pub screen: Vector3,
+    pub obj: Vector3,    // posición en espacio objeto (tras el vertex shader)
+    pub normal: Vector3, // espacio objeto
+    pub uv: Vector2,
+}
+
+/// log2 of UV units per screen pixel for a whole triangle (ratio of UV area to screen area).
+fn uv_footprint(v1: &Vector3, v2: &Vector3, v3: &Vector3, uv1: &Vector2, uv2: &Vector2, uv3: &Vector2) -> f32 {
+    let area_px = ((v2.x - v1.x) * (v3.y - v1.y) - (v3.x - v1.x) * (v2.y - v1.y)).abs();
+    let area_uv = ((uv2.x - uv1.x) * (uv3.y - uv1.y) - (uv3.x - uv1.x) * (uv2.y - uv1.y)).abs();
+    if area_px <= 1e-10 || area_uv <= 1e-12 {
+        return -16.0;
+    }
+    0.5 * (area_uv / area_px).log2()
+}
+
+pub fn triangle(a: &Vertex, b: &Vertex, c: &Vertex, light: &Light) -> Vec<Fragment> {
+    let (v1, v2, v3) = (&a.screen, &b.screen, &c.screen);
+    let (obj1, obj2, obj3) = (&a.obj, &b.obj, &c.obj);
+    let (n1, n2, n3) = (&a.normal, &b.normal, &c.normal);
+    let (uv1, uv2, uv3) = (&a.uv, &b.uv, &c.uv);
     let mut fragments: Vec<Fragment> = Vec::new();
+    let uv_lod = uv_footprint(v1, v2, v3, uv1, uv2, uv3);
 
     let a_x = v1.x;
     let b_x = v2.x;
@@ -62,13 +87,21 @@ pub fn triangle(v1: &Vector3, v2: &Vector3, v3: &Vector3, obj1: &Vector3, obj2:
             let obj_pos = Vector3::new(ox, oy, oz);
 
             if w >= 0.0 && v >= 0.0 && u >= 0.0 {
+                let uv = Vector2::new(
+                    uv1.x*w + uv2.x*v + uv3.x*u,
+                    uv1.y*w + uv2.y*v + uv3.y*u,
+                );
+                let mut normal = *n1*w + *n2*v + *n3*u;
+                if normal.length() > 0.0 {
+                    normal = normal.normalized();
+                }
                 fragments.push(Fragment::new(
                     x as f32,
                     y as f32,
                     final_color,
                     depth,
                     obj_pos,
-                ));
+                ).with_normal(normal).with_uv(uv, uv_lod));
             }
         }
     }
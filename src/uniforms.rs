@@ -1,11 +1,47 @@
 // uniforms.rs (si quieres en un archivo aparte) o al inicio de tu shader.rs
 use raylib::prelude::*;
 
-pub struct Uniforms {
+/// Luz de la escena vista desde un objeto: dirección hacia ella (espacio objeto) y radiancia.
+pub struct ObjLight {
+    pub dir: Vector3,
+    pub radiance: Vector3,
+}
+
+/// Parámetros propios de una entidad (p. ej. `temp`/`intensity` del Sol), por nombre.
+#[derive(Clone, Default)]
+pub struct ShaderParams {
+    values: Vec<(String, f32)>,
+}
+
+impl ShaderParams {
+    pub fn get(&self, name: &str) -> Option<f32> {
+        self.values.iter().find(|(n, _)| n == name).map(|(_, v)| *v)
+    }
+
+    pub fn set(&mut self, name: &str, value: f32) {
+        match self.values.iter_mut().find(|(n, _)| n == name) {
+            Some(entry) => entry.1 = value,
+            None => self.values.push((name.to_string(), value)),
+        }
+    }
+}
+
+/// Bloque de uniforms de una entidad para un frame; toma prestados los parámetros de la entidad.
+pub struct Uniforms<'a> {
     pub time: f32,         // segundos
     pub resolution: Vector2, // tamaño ventana en píxeles
-    pub temp: f32,
-    pub intensity: f32,
+    pub world: Matrix,       // matriz de mundo de la entidad (objeto → mundo)
+    pub sun_dir: Vector3,    // dirección hacia el Sol en espacio objeto (normalizada)
+    pub camera_pos: Vector3, // posición de la cámara en espacio objeto
+    pub lights: Vec<ObjLight>, // luces de la escena en espacio objeto
+    pub params: &'a ShaderParams, // parámetros propios de la entidad
+}
+
+impl Uniforms<'_> {
+    /// Custom parameter `name`, or `default` when the entity does not define it.
+    pub fn param(&self, name: &str, default: f32) -> f32 {
+        self.params.get(name).unwrap_or(default)
+    }
 }
 
 // Convierte Color (0..255) a vec3 0..1